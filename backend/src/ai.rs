@@ -0,0 +1,370 @@
+use crate::book::Book;
+use crate::connect4::{Board, Cell, State};
+use std::collections::HashMap;
+
+// Score of a win on the very first move; later wins score a little less, so sooner wins are preferred
+pub const WIN: i32 = 1_000_000;
+// How far ahead the computer player looks when the opening book runs out
+pub const DEFAULT_DEPTH: u32 = 10;
+// Forget the transposition table once it gets this big, to keep memory bounded on long runs
+const MAX_TABLE_SIZE: usize = 4_000_000;
+
+// A two-player Connect Four position packed into bitboards, for fast search.
+// Each column takes rows + 1 bits (the extra bit is a sentinel that keeps columns apart),
+// with bit 0 of a column being its bottom cell.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    current: u64, // discs of the player to move
+    mask: u64,    // all discs
+    moves: usize,
+    rows: usize,
+    cols: usize,
+    bottom: u64,     // bottom cell of every column
+    board_mask: u64, // every playable cell
+}
+
+impl Position {
+    pub fn new(rows: usize, cols: usize) -> Option<Position> {
+        if rows == 0 || cols == 0 || (rows + 1) * cols > 64 {
+            return None;
+        }
+        let bottom = (0..cols).fold(0u64, |bits, col| bits | 1 << (col * (rows + 1)));
+        Some(Position {
+            current: 0,
+            mask: 0,
+            moves: 0,
+            rows,
+            cols,
+            bottom,
+            board_mask: bottom * ((1 << rows) - 1),
+        })
+    }
+
+    // Only running two-player games on boards that fit in 64 bits can be searched
+    pub fn from_board(board: &Board) -> Option<Position> {
+        if board.turn_order.len() != 2 || board.state != State::Running {
+            return None;
+        }
+        let mut pos = Position::new(board.rows, board.cols)?;
+        for (row, line) in board.grid.iter().enumerate() {
+            for (col, cell) in line.iter().enumerate() {
+                if let Cell::Occupied(player) = *cell {
+                    let bit = 1u64 << (col * (board.rows + 1) + board.rows - 1 - row);
+                    pos.mask |= bit;
+                    pos.moves += 1;
+                    if player == board.current_turn {
+                        pos.current |= bit;
+                    }
+                }
+            }
+        }
+        Some(pos)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    // Uniquely identifies the position for a given board size
+    pub fn key(&self) -> u64 {
+        self.current + self.mask
+    }
+
+    pub fn can_play(&self, col: usize) -> bool {
+        col < self.cols && self.mask & self.top_cell(col) == 0
+    }
+
+    // Drop a disc for the player to move; the column must not be full
+    pub fn play(&mut self, col: usize) {
+        self.current ^= self.mask;
+        self.mask |= self.mask + self.bottom_cell(col);
+        self.moves += 1;
+    }
+
+    pub fn is_winning_move(&self, col: usize) -> bool {
+        self.winning_cells(self.current) & self.possible() & self.column(col) != 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.moves == self.rows * self.cols
+    }
+
+    // Columns from the centre outwards, which is usually the best order to try moves in
+    pub fn move_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.cols).collect();
+        order.sort_by_key(|&col| (2 * col as isize - self.cols as isize + 1).abs());
+        order
+    }
+
+    // The cells each move would fill
+    fn possible(&self) -> u64 {
+        (self.mask + self.bottom) & self.board_mask
+    }
+
+    // Empty cells that would complete a line of four for `discs`
+    fn winning_cells(&self, discs: u64) -> u64 {
+        let h = self.rows + 1;
+        // vertical
+        let mut cells = (discs << 1) & (discs << 2) & (discs << 3);
+        // horizontal and both diagonals
+        for shift in [h, h - 1, h + 1] {
+            let mut pair = (discs << shift) & (discs << (2 * shift));
+            cells |= pair & (discs << (3 * shift));
+            cells |= pair & (discs >> shift);
+            pair = (discs >> shift) & (discs >> (2 * shift));
+            cells |= pair & (discs << shift);
+            cells |= pair & (discs >> (3 * shift));
+        }
+        cells & (self.board_mask ^ self.mask)
+    }
+
+    fn column(&self, col: usize) -> u64 {
+        ((1u64 << self.rows) - 1) << (col * (self.rows + 1))
+    }
+
+    fn bottom_cell(&self, col: usize) -> u64 {
+        1u64 << (col * (self.rows + 1))
+    }
+
+    fn top_cell(&self, col: usize) -> u64 {
+        1u64 << (self.rows - 1 + col * (self.rows + 1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+// Depth-limited negamax with alpha-beta pruning and a transposition table.
+// Wins and losses within the search horizon are found exactly; beyond it positions are
+// scored by counting open threats.
+#[derive(Default)]
+pub struct Solver {
+    table: HashMap<u64, (u32, i32, Bound)>, // key -> (depth searched, score, bound)
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    // Best column for the player to move, looking `depth` plies ahead
+    pub fn best_move(&mut self, pos: &Position, depth: u32) -> Option<usize> {
+        if self.table.len() > MAX_TABLE_SIZE {
+            self.table.clear();
+        }
+        let mut best = None;
+        let mut alpha = -2 * WIN;
+        for col in pos.move_order() {
+            if !pos.can_play(col) {
+                continue;
+            }
+            if pos.is_winning_move(col) {
+                return Some(col);
+            }
+            let mut next = *pos;
+            next.play(col);
+            let score = -self.negamax(&next, depth.saturating_sub(1), -2 * WIN, -alpha);
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(col);
+            }
+        }
+        best
+    }
+
+    // The score of every playable column for the player to move, looking `depth` plies ahead.
+    // Slower than best_move, which can skip moves it knows are no better than one it has.
+    pub fn score_moves(&mut self, pos: &Position, depth: u32) -> Vec<(usize, i32)> {
+        if self.table.len() > MAX_TABLE_SIZE {
+            self.table.clear();
+        }
+        (0..pos.cols)
+            .filter(|&col| pos.can_play(col))
+            .map(|col| {
+                if pos.is_winning_move(col) {
+                    return (col, WIN - pos.moves as i32);
+                }
+                let mut next = *pos;
+                next.play(col);
+                (col, -self.negamax(&next, depth.saturating_sub(1), -2 * WIN, 2 * WIN))
+            })
+            .collect()
+    }
+
+    fn negamax(&mut self, pos: &Position, depth: u32, mut alpha: i32, mut beta: i32) -> i32 {
+        let possible = pos.possible();
+        // the player to move wins right away
+        if pos.winning_cells(pos.current) & possible != 0 {
+            return WIN - pos.moves as i32;
+        }
+        if pos.is_full() {
+            return 0;
+        }
+
+        // moves that do not hand the opponent a win, either directly or by filling the cell below one
+        let opponent_wins = pos.winning_cells(pos.current ^ pos.mask);
+        let forced = opponent_wins & possible;
+        let mut candidates = possible & !(opponent_wins >> 1);
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                // two threats at once cannot both be blocked
+                return -(WIN - pos.moves as i32 - 1);
+            }
+            candidates &= forced;
+        }
+        if candidates == 0 {
+            return -(WIN - pos.moves as i32 - 1);
+        }
+        if depth == 0 {
+            return self.evaluate(pos);
+        }
+
+        let key = pos.key();
+        if let Some(&(searched, score, bound)) = self.table.get(&key) {
+            if searched >= depth {
+                match bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return score;
+                }
+            }
+        }
+
+        let alpha_start = alpha;
+        let mut best = -2 * WIN;
+        for col in pos.move_order() {
+            if candidates & pos.column(col) == 0 {
+                continue;
+            }
+            let mut next = *pos;
+            next.play(col);
+            let score = -self.negamax(&next, depth - 1, -beta, -alpha);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= alpha_start {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, (depth, best, bound));
+        best
+    }
+
+    // Heuristic score for the player to move: open threats count most, then centre control
+    fn evaluate(&self, pos: &Position) -> i32 {
+        let opponent = pos.current ^ pos.mask;
+        let threats = pos.winning_cells(pos.current).count_ones() as i32
+            - pos.winning_cells(opponent).count_ones() as i32;
+        let centre = pos.column(pos.cols / 2);
+        let centre_discs = (pos.current & centre).count_ones() as i32 - (opponent & centre).count_ones() as i32;
+        threats * 10 + centre_discs * 3
+    }
+}
+
+// The computer player: the opening book first, then search
+pub struct Ai {
+    book: Book,
+    solver: Solver,
+    depth: u32,
+}
+
+impl Ai {
+    pub fn new(depth: u32) -> Ai {
+        Ai {
+            book: Book::bundled(),
+            solver: Solver::new(),
+            depth,
+        }
+    }
+
+    // The column the computer wants to play, if the position can be searched
+    pub fn choose(&mut self, board: &Board) -> Option<usize> {
+        let pos = Position::from_board(board)?;
+        self.book
+            .lookup(&pos)
+            .or_else(|| self.solver.best_move(&pos, self.depth))
+    }
+
+    // Make the computer's move, falling back to a random column for boards it cannot search
+    pub fn play(&mut self, board: &mut Board) -> Result<usize, &'static str> {
+        match self.choose(board) {
+            Some(col) => board.insert_disc(col).map(|_| col),
+            None => board.computer_move(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connect4::Player;
+
+    fn position(moves: &[usize]) -> Position {
+        let mut board = Board::new(6, 7);
+        for &col in moves {
+            board.insert_disc(col).unwrap();
+        }
+        Position::from_board(&board).unwrap()
+    }
+
+    #[test]
+    fn boards_too_big_for_64_bits_are_refused() {
+        assert!(Position::new(6, 7).is_some());
+        assert!(Position::new(7, 9).is_none());
+        assert!(Position::new(8, 10).is_none());
+    }
+
+    #[test]
+    fn the_solver_takes_a_win_in_one() {
+        // Red has three in column 1, Yellow has two in column 2 and one in column 7
+        let pos = position(&[0, 1, 0, 1, 0, 6]);
+        assert!(pos.is_winning_move(0));
+        for depth in [1, 4, 8] {
+            assert_eq!(Solver::new().best_move(&pos, depth), Some(0));
+        }
+    }
+
+    #[test]
+    fn the_solver_blocks_a_loss_in_one() {
+        // Yellow to move has to stop Red's three in column 1
+        let pos = position(&[0, 1, 0, 1, 0]);
+        for depth in [1, 4, 8] {
+            assert_eq!(Solver::new().best_move(&pos, depth), Some(0));
+        }
+        let scores = Solver::new().score_moves(&pos, 4);
+        let block = scores.iter().find(|&&(col, _)| col == 0).unwrap().1;
+        assert!(scores.iter().all(|&(col, score)| col == 0 || score < block));
+    }
+
+    #[test]
+    fn the_computer_follows_the_book_then_searches() {
+        let mut ai = Ai::new(4);
+        let board = Board::new(6, 7);
+        assert_eq!(ai.choose(&board), Book::bundled().lookup(&Position::new(6, 7).unwrap()));
+        // three or more players can't be searched
+        let board = Board::with_players(6, 7, vec![Player::Red, Player::Yellow, Player::Green]).unwrap();
+        assert_eq!(ai.choose(&board), None);
+    }
+}
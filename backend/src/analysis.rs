@@ -0,0 +1,63 @@
+use crate::ai::{Position, Solver, WIN};
+use crate::history::Replay;
+use serde::Serialize;
+
+// How far the engine looks ahead from every position of a game under review
+const ANALYSIS_DEPTH: u32 = 8;
+
+// A position's value for the player about to move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Eval {
+    Wins { moves: usize },  // the winning disc is this many discs away, counting the move itself
+    Loses { moves: usize }, // the same for the opponent's winning disc
+    Score(i32),             // no forced result within the horizon; positive is better for the mover
+}
+
+// What the engine thinks of one move of a game
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MoveReview {
+    pub best: usize, // the column the engine would have played
+    pub best_eval: Eval,
+    pub played_eval: Eval,
+}
+
+fn eval(score: i32, played: usize) -> Eval {
+    // win scores count down from WIN by the number of discs on the board when the game ends
+    let distance = |score: i32| (WIN - score.abs()) as usize + 1 - played;
+    match score {
+        s if s > WIN / 2 => Eval::Wins { moves: distance(s) },
+        s if s < -WIN / 2 => Eval::Loses { moves: distance(s) },
+        s => Eval::Score(s),
+    }
+}
+
+// The engine's review of every move of a two-player Connect Four game, oldest first
+pub fn review(replay: &Replay) -> Result<Vec<MoveReview>, &'static str> {
+    let Replay::ConnectFour { rows, cols, turn_order, moves } = replay else {
+        return Err("Only Connect Four games can be analysed");
+    };
+    if turn_order.len() != 2 {
+        return Err("Only two-player games can be analysed");
+    }
+    let mut pos = Position::new(*rows, *cols).ok_or("The board is too big to analyse")?;
+    let mut solver = Solver::new();
+    let mut reviews = Vec::with_capacity(moves.len());
+    for &(_, col) in moves {
+        if !pos.can_play(col) {
+            return Err("The replay has a move that can't be played");
+        }
+        let scores = solver.score_moves(&pos, ANALYSIS_DEPTH);
+        let &(best, best_score) = scores.iter().max_by_key(|&&(_, score)| score).ok_or("The replay goes on after the board is full")?;
+        let played_score = scores.iter().find(|&&(c, _)| c == col).map_or(best_score, |&(_, score)| score);
+        reviews.push(MoveReview {
+            best,
+            best_eval: eval(best_score, pos.moves()),
+            played_eval: eval(played_score, pos.moves()),
+        });
+        if pos.is_winning_move(col) {
+            break;
+        }
+        pos.play(col);
+    }
+    Ok(reviews)
+}
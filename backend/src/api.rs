@@ -0,0 +1,119 @@
+use crate::analysis::{self, MoveReview};
+use crate::history::{GameRecord, HistoryStore, Replay};
+use crate::online::{self, GameSummary, GameView, Joined, Lobby, Move, NewGame, Spectating, Viewer};
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Status};
+use rocket::response::status::BadRequest;
+use rocket::serde::json::Json;
+use rocket::{get, options, post, routes, Request, Response, State};
+
+// Lets the web app, which trunk serves from another port, call the API from the browser
+pub struct Cors;
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info { name: "CORS headers", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+        response.set_header(Header::new("Access-Control-Allow-Methods", "GET, POST, OPTIONS"));
+        response.set_header(Header::new("Access-Control-Allow-Headers", "Content-Type"));
+    }
+}
+
+// Answer the browser's CORS preflight for any path; the fairing adds the headers
+#[options("/<_..>")]
+fn preflight() -> Status {
+    Status::NoContent
+}
+
+#[get("/api/history")]
+fn list_history(history: &State<HistoryStore>) -> Json<Vec<GameRecord>> {
+    Json(history.games())
+}
+
+#[post("/api/history", data = "<record>")]
+fn add_history(record: Json<GameRecord>, history: &State<HistoryStore>) -> Result<Status, BadRequest<&'static str>> {
+    history.add(record.into_inner()).map_err(BadRequest)?;
+    Ok(Status::Created)
+}
+
+// The engine's opinion of every move of a finished game. Searching takes a while, so it runs off the async workers.
+#[post("/api/analysis", data = "<replay>")]
+async fn analyse_game(replay: Json<Replay>) -> Result<Json<Vec<MoveReview>>, BadRequest<&'static str>> {
+    let replay = replay.into_inner();
+    rocket::tokio::task::spawn_blocking(move || analysis::review(&replay))
+        .await
+        .map_err(|_| BadRequest("The analysis failed"))?
+        .map(Json)
+        .map_err(BadRequest)
+}
+
+// Unknown games are 404s; everything else a player can get wrong is a conflict with the game's state
+fn rejected(reason: &'static str) -> (Status, &'static str) {
+    if reason == online::NO_SUCH_GAME {
+        (Status::NotFound, reason)
+    } else {
+        (Status::Conflict, reason)
+    }
+}
+
+#[post("/api/games", data = "<new>")]
+fn create_game(new: Json<NewGame>, lobby: &State<Lobby>) -> Result<Json<Joined>, BadRequest<&'static str>> {
+    lobby.create(new.into_inner()).map(Json).map_err(BadRequest)
+}
+
+#[post("/api/games/<code>/join")]
+fn join_game(code: &str, lobby: &State<Lobby>) -> Result<Json<Joined>, (Status, &'static str)> {
+    lobby.join(code).map(Json).map_err(rejected)
+}
+
+#[get("/api/games")]
+fn list_games(lobby: &State<Lobby>) -> Json<Vec<GameSummary>> {
+    Json(lobby.active())
+}
+
+#[post("/api/games/<code>/spectate")]
+fn spectate_game(code: &str, lobby: &State<Lobby>) -> Result<Json<Spectating>, (Status, &'static str)> {
+    lobby.spectate(code).map(Json).map_err(rejected)
+}
+
+// Long poll: answers as soon as the game is newer than `since`, or after a while with no news.
+// Players send their seat's token and spectators their id, so the others can see who's there.
+#[get("/api/games/<code>?<since>&<token>&<spectator>")]
+async fn watch_game(code: &str, since: Option<u64>, token: Option<&str>, spectator: Option<&str>, lobby: &State<Lobby>) -> Result<Json<GameView>, (Status, &'static str)> {
+    let viewer = match (token, spectator) {
+        (Some(token), _) => Viewer::Player(token),
+        (None, Some(spectator)) => Viewer::Spectator(spectator),
+        (None, None) => Viewer::Anonymous,
+    };
+    match since {
+        Some(since) => lobby.watch(code, since, viewer).await,
+        None => lobby.view(code, viewer),
+    }
+    .map(Json)
+    .map_err(rejected)
+}
+
+#[post("/api/games/<code>/move", data = "<mv>")]
+fn play_move(code: &str, mv: Json<Move>, lobby: &State<Lobby>) -> Result<Json<GameView>, (Status, &'static str)> {
+    lobby.play(code, mv.into_inner()).map(Json).map_err(rejected)
+}
+
+// Serve the web app's API until the process is stopped. The port comes from Rocket.toml.
+pub fn serve(history: HistoryStore) -> Result<(), Box<rocket::Error>> {
+    rocket::execute(async move {
+        rocket::build()
+            .attach(Cors)
+            .manage(history)
+            .manage(Lobby::new())
+            .mount("/", routes![preflight, list_history, add_history, analyse_game, create_game, join_game, list_games, spectate_game, watch_game, play_move])
+            .launch()
+            .await
+            .map(drop)
+            .map_err(Box::new)
+    })
+}
@@ -0,0 +1,185 @@
+use crate::ai::{Position, Solver};
+use std::collections::BTreeMap;
+
+// Book file layout: the magic bytes, a version byte, the board rows and columns, then one
+// little-endian u64 per position sorted by key. The low 56 bits of an entry hold the position
+// key and the top byte holds the best reply's column, so a 6x7 book costs 8 bytes a position.
+const MAGIC: &[u8; 4] = b"C4BK";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 7;
+const KEY_MASK: u64 = (1 << 56) - 1;
+
+// The book shipped with the engine, built with `backend gen-book`
+const BUNDLED: &[u8] = include_bytes!("../book/connect4.book");
+
+// Precomputed best replies for the opening of one board size
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    rows: usize,
+    cols: usize,
+    entries: Vec<u64>,
+}
+
+impl Book {
+    // The bundled 6x7 book; an unreadable book just means the engine searches every move
+    pub fn bundled() -> Book {
+        Book::from_bytes(BUNDLED).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, &'static str> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err("Not an opening book");
+        }
+        if bytes[4] != VERSION {
+            return Err("Unsupported opening book version");
+        }
+        let body = &bytes[HEADER_LEN..];
+        if !body.len().is_multiple_of(8) {
+            return Err("Truncated opening book");
+        }
+        let entries: Vec<u64> = body
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunks are 8 bytes")))
+            .collect();
+        if entries.windows(2).any(|pair| pair[0] & KEY_MASK >= pair[1] & KEY_MASK) {
+            return Err("Opening book entries are not sorted");
+        }
+        Ok(Book {
+            rows: bytes[5] as usize,
+            cols: bytes[6] as usize,
+            entries,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 8 * self.entries.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[VERSION, self.rows as u8, self.cols as u8]);
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // The book move for a position, if the book covers it
+    pub fn lookup(&self, pos: &Position) -> Option<usize> {
+        if pos.rows() != self.rows || pos.cols() != self.cols {
+            return None;
+        }
+        let i = self
+            .entries
+            .binary_search_by_key(&pos.key(), |entry| entry & KEY_MASK)
+            .ok()?;
+        Some((self.entries[i] >> 56) as usize)
+    }
+
+    // Build a book for the first `plies` moves by searching `depth` plies deep.
+    // For each side the book plays its own best move and answers every reply of the opponent,
+    // so it covers every position the computer can reach while it follows the book.
+    pub fn generate(rows: usize, cols: usize, plies: usize, depth: u32) -> Result<Book, &'static str> {
+        let start = Position::new(rows, cols).ok_or("The board is too big for the solver")?;
+        let mut solver = Solver::new();
+        let mut entries = BTreeMap::new();
+        for book_side in 0..2 {
+            explore(&start, book_side, plies, depth, &mut solver, &mut entries);
+        }
+        Ok(Book {
+            rows,
+            cols,
+            entries: entries.into_iter().map(|(key, col)| key | (col as u64) << 56).collect(),
+        })
+    }
+}
+
+fn explore(
+    pos: &Position,
+    book_side: usize,
+    plies: usize,
+    depth: u32,
+    solver: &mut Solver,
+    entries: &mut BTreeMap<u64, usize>,
+) {
+    if pos.moves() >= plies || pos.is_full() {
+        return;
+    }
+    let cols: Vec<usize> = if pos.moves() % 2 == book_side {
+        let Some(col) = solver.best_move(pos, depth) else {
+            return;
+        };
+        entries.insert(pos.key(), col);
+        vec![col]
+    } else {
+        (0..pos.cols()).filter(|&col| pos.can_play(col)).collect()
+    };
+
+    for col in cols {
+        // a winning move ends the game, so there is nothing to look up after it
+        if pos.is_winning_move(col) {
+            continue;
+        }
+        let mut next = *pos;
+        next.play(col);
+        explore(&next, book_side, plies, depth, solver, entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(entries: Vec<u64>) -> Book {
+        Book { rows: 6, cols: 7, entries }
+    }
+
+    #[test]
+    fn books_survive_a_round_trip() {
+        let original = book(vec![1 | 3 << 56, 42 | 2 << 56, KEY_MASK | 6 << 56]);
+        let read = Book::from_bytes(&original.to_bytes()).unwrap();
+        assert_eq!((read.rows, read.cols), (6, 7));
+        assert_eq!(read.entries, original.entries);
+
+        assert_eq!(Book::bundled().to_bytes(), BUNDLED);
+    }
+
+    #[test]
+    fn bad_books_are_rejected() {
+        let bytes = book(vec![1 | 3 << 56, 2 | 4 << 56]).to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(Book::from_bytes(&bad_magic).unwrap_err(), "Not an opening book");
+        assert_eq!(Book::from_bytes(&bytes[..3]).unwrap_err(), "Not an opening book");
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = VERSION + 1;
+        assert_eq!(Book::from_bytes(&bad_version).unwrap_err(), "Unsupported opening book version");
+
+        assert_eq!(Book::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), "Truncated opening book");
+
+        let unsorted = book(vec![2 | 4 << 56, 1 | 3 << 56]).to_bytes();
+        assert_eq!(Book::from_bytes(&unsorted).unwrap_err(), "Opening book entries are not sorted");
+        // the reply column isn't part of the key, so two replies for one position are out of order too
+        let duplicate = book(vec![1 | 3 << 56, 1 | 4 << 56]).to_bytes();
+        assert!(Book::from_bytes(&duplicate).is_err());
+    }
+
+    #[test]
+    fn the_bundled_book_knows_the_opening() {
+        let book = Book::bundled();
+        assert!(book.len() > 0);
+        let start = Position::new(6, 7).unwrap();
+        let col = book.lookup(&start).expect("the empty board is in the book");
+        assert!(start.can_play(col));
+
+        let mut reply = start;
+        reply.play(col);
+        assert!(book.lookup(&reply).is_some(), "the book answers every first move of the opponent");
+
+        // other board sizes aren't covered
+        assert_eq!(book.lookup(&Position::new(5, 6).unwrap()), None);
+    }
+}
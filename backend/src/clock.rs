@@ -0,0 +1,282 @@
+use serde::{Serialize, Deserialize};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Where the clocks get the current time from. Games never read the time themselves,
+// so anything that counts time (the system clock, a browser, a fake in tests) can drive them.
+pub trait TimeSource {
+    // Time elapsed since the Unix epoch
+    fn now(&self) -> Duration;
+}
+
+// The real wall clock
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+// Chess-style time control: every player starts with `initial_ms` and gains `increment_ms` per move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub initial_ms: u64,
+    pub increment_ms: u64,
+}
+
+// The time controls games can be played with: at least a second each and at most a day, with up to an hour a move
+const MIN_INITIAL_MS: u64 = 1_000;
+const MAX_INITIAL_MS: u64 = 24 * 60 * 60 * 1000;
+const MAX_INCREMENT_MS: u64 = 60 * 60 * 1000;
+
+impl TimeControl {
+    // Whether the control is one games can be played with. Online games get theirs from the client, so
+    // nothing can be assumed about them.
+    pub fn check(&self) -> Result<(), &'static str> {
+        if !(MIN_INITIAL_MS..=MAX_INITIAL_MS).contains(&self.initial_ms) {
+            return Err("The initial time must be between 1 second and 24 hours");
+        }
+        if self.increment_ms > MAX_INCREMENT_MS {
+            return Err("The increment can be at most an hour");
+        }
+        Ok(())
+    }
+}
+
+// Parses "minutes+increment seconds", e.g. "5+3" or just "10"
+impl FromStr for TimeControl {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (minutes, increment) = match s.trim().split_once('+') {
+            Some((minutes, increment)) => (minutes, increment),
+            None => (s.trim(), "0"),
+        };
+        let minutes: f64 = minutes.trim().parse().map_err(|_| "Invalid number of minutes")?;
+        let increment: u64 = increment.trim().parse().map_err(|_| "Invalid increment")?;
+        // NaN and infinity parse as numbers too
+        if !(minutes.is_finite() && minutes > 0.0) {
+            return Err("The initial time must be positive");
+        }
+        // the cast saturates, so a huge number of minutes is caught by check like any other
+        let control = TimeControl {
+            initial_ms: (minutes * 60_000.0) as u64,
+            increment_ms: increment.checked_mul(1000).ok_or("Invalid increment")?,
+        };
+        control.check()?;
+        Ok(control)
+    }
+}
+
+// One clock per player. Only the player to move has their clock running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameClock<P> {
+    pub control: TimeControl,
+    players: Vec<P>,
+    remaining_ms: Vec<u64>,
+    running: Option<P>,
+    turn_started_ms: u64, // When the running clock was last started, in ms since the epoch
+}
+
+impl<P: Copy + PartialEq> GameClock<P> {
+    pub fn new(control: TimeControl, players: &[P]) -> GameClock<P> {
+        GameClock {
+            control,
+            players: players.to_vec(),
+            remaining_ms: vec![control.initial_ms; players.len()],
+            running: None,
+            turn_started_ms: 0,
+        }
+    }
+
+    // Start the clock of the player to move
+    pub fn start(&mut self, player: P, time: &impl TimeSource) {
+        self.running = Some(player);
+        self.turn_started_ms = time.now().as_millis() as u64;
+    }
+
+    // The running player has moved: charge their thinking time, add the increment and start `next`
+    pub fn press(&mut self, next: P, time: &impl TimeSource) {
+        if let Some(mover) = self.running {
+            let now = time.now().as_millis() as u64;
+            if let Some(i) = self.index(mover) {
+                let spent = now.saturating_sub(self.turn_started_ms);
+                self.remaining_ms[i] = self.remaining_ms[i].saturating_sub(spent).saturating_add(self.control.increment_ms);
+            }
+        }
+        self.start(next, time);
+    }
+
+    // Freeze all clocks, e.g. when the game is over
+    pub fn stop(&mut self, time: &impl TimeSource) {
+        if let Some(player) = self.running {
+            let left = self.remaining(player, time);
+            if let Some(i) = self.index(player) {
+                self.remaining_ms[i] = left.as_millis() as u64;
+            }
+        }
+        self.running = None;
+    }
+
+    // Time left for a player, counting the thinking time of a running clock
+    pub fn remaining(&self, player: P, time: &impl TimeSource) -> Duration {
+        let Some(i) = self.index(player) else {
+            return Duration::ZERO;
+        };
+        let mut left = self.remaining_ms[i];
+        if self.running == Some(player) {
+            let spent = (time.now().as_millis() as u64).saturating_sub(self.turn_started_ms);
+            left = left.saturating_sub(spent);
+        }
+        Duration::from_millis(left)
+    }
+
+    // The player whose clock is running, if any
+    pub fn running(&self) -> Option<P> {
+        self.running
+    }
+
+    // The player whose time has run out, if any
+    pub fn flagged(&self, time: &impl TimeSource) -> Option<P> {
+        self.running.filter(|&player| self.remaining(player, time).is_zero())
+    }
+
+    fn index(&self, player: P) -> Option<usize> {
+        self.players.iter().position(|&p| p == player)
+    }
+}
+
+// Formats a remaining time as m:ss, with tenths of a second in the last ten seconds
+pub fn format_remaining(time: Duration) -> String {
+    let ms = time.as_millis();
+    if ms < 10_000 {
+        format!("0:{:02}.{}", ms / 1000, (ms % 1000) / 100)
+    } else {
+        format!("{}:{:02}", ms / 60_000, (ms / 1000) % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // A clock that only moves when the test says so
+    struct ManualTimeSource {
+        now: Cell<Duration>,
+    }
+
+    impl ManualTimeSource {
+        fn new() -> ManualTimeSource {
+            ManualTimeSource { now: Cell::new(Duration::from_secs(1_000_000)) }
+        }
+
+        fn advance(&self, ms: u64) {
+            self.now.set(self.now.get() + Duration::from_millis(ms));
+        }
+    }
+
+    impl TimeSource for ManualTimeSource {
+        fn now(&self) -> Duration {
+            self.now.get()
+        }
+    }
+
+    const FIVE_PLUS_THREE: TimeControl = TimeControl { initial_ms: 300_000, increment_ms: 3_000 };
+
+    fn left(clock: &GameClock<u8>, player: u8, time: &ManualTimeSource) -> u64 {
+        clock.remaining(player, time).as_millis() as u64
+    }
+
+    #[test]
+    fn only_the_running_clock_counts_down() {
+        let time = ManualTimeSource::new();
+        let mut clock = GameClock::new(FIVE_PLUS_THREE, &[0, 1]);
+        clock.start(0, &time);
+        time.advance(10_000);
+        assert_eq!(left(&clock, 0, &time), 290_000);
+        assert_eq!(left(&clock, 1, &time), 300_000);
+        assert_eq!(clock.running(), Some(0));
+    }
+
+    #[test]
+    fn press_charges_the_mover_and_adds_the_increment() {
+        let time = ManualTimeSource::new();
+        let mut clock = GameClock::new(FIVE_PLUS_THREE, &[0, 1]);
+        clock.start(0, &time);
+        time.advance(10_000);
+        clock.press(1, &time);
+        assert_eq!(left(&clock, 0, &time), 293_000);
+        assert_eq!(clock.running(), Some(1));
+
+        time.advance(1_000);
+        assert_eq!(left(&clock, 0, &time), 293_000);
+        assert_eq!(left(&clock, 1, &time), 299_000);
+    }
+
+    #[test]
+    fn stop_freezes_every_clock() {
+        let time = ManualTimeSource::new();
+        let mut clock = GameClock::new(FIVE_PLUS_THREE, &[0, 1]);
+        clock.start(1, &time);
+        time.advance(4_000);
+        clock.stop(&time);
+        time.advance(60_000);
+        assert_eq!(left(&clock, 1, &time), 296_000);
+        assert_eq!(clock.running(), None);
+        assert_eq!(clock.flagged(&time), None);
+    }
+
+    #[test]
+    fn a_player_out_of_time_is_flagged() {
+        let time = ManualTimeSource::new();
+        let mut clock = GameClock::new(TimeControl { initial_ms: 60_000, increment_ms: 0 }, &[0, 1, 2]);
+        clock.start(2, &time);
+        time.advance(59_999);
+        assert_eq!(clock.flagged(&time), None);
+        time.advance(1);
+        assert_eq!(clock.flagged(&time), Some(2));
+        assert_eq!(left(&clock, 2, &time), 0);
+        // the time can't go below zero however long they take
+        time.advance(5_000);
+        assert_eq!(left(&clock, 2, &time), 0);
+    }
+
+    #[test]
+    fn a_huge_increment_does_not_overflow() {
+        let time = ManualTimeSource::new();
+        let control = TimeControl { initial_ms: u64::MAX, increment_ms: u64::MAX };
+        let mut clock = GameClock::new(control, &[0, 1]);
+        clock.start(0, &time);
+        time.advance(1_000);
+        clock.press(1, &time);
+        assert_eq!(left(&clock, 0, &time), u64::MAX);
+        assert_eq!(clock.flagged(&time), None);
+    }
+
+    #[test]
+    fn time_controls_parse_minutes_and_increment() {
+        assert_eq!("5+3".parse(), Ok(FIVE_PLUS_THREE));
+        assert_eq!(" 10 ".parse(), Ok(TimeControl { initial_ms: 600_000, increment_ms: 0 }));
+        assert_eq!("0.5+0".parse(), Ok(TimeControl { initial_ms: 30_000, increment_ms: 0 }));
+    }
+
+    #[test]
+    fn bad_time_controls_are_errors() {
+        for bad in ["", "x", "0", "-1+2", "nan+0", "inf", "5+x", "5+-1", "5+18446744073709551615"] {
+            assert!(bad.parse::<TimeControl>().is_err(), "{:?} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn time_controls_out_of_bounds_are_errors() {
+        // 1e300 minutes would saturate to u64::MAX ms, and 0.00001 minutes rounds down to 0 ms
+        for bad in ["1e300+3", "0.00001", "1441", "5+3601"] {
+            assert!(bad.parse::<TimeControl>().is_err(), "{:?} should not parse", bad);
+        }
+        assert!("1440+3600".parse::<TimeControl>().is_ok());
+        assert!(TimeControl { initial_ms: 0, increment_ms: 0 }.check().is_err());
+        assert!(TimeControl { initial_ms: u64::MAX, increment_ms: 1 }.check().is_err());
+    }
+}
@@ -5,6 +5,18 @@ use rand::Rng; // Import the Rng trait to use random number generation
 pub enum Player {
     Red,
     Yellow,
+    Green,
+    Blue,
+}
+
+impl Player {
+    // Every player colour in seating order, used to build turn orders for 3-4 player games
+    pub const ALL: [Player; 4] = [Player::Red, Player::Yellow, Player::Green, Player::Blue];
+}
+
+// Two-player games (and saves made before multi-player support) go Red then Yellow
+fn default_turn_order() -> Vec<Player> {
+    vec![Player::Red, Player::Yellow]
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -15,12 +27,16 @@ pub enum Cell {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Board {
-    grid: Vec<Vec<Cell>>,
+    pub grid: Vec<Vec<Cell>>,
     pub current_turn: Player,
     pub rows: usize,
     pub cols: usize,
     pub state: State,
-    last_move: Option<(usize, usize)>, // Track the last move as (row, col)
+    pub last_move: Option<(usize, usize)>, // Track the last move as (row, col)
+    #[serde(default = "default_turn_order")]
+    pub turn_order: Vec<Player>, // Players in the order they take turns
+    #[serde(default)]
+    pub moves: Vec<(Player, usize)>, // Every move so far as (player, column), oldest first
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -31,7 +47,7 @@ pub enum State {
 }
 
 impl Board {
-    // Initialize a new game board
+    // Initialize a new two-player game board
     pub fn new(rows: usize, cols: usize) -> Board {
         Board {
             grid: vec![vec![Cell::Empty; cols]; rows],
@@ -40,40 +56,81 @@ impl Board {
             cols,
             state: State::Running,
             last_move: None,
+            turn_order: default_turn_order(),
+            moves: Vec::new(),
+        }
+    }
+
+    // Initialize a board for any number of players, who move in the given order
+    pub fn with_players(rows: usize, cols: usize, turn_order: Vec<Player>) -> Result<Board, &'static str> {
+        if turn_order.len() < 2 {
+            return Err("At least two players are needed");
+        }
+        if turn_order.iter().enumerate().any(|(i, p)| turn_order[..i].contains(p)) {
+            return Err("A player can only appear once in the turn order");
+        }
+
+        let mut board = Board::new(rows, cols);
+        board.current_turn = turn_order[0];
+        board.turn_order = turn_order;
+        Ok(board)
+    }
+
+    // Suggested board size for a number of players, so 3-4 player games have room to move
+    pub fn dimensions_for(players: usize) -> (usize, usize) {
+        match players {
+            0..=2 => (6, 7),
+            3 => (7, 9),
+            _ => (8, 10),
         }
     }
 
-    // Display the current state of the board
-    pub fn display(&self) {
-        for row in &self.grid {
-            for cell in row {
-                match cell {
-                    Cell::Empty => print!(" . "),
-                    Cell::Occupied(player) => match player {
-                        Player::Red => print!(" R "),
-                        Player::Yellow => print!(" Y "),
-                    },
+    // The cells of the line of four (or more) through the winning move, once the game is won
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        if !matches!(self.state, State::Won(_)) {
+            return None;
+        }
+        let (row, col) = self.last_move?;
+        let player = self.grid[row][col];
+
+        for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            let mut line = vec![(row, col)];
+            // walk away from the last move in both directions while the discs match
+            for sign in [-1, 1] {
+                let (mut r, mut c) = (row as isize, col as isize);
+                loop {
+                    r += dr * sign;
+                    c += dc * sign;
+                    if r < 0 || c < 0 || r >= self.rows as isize || c >= self.cols as isize
+                        || self.grid[r as usize][c as usize] != player
+                    {
+                        break;
+                    }
+                    line.push((r as usize, c as usize));
                 }
             }
-            println!();
+            if line.len() >= 4 {
+                line.sort();
+                return Some(line);
+            }
         }
+        None
     }
 
-    pub fn computer_move(&mut self) -> Result<(), &'static str> {
+    // Drop a disc in a random column, returning the column played
+    pub fn computer_move(&mut self) -> Result<usize, &'static str> {
         let mut rng = rand::thread_rng();
         let mut attempts = 0;
         loop {
             let col = rng.gen_range(0..self.cols);
-            if let Ok(_) = self.insert_disc(col) {
-                println!("Computer placed on column {}", col + 1);
-                break;
+            if self.insert_disc(col).is_ok() {
+                return Ok(col);
             }
             attempts += 1;
             if attempts > 100 { // Just to prevent an infinite loop
                 return Err("Failed to make a move after multiple attempts.");
             }
         }
-        Ok(())
     }
 
     // Insert a disc into the specified column
@@ -86,6 +143,7 @@ impl Board {
             if let Cell::Empty = self.grid[row][col] {
                 self.grid[row][col] = Cell::Occupied(self.current_turn);
                 self.last_move = Some((row, col));
+                self.moves.push((self.current_turn, col));
                 if self.check_win(row, col) {
                     self.state = State::Won(self.current_turn);
                 } else if self.is_draw() {
@@ -100,12 +158,48 @@ impl Board {
     }
     
 
-    // Switch the current player's turn
+    // Take back the last move, handing the turn back to whoever made it
+    pub fn undo(&mut self) -> Result<(), &'static str> {
+        let (player, col) = self.moves.pop().ok_or("There is no move to undo")?;
+        // the last disc dropped in a column is the topmost one
+        if let Some(row) = (0..self.rows).find(|&row| self.grid[row][col] != Cell::Empty) {
+            self.grid[row][col] = Cell::Empty;
+        }
+        self.current_turn = player;
+        self.state = State::Running;
+        self.last_move = self.moves.last().map(|&(_, col)| {
+            let row = (0..self.rows).find(|&row| self.grid[row][col] != Cell::Empty).unwrap_or(0);
+            (row, col)
+        });
+        Ok(())
+    }
+
+    // Discs a player has not played yet, when the board is shared out evenly between the players
+    pub fn discs_left(&self, player: Player) -> usize {
+        let share = (self.rows * self.cols).div_ceil(self.turn_order.len());
+        let played = self.moves.iter().filter(|&&(p, _)| p == player).count();
+        share.saturating_sub(played)
+    }
+
+    // A player ran out of time. With two players the opponent wins; in bigger games
+    // the flagged player drops out and the last one standing wins.
+    pub fn flag(&mut self, player: Player) {
+        if self.state != State::Running || !self.turn_order.contains(&player) {
+            return;
+        }
+        if self.current_turn == player {
+            self.switch_turn();
+        }
+        self.turn_order.retain(|&p| p != player);
+        if self.turn_order.len() == 1 {
+            self.state = State::Won(self.turn_order[0]);
+        }
+    }
+
+    // Pass the turn to the next player in the turn order
     fn switch_turn(&mut self) {
-        self.current_turn = match self.current_turn {
-            Player::Red => Player::Yellow,
-            Player::Yellow => Player::Red,
-        };
+        let current = self.turn_order.iter().position(|&p| p == self.current_turn).unwrap_or(0);
+        self.current_turn = self.turn_order[(current + 1) % self.turn_order.len()];
     }
 
     // Check the board for a winning condition. 
@@ -206,3 +300,95 @@ impl Board {
         }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Player::*;
+
+    fn play(board: &mut Board, cols: &[usize]) {
+        for &col in cols {
+            board.insert_disc(col).unwrap();
+        }
+    }
+
+    #[test]
+    fn turn_orders_need_two_or_more_different_players() {
+        assert!(Board::with_players(6, 7, vec![]).is_err());
+        assert!(Board::with_players(6, 7, vec![Red]).is_err());
+        assert!(Board::with_players(6, 7, vec![Red, Yellow, Red]).is_err());
+        assert!(Board::with_players(6, 7, vec![Green, Blue]).is_ok());
+    }
+
+    #[test]
+    fn turns_follow_the_turn_order_round_the_table() {
+        let mut board = Board::with_players(7, 9, vec![Blue, Red, Green]).unwrap();
+        assert_eq!(board.current_turn, Blue);
+        play(&mut board, &[0, 1, 2, 3]);
+        assert_eq!(board.moves, vec![(Blue, 0), (Red, 1), (Green, 2), (Blue, 3)]);
+        assert_eq!(board.current_turn, Red);
+    }
+
+    #[test]
+    fn green_and_blue_can_win() {
+        // Green stacks column 3 while the others spread out
+        let mut board = Board::with_players(7, 9, vec![Red, Yellow, Green]).unwrap();
+        play(&mut board, &[0, 1, 2, 4, 5, 2, 0, 1, 2, 4, 5]);
+        assert_eq!(board.state, State::Running);
+        play(&mut board, &[2]);
+        assert_eq!(board.state, State::Won(Green));
+
+        // Blue goes first and stacks column 4
+        let mut board = Board::with_players(8, 10, vec![Blue, Red, Yellow, Green]).unwrap();
+        play(&mut board, &[3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2]);
+        assert_eq!(board.state, State::Running);
+        play(&mut board, &[3]);
+        assert_eq!(board.state, State::Won(Blue));
+    }
+
+    #[test]
+    fn flagged_players_leave_until_one_is_left() {
+        let mut board = Board::with_players(7, 9, vec![Red, Yellow, Green]).unwrap();
+        board.flag(Red);
+        assert_eq!(board.state, State::Running);
+        assert_eq!(board.turn_order, vec![Yellow, Green]);
+        assert_eq!(board.current_turn, Yellow);
+
+        play(&mut board, &[0]);
+        assert_eq!(board.current_turn, Green);
+        board.flag(Green);
+        assert_eq!(board.state, State::Won(Yellow));
+
+        let mut board = Board::new(6, 7);
+        board.flag(Yellow);
+        assert_eq!(board.state, State::Won(Red));
+    }
+
+    #[test]
+    fn undo_gives_the_turn_back() {
+        let mut board = Board::with_players(7, 9, vec![Green, Red, Yellow]).unwrap();
+        play(&mut board, &[4, 4, 5]);
+        assert_eq!(board.current_turn, Green);
+        board.undo().unwrap();
+        assert_eq!(board.current_turn, Yellow);
+        assert_eq!(board.last_move, Some((5, 4))); // Red on top of Green
+        board.undo().unwrap();
+        assert_eq!(board.current_turn, Red);
+        assert_eq!(board.grid[6][4], Cell::Occupied(Green));
+        assert_eq!(board.grid[5][4], Cell::Empty);
+        board.undo().unwrap();
+        assert_eq!(board.current_turn, Green);
+        assert_eq!(board.last_move, None);
+        assert!(board.undo().is_err());
+    }
+
+    #[test]
+    fn undo_reopens_a_won_game() {
+        let mut board = Board::new(6, 7);
+        play(&mut board, &[0, 1, 0, 1, 0, 1, 0]);
+        assert_eq!(board.state, State::Won(Red));
+        board.undo().unwrap();
+        assert_eq!(board.state, State::Running);
+        assert_eq!(board.current_turn, Red);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{connect4, toot_otto};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Only the most recent games are kept
+const MAX_GAMES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GameKind {
+    ConnectFour,
+    TootOtto,
+}
+
+// One finished game, as the web app reports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub game: GameKind,
+    pub players: Vec<String>,   // In seating order, e.g. ["Player1", "Computer (Hard)"]
+    pub winner: Option<String>, // None for a draw
+    pub date_ms: u64,           // When the game ended, in ms since the epoch
+    pub moves: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Replay>, // Missing for games recorded before replays were saved
+}
+
+// Every move of a game, enough to play it again from an empty board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Replay {
+    ConnectFour {
+        rows: usize,
+        cols: usize,
+        turn_order: Vec<connect4::Player>,
+        moves: Vec<(connect4::Player, usize)>, // (player, column), oldest first
+    },
+    TootOtto {
+        rows: usize,
+        cols: usize,
+        moves: Vec<(toot_otto::Player, usize, toot_otto::Piece)>, // (player, column, piece), oldest first
+    },
+}
+
+impl Replay {
+    pub fn game(&self) -> GameKind {
+        match self {
+            Replay::ConnectFour { .. } => GameKind::ConnectFour,
+            Replay::TootOtto { .. } => GameKind::TootOtto,
+        }
+    }
+}
+
+impl GameRecord {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if !(2..=4).contains(&self.players.len()) {
+            return Err("A game has 2 to 4 players");
+        }
+        if self.players.iter().any(|name| name.is_empty() || name.chars().count() > 40) {
+            return Err("Player names must be 1 to 40 characters");
+        }
+        if let Some(winner) = &self.winner {
+            if !self.players.contains(winner) {
+                return Err("The winner must be one of the players");
+            }
+        }
+        if let Some(replay) = &self.replay {
+            if replay.game() != self.game {
+                return Err("The replay is of a different game");
+            }
+        }
+        Ok(())
+    }
+}
+
+// Finished games, kept in memory and saved to a JSON file after every change
+pub struct HistoryStore {
+    path: PathBuf,
+    games: Mutex<Vec<GameRecord>>,
+}
+
+impl HistoryStore {
+    // Load the history saved at `path`; a missing or unreadable file starts an empty history
+    pub fn open(path: impl Into<PathBuf>) -> HistoryStore {
+        let path = path.into();
+        let games = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        HistoryStore { path, games: Mutex::new(games) }
+    }
+
+    // Every game, oldest first
+    pub fn games(&self) -> Vec<GameRecord> {
+        self.games.lock().expect("history lock poisoned").clone()
+    }
+
+    pub fn add(&self, record: GameRecord) -> Result<(), &'static str> {
+        record.validate()?;
+        let mut games = self.games.lock().expect("history lock poisoned");
+        games.push(record);
+        let excess = games.len().saturating_sub(MAX_GAMES);
+        games.drain(..excess);
+        let json = serde_json::to_string(&*games).map_err(|_| "Could not save the history")?;
+        std::fs::write(&self.path, json).map_err(|_| "Could not save the history")
+    }
+}
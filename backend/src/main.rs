@@ -4,9 +4,73 @@ use connect4::{Board, Player, State};
 mod toot_otto;
 use toot_otto::{Board as TootBoard, Player as TootPlayer, State as TootState, Piece};
 
-use std::io::{self, Write};
+mod clock;
+use clock::{format_remaining, GameClock, SystemTimeSource, TimeControl};
+
+mod render;
+use render::Renderer;
+
+mod tui;
+
+mod ai;
+use ai::{Ai, DEFAULT_DEPTH};
+
+mod book;
+use book::Book;
+
+mod history;
+use history::HistoryStore;
+
+mod online;
+
+mod analysis;
+
+mod puzzle;
+
+mod api;
+
+use std::fmt::Debug;
+use std::io::{self, IsTerminal, Write};
 
 fn main() {
+    // --unicode draws the board with box-drawing characters, --no-color (or NO_COLOR) turns off ANSI colours
+    let args: Vec<String> = std::env::args().collect();
+    let renderer = Renderer {
+        color: io::stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").is_none()
+            && !args.iter().any(|arg| arg == "--no-color"),
+        unicode: args.iter().any(|arg| arg == "--unicode"),
+    };
+
+    // `backend gen-book [plies] [depth] [file]` rebuilds the Connect Four opening book
+    if args.get(1).map(String::as_str) == Some("gen-book") {
+        generate_book(&args[2..]);
+        return;
+    }
+
+    // `backend gen-puzzles [count] [file]` finds new puzzles in self-play games
+    if args.get(1).map(String::as_str) == Some("gen-puzzles") {
+        generate_puzzles(&args[2..]);
+        return;
+    }
+
+    // `backend serve [history file]` runs the web app's API, saving finished games to the file
+    if args.get(1).map(String::as_str) == Some("serve") {
+        let path = args.get(2).map(String::as_str).unwrap_or("history.json");
+        if let Err(e) = api::serve(HistoryStore::open(path)) {
+            println!("Error: {}", e);
+        }
+        return;
+    }
+
+    // --tui plays in a full-screen terminal UI instead of the line-by-line prompts
+    if args.iter().any(|arg| arg == "--tui") {
+        if let Err(e) = tui::run(renderer) {
+            println!("Error: {}", e);
+        }
+        return;
+    }
+
     println!("Choose your game:");
     println!("1: Connect Four");
     println!("2: TOOT-OTTO");
@@ -14,14 +78,43 @@ fn main() {
     let choice = get_user_input("Enter choice (1 for Connect Four, 2 for TOOT-OTTO): ");
 
     match choice.as_str() {
-        "1" => connect_four_interface(),
-        "2" => toot_otto_interface(),
+        "1" => connect_four_interface(&renderer),
+        "2" => toot_otto_interface(&renderer),
         _ => println!("Invalid choice, please restart the program."),
     }
 }
 
 
 
+fn generate_book(args: &[String]) {
+    let plies = args.first().and_then(|arg| arg.parse().ok()).unwrap_or(8);
+    let depth = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(12);
+    let path = args.get(2).map(String::as_str).unwrap_or("book/connect4.book");
+
+    println!("Building a 6x7 opening book for the first {} plies, searching {} plies deep...", plies, depth);
+    let started = std::time::Instant::now();
+    match Book::generate(6, 7, plies, depth) {
+        Ok(book) => match std::fs::write(path, book.to_bytes()) {
+            Ok(()) => println!("Wrote {} positions to {} in {:.1?}", book.len(), path, started.elapsed()),
+            Err(e) => println!("Error: {}", e),
+        },
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+fn generate_puzzles(args: &[String]) {
+    let count = args.first().and_then(|arg| arg.parse().ok()).unwrap_or(40);
+    let path = args.get(1).map(String::as_str).unwrap_or("puzzles/puzzles.json");
+
+    println!("Looking for {} puzzles in self-play games...", count);
+    let started = std::time::Instant::now();
+    let puzzles = puzzle::generate(count);
+    match serde_json::to_string(&puzzles).map_err(|e| e.to_string()).and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string())) {
+        Ok(()) => println!("Wrote {} puzzles to {} in {:.1?}", puzzles.len(), path, started.elapsed()),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
 fn get_user_input(prompt: &str) -> String {
     print!("{}", prompt);
     io::stdout().flush().unwrap();
@@ -30,52 +123,139 @@ fn get_user_input(prompt: &str) -> String {
     input.trim().to_owned()
 }
 
-fn connect_four_interface() {
+// Ask for an optional time control, e.g. "5+3" for five minutes plus three seconds a move
+fn get_time_control() -> Option<TimeControl> {
+    loop {
+        let input = get_user_input("Time control in minutes+increment seconds (e.g. 5+3), or press Enter for an untimed game: ");
+        if input.is_empty() {
+            return None;
+        }
+        match input.parse::<TimeControl>() {
+            Ok(control) => return Some(control),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
+
+// Print every player's remaining time
+fn print_clocks<P: Copy + PartialEq + Debug>(clock: &GameClock<P>, players: &[P]) {
+    let times: Vec<String> = players
+        .iter()
+        .map(|&p| format!("{:?} {}", p, format_remaining(clock.remaining(p, &SystemTimeSource))))
+        .collect();
+    println!("Clocks: {}", times.join(" | "));
+}
+
+// The player whose time ran out while they were thinking; they lose instead of making their move
+fn flagged<P: Copy + PartialEq + Debug>(clock: &Option<GameClock<P>>) -> Option<P> {
+    let player = clock.as_ref()?.flagged(&SystemTimeSource)?;
+    println!("{:?} ran out of time!", player);
+    Some(player)
+}
+
+fn connect_four_interface(renderer: &Renderer) {
     println!("Do you want to play against (1) another player or (2) the computer? Enter 1 or 2: ");
     let mode_input = get_user_input("");
     let play_against_computer = mode_input == "2";
 
-    let mut board = Board::new(6, 7); // Standard Connect Four board size
+    // The computer only plays two-player games; human games can seat up to four players
+    let players = if play_against_computer {
+        2
+    } else {
+        match get_user_input("How many players (2-4)? ").parse::<usize>() {
+            Ok(num) if (2..=4).contains(&num) => num,
+            _ => {
+                println!("Invalid number of players, starting a two-player game.");
+                2
+            }
+        }
+    };
+
+    let (rows, cols) = Board::dimensions_for(players);
+    let mut board = Board::with_players(rows, cols, get_turn_order(players))
+        .expect("every seated player once is always a valid turn order");
+    let clock = get_time_control().map(|control| GameClock::new(control, &board.turn_order));
+
+    game_loop(&mut board, renderer, play_against_computer, clock);
+}
 
-    game_loop(&mut board, play_against_computer);
+// Ask who moves first, second and so on, by colour; Enter keeps seating order
+fn get_turn_order(players: usize) -> Vec<Player> {
+    let seats = &Player::ALL[..players];
+    let names: Vec<String> = seats.iter().map(|p| format!("{:?}", p)).collect();
+    let input = get_user_input(&format!("Turn order, e.g. \"{}\" (Enter to play in this order): ", names.join(" ")));
+    if input.is_empty() {
+        return seats.to_vec();
+    }
+    let order: Option<Vec<Player>> = input
+        .split([' ', ','])
+        .filter(|word| !word.is_empty())
+        .map(|word| seats.iter().copied().find(|p| format!("{:?}", p).eq_ignore_ascii_case(word)))
+        .collect();
+    match order {
+        Some(order) if order.len() == players && seats.iter().all(|p| order.contains(p)) => order,
+        _ => {
+            println!("Invalid turn order, playing in seating order.");
+            seats.to_vec()
+        }
+    }
 }
 
-fn game_loop(board: &mut Board, play_against_computer: bool) {
+fn game_loop(board: &mut Board, renderer: &Renderer, play_against_computer: bool, mut clock: Option<GameClock<Player>>) {
+    let mut ai = Ai::new(DEFAULT_DEPTH);
+    if let Some(clock) = clock.as_mut() {
+        clock.start(board.current_turn, &SystemTimeSource);
+    }
     loop {
-        board.display();
+        print!("{}", renderer.connect4(board));
         println!("Current turn: {:?}", board.current_turn);
+        if let Some(clock) = &clock {
+            print_clocks(clock, &board.turn_order);
+        }
 
         if !play_against_computer || matches!(board.current_turn, Player::Red) {
-            let col_input = get_user_input("Enter column (0-6) to drop your disc: ");
+            let col_input = get_user_input(&format!("Enter column (1-{}) to drop your disc: ", board.cols));
             let col = match col_input.parse::<usize>() {
-                Ok(num) if num < 7 => num,
+                Ok(num) if (1..=board.cols).contains(&num) => num - 1,
                 _ => {
-                    println!("Invalid input. Please enter a column number between 0 and 6.");
+                    println!("Invalid input. Please enter a column number between 1 and {}.", board.cols);
                     continue;
                 },
             };
 
-            if let Err(e) = board.insert_disc(col) {
+            if let Some(player) = flagged(&clock) {
+                board.flag(player);
+            } else if let Err(e) = board.insert_disc(col) {
                 println!("Error: {}", e);
                 continue;
             }
         } else {
             // Computer's turn
             println!("Computer's turn.");
-            if let Err(e) = board.computer_move() {
-                println!("Error: {}", e);
-                continue;
+            match ai.play(board) {
+                Ok(col) => println!("Computer placed on column {}", col + 1),
+                Err(e) => {
+                    println!("Error: {}", e);
+                    continue;
+                }
+            }
+        }
+        if let Some(clock) = clock.as_mut() {
+            if board.state == State::Running {
+                clock.press(board.current_turn, &SystemTimeSource);
+            } else {
+                clock.stop(&SystemTimeSource);
             }
         }
 
         match board.state {
             State::Won(player) => {
-                board.display();
+                print!("{}", renderer.connect4(board));
                 println!("Player {:?} wins!", player);
                 break;
             },
             State::Draw => {
-                board.display();
+                print!("{}", renderer.connect4(board));
                 println!("The game is a draw!");
                 break;
             },
@@ -94,19 +274,22 @@ fn game_loop(board: &mut Board, play_against_computer: bool) {
 ////////////////////// toot otto
 
 
-fn toot_otto_interface() {
+fn toot_otto_interface(renderer: &Renderer) {
     let mut board = TootBoard::new(4, 6); // Assuming a standard size for TOOT-OTTO
-    let mut play_against_computer = false;  // Default to two player mode
     println!("Do you want to play against (1) another player or (2) the computer? Enter 1 or 2: ");
     let mode_input = get_user_input("");
-    play_against_computer = mode_input == "2";
+    let play_against_computer = mode_input == "2"; // The computer plays OTTO
+    let clock = get_time_control().map(|control| GameClock::new(control, &[TootPlayer::Toot, TootPlayer::Otto]));
 
-    toot_otto_game_loop(&mut board, play_against_computer);
+    toot_otto_game_loop(&mut board, renderer, play_against_computer, clock);
 }
 
-fn toot_otto_game_loop(board: &mut TootBoard, play_against_computer: bool) {
+fn toot_otto_game_loop(board: &mut TootBoard, renderer: &Renderer, play_against_computer: bool, mut clock: Option<GameClock<TootPlayer>>) {
+    if let Some(clock) = clock.as_mut() {
+        clock.start(board.current_turn, &SystemTimeSource);
+    }
     loop {
-        board.display();
+        print!("{}", renderer.toot_otto(board));
 
         let current_player = if matches!(board.current_turn, TootPlayer::Toot) {
             "TOOT"
@@ -114,43 +297,66 @@ fn toot_otto_game_loop(board: &mut TootBoard, play_against_computer: bool) {
             "OTTO"
         };
         println!("Current turn for: {}", current_player);
+        if let Some(clock) = &clock {
+            print_clocks(clock, &[TootPlayer::Toot, TootPlayer::Otto]);
+        }
 
-        // Get player's piece choice
-        let piece_input = get_user_input("Choose your piece (T or O): ");
-        let piece = match piece_input.as_str() {
-            "T" | "t" => Piece::T,
-            "O" | "o" => Piece::O,
-            _ => {
-                println!("Invalid piece. Please choose 'T' or 'O'.");
-                continue;
+        if play_against_computer && matches!(board.current_turn, TootPlayer::Otto) {
+            println!("Computer's turn.");
+            match board.computer_move() {
+                Ok((col, piece)) => println!("Computer placed {:?} on column {}", piece, col + 1),
+                Err(e) => {
+                    println!("Error: {}", e);
+                    continue;
+                }
             }
-        };
+        } else {
+            // Get player's piece choice
+            let piece_input = get_user_input("Choose your piece (T or O): ");
+            let piece = match piece_input.as_str() {
+                "T" | "t" => Piece::T,
+                "O" | "o" => Piece::O,
+                _ => {
+                    println!("Invalid piece. Please choose 'T' or 'O'.");
+                    continue;
+                }
+            };
 
-        // Get player's column choice
-        let col_input = get_user_input("Enter column number to place your piece: ");
-        let col = match col_input.parse::<usize>() {
-            Ok(num) if num < board.cols => num,
-            _ => {
-                println!("Invalid input. Please enter a valid column number.");
+            // Get player's column choice
+            let col_input = get_user_input(&format!("Enter column (1-{}) to place your piece: ", board.cols));
+            let col = match col_input.parse::<usize>() {
+                Ok(num) if (1..=board.cols).contains(&num) => num - 1,
+                _ => {
+                    println!("Invalid input. Please enter a column number between 1 and {}.", board.cols);
+                    continue;
+                }
+            };
+
+            // Attempt to insert the piece into the board
+            if let Some(player) = flagged(&clock) {
+                board.flag(player);
+            } else if let Err(e) = board.insert_piece(col, piece) {
+                println!("Error: {}", e);
                 continue;
             }
-        };
-
-        // Attempt to insert the piece into the board
-        if let Err(e) = board.insert_piece(col, piece) {
-            println!("Error: {}", e);
-            continue;
+        }
+        if let Some(clock) = clock.as_mut() {
+            if board.state == TootState::Running {
+                clock.press(board.current_turn, &SystemTimeSource);
+            } else {
+                clock.stop(&SystemTimeSource);
+            }
         }
 
         // Check game state after each move
         match board.state {
             TootState::Won(player) => {
-                board.display();
+                print!("{}", renderer.toot_otto(board));
                 println!("Player {:?} wins!", player);
                 break;
             },
             TootState::Draw => {
-                board.display();
+                print!("{}", renderer.toot_otto(board));
                 println!("The game is a draw!");
                 break;
             },
@@ -0,0 +1,535 @@
+use crate::clock::{GameClock, SystemTimeSource, TimeControl};
+use crate::connect4;
+use crate::history::GameKind;
+use crate::toot_otto::{self, Piece};
+
+use rand::Rng;
+use rocket::tokio::sync::Notify;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// A seat counts as connected if its player has asked about the game this recently.
+// Clients long-poll, so a connected player asks at least every LONG_POLL.
+pub const LONG_POLL: Duration = Duration::from_secs(20);
+const CONNECTED_WITHIN: Duration = Duration::from_secs(30);
+// Games nobody has looked at for this long are forgotten
+const ABANDONED_AFTER: Duration = Duration::from_secs(60 * 60);
+// Letters for game codes, without ones that are easy to mix up (I/1, O/0)
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 5;
+
+pub const NO_SUCH_GAME: &str = "There is no game with that code";
+
+// The board of an online game; the server's copy is the real one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OnlineBoard {
+    ConnectFour(connect4::Board),
+    TootOtto(toot_otto::Board),
+}
+
+impl OnlineBoard {
+    fn new(game: GameKind, rows: usize, cols: usize) -> OnlineBoard {
+        match game {
+            GameKind::ConnectFour => OnlineBoard::ConnectFour(connect4::Board::new(rows, cols)),
+            GameKind::TootOtto => OnlineBoard::TootOtto(toot_otto::Board::new(rows, cols)),
+        }
+    }
+
+    fn game(&self) -> GameKind {
+        match self {
+            OnlineBoard::ConnectFour(_) => GameKind::ConnectFour,
+            OnlineBoard::TootOtto(_) => GameKind::TootOtto,
+        }
+    }
+
+    fn moves(&self) -> usize {
+        match self {
+            OnlineBoard::ConnectFour(b) => b.moves.len(),
+            OnlineBoard::TootOtto(b) => b.moves.len(),
+        }
+    }
+
+    // Seat 0 is Player1 (Red, or TOOT) and moves first; seat 1 is Player2 (Yellow, or OTTO)
+    fn seat_to_move(&self) -> Option<usize> {
+        match self {
+            OnlineBoard::ConnectFour(b) if b.state == connect4::State::Running => {
+                Some(if b.current_turn == connect4::Player::Red { 0 } else { 1 })
+            }
+            OnlineBoard::TootOtto(b) if b.state == toot_otto::State::Running => {
+                Some(if b.current_turn == toot_otto::Player::Toot { 0 } else { 1 })
+            }
+            _ => None,
+        }
+    }
+
+    fn play(&mut self, col: usize, piece: Option<Piece>) -> Result<(), &'static str> {
+        match self {
+            OnlineBoard::ConnectFour(b) => b.insert_disc(col),
+            OnlineBoard::TootOtto(b) => b.insert_piece(col, piece.ok_or("Choose a T or an O")?),
+        }
+    }
+
+    // The seat's time ran out, so the other seat wins
+    fn flag(&mut self, seat: usize) {
+        match self {
+            OnlineBoard::ConnectFour(b) => b.flag(if seat == 0 { connect4::Player::Red } else { connect4::Player::Yellow }),
+            OnlineBoard::TootOtto(b) => b.flag(if seat == 0 { toot_otto::Player::Toot } else { toot_otto::Player::Otto }),
+        }
+    }
+}
+
+// What a new game is
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewGame {
+    pub game: GameKind,
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(default)]
+    pub control: Option<TimeControl>, // Untimed without one
+}
+
+// A seat in a game; the token proves the seat is yours when moving
+#[derive(Debug, Clone, Serialize)]
+pub struct Joined {
+    pub code: String,
+    pub seat: usize,
+    pub token: String,
+}
+
+// A spectator's id, which they send when watching so they are counted
+#[derive(Debug, Clone, Serialize)]
+pub struct Spectating {
+    pub code: String,
+    pub spectator: String,
+}
+
+// Who is asking about a game. Players and spectators are counted as connected; anyone else just looks.
+#[derive(Debug, Clone, Copy)]
+pub enum Viewer<'a> {
+    Player(&'a str), // a seat's token
+    Spectator(&'a str),
+    Anonymous,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Move {
+    pub token: String,
+    pub board_version: u64, // The board version the move was made on
+    pub col: usize,
+    pub piece: Option<Piece>, // Toot and Otto only
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeatStatus {
+    pub joined: bool,
+    pub connected: bool,
+}
+
+// A timed game's clocks when the view was made; the page counts the running one down from there
+#[derive(Debug, Clone, Serialize)]
+pub struct ClockView {
+    pub control: TimeControl,
+    pub remaining_ms: Vec<u64>, // By seat
+    pub running: Option<usize>, // The seat whose clock is running
+}
+
+// A game as the players see it
+#[derive(Debug, Clone, Serialize)]
+pub struct GameView {
+    pub code: String,
+    pub version: u64,       // Goes up by one with every change to the game, including who is connected
+    pub board_version: u64, // Goes up by one with every move
+    pub board: OnlineBoard,
+    pub seats: Vec<SeatStatus>,
+    pub spectators: usize, // Spectators watching right now
+    pub clock: Option<ClockView>,
+}
+
+// A game in the list of games to watch
+#[derive(Debug, Clone, Serialize)]
+pub struct GameSummary {
+    pub code: String,
+    pub game: GameKind,
+    pub moves: usize,
+    pub spectators: usize,
+}
+
+struct Seat {
+    token: String,
+    last_seen: Instant,
+}
+
+struct OnlineGame {
+    version: u64,       // What watchers wait on
+    board_version: u64, // What moves are checked against, so people coming and going can't make a move miss
+    board: OnlineBoard,
+    clock: Option<GameClock<usize>>, // By seat; it starts once both players are seated
+    seats: Vec<Seat>,
+    spectators: HashMap<String, Instant>, // id given out by spectate -> last seen
+    watching: usize,                      // Spectators seen within CONNECTED_WITHIN at the last look
+    last_seen: Instant,
+}
+
+impl OnlineGame {
+    fn view(&self, code: &str) -> GameView {
+        GameView {
+            code: code.to_owned(),
+            version: self.version,
+            board_version: self.board_version,
+            board: self.board.clone(),
+            seats: (0..2)
+                .map(|i| SeatStatus {
+                    joined: i < self.seats.len(),
+                    connected: self.seats.get(i).is_some_and(|s| s.last_seen.elapsed() < CONNECTED_WITHIN),
+                })
+                .collect(),
+            spectators: self.watching,
+            clock: self.clock.as_ref().map(|clock| ClockView {
+                control: clock.control,
+                remaining_ms: (0..2).map(|seat| clock.remaining(seat, &SystemTimeSource).as_millis() as u64).collect(),
+                running: clock.running(),
+            }),
+        }
+    }
+
+    // End the game for a player whose time has run out. Returns whether anyone was flagged.
+    fn check_flag(&mut self) -> bool {
+        let Some(clock) = self.clock.as_mut() else {
+            return false;
+        };
+        let Some(seat) = clock.flagged(&SystemTimeSource) else {
+            return false;
+        };
+        self.board.flag(seat);
+        clock.stop(&SystemTimeSource);
+        true
+    }
+
+    fn seat_of(&self, token: &str) -> Option<usize> {
+        self.seats.iter().position(|s| s.token == token)
+    }
+}
+
+// Every online game being played, by code
+#[derive(Default)]
+pub struct Lobby {
+    games: Mutex<HashMap<String, OnlineGame>>,
+    changed: Notify, // Wakes everyone waiting for a game to change
+}
+
+// Codes are shown in capitals, but people type them however they like
+fn normalise_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn random_string(letters: &[u8], length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length).map(|_| letters[rng.gen_range(0..letters.len())] as char).collect()
+}
+
+impl Lobby {
+    pub fn new() -> Lobby {
+        Lobby::default()
+    }
+
+    // Start a game and take its first seat
+    pub fn create(&self, new: NewGame) -> Result<Joined, &'static str> {
+        if !(4..=10).contains(&new.rows) || !(4..=12).contains(&new.cols) {
+            return Err("Boards are 4-10 rows by 4-12 columns");
+        }
+        if let Some(control) = &new.control {
+            control.check()?;
+        }
+        let mut games = self.games.lock().expect("lobby lock poisoned");
+        games.retain(|_, game| game.last_seen.elapsed() < ABANDONED_AFTER);
+        let code = loop {
+            let code = random_string(CODE_LETTERS, CODE_LENGTH);
+            if !games.contains_key(&code) {
+                break code;
+            }
+        };
+        let token = random_string(b"0123456789abcdef", 32);
+        let now = Instant::now();
+        games.insert(
+            code.clone(),
+            OnlineGame {
+                version: 0,
+                board_version: 0,
+                board: OnlineBoard::new(new.game, new.rows, new.cols),
+                clock: new.control.map(|control| GameClock::new(control, &[0, 1])),
+                seats: vec![Seat { token: token.clone(), last_seen: now }],
+                spectators: HashMap::new(),
+                watching: 0,
+                last_seen: now,
+            },
+        );
+        Ok(Joined { code, seat: 0, token })
+    }
+
+    // Take the free seat in someone else's game
+    pub fn join(&self, code: &str) -> Result<Joined, &'static str> {
+        let code = normalise_code(code);
+        let mut games = self.games.lock().expect("lobby lock poisoned");
+        let game = games.get_mut(&code).ok_or(NO_SUCH_GAME)?;
+        if game.seats.len() >= 2 {
+            return Err("That game already has two players");
+        }
+        let token = random_string(b"0123456789abcdef", 32);
+        game.seats.push(Seat { token: token.clone(), last_seen: Instant::now() });
+        // the first player's time starts once there is someone to play against
+        if let (Some(clock), Some(seat)) = (game.clock.as_mut(), game.board.seat_to_move()) {
+            clock.start(seat, &SystemTimeSource);
+        }
+        game.version += 1;
+        self.changed.notify_waiters();
+        Ok(Joined { code, seat: 1, token })
+    }
+
+    // Games with both players seated and still going, which anyone may watch. Games waiting for
+    // their second player aren't listed, so only someone given the code can take the seat.
+    pub fn active(&self) -> Vec<GameSummary> {
+        let games = self.games.lock().expect("lobby lock poisoned");
+        let mut active: Vec<GameSummary> = games
+            .iter()
+            .filter(|(_, game)| game.seats.len() == 2 && game.board.seat_to_move().is_some())
+            .filter(|(_, game)| game.last_seen.elapsed() < ABANDONED_AFTER)
+            .map(|(code, game)| GameSummary {
+                code: code.clone(),
+                game: game.board.game(),
+                moves: game.board.moves(),
+                spectators: game.watching,
+            })
+            .collect();
+        active.sort_by(|a, b| a.code.cmp(&b.code));
+        active
+    }
+
+    // Start watching a game
+    pub fn spectate(&self, code: &str) -> Result<Spectating, &'static str> {
+        let code = normalise_code(code);
+        let mut games = self.games.lock().expect("lobby lock poisoned");
+        let game = games.get_mut(&code).ok_or(NO_SUCH_GAME)?;
+        let spectator = random_string(b"0123456789abcdef", 16);
+        game.spectators.insert(spectator.clone(), Instant::now());
+        game.watching += 1;
+        game.version += 1;
+        self.changed.notify_waiters();
+        Ok(Spectating { code, spectator })
+    }
+
+    // The game as it is now. Asking as a player or a spectator marks them as connected.
+    pub fn view(&self, code: &str, viewer: Viewer) -> Result<GameView, &'static str> {
+        let code = normalise_code(code);
+        let mut games = self.games.lock().expect("lobby lock poisoned");
+        let game = games.get_mut(&code).ok_or(NO_SUCH_GAME)?;
+        let now = Instant::now();
+        game.last_seen = now;
+        // who comes and goes changes what everyone else sees
+        let mut changed = false;
+        match viewer {
+            Viewer::Player(token) => {
+                if let Some(seat) = game.seat_of(token) {
+                    changed = game.seats[seat].last_seen.elapsed() >= CONNECTED_WITHIN;
+                    game.seats[seat].last_seen = now;
+                }
+            }
+            // only ids handed out by spectate count, so made-up ones can't pad the numbers. They are
+            // kept after going quiet so a spectator whose tab slept is counted again when it wakes.
+            Viewer::Spectator(id) => {
+                if let Some(last_seen) = game.spectators.get_mut(id) {
+                    *last_seen = now;
+                }
+            }
+            Viewer::Anonymous => {}
+        }
+        let watching = game.spectators.values().filter(|last_seen| last_seen.elapsed() < CONNECTED_WITHIN).count();
+        changed |= watching != game.watching;
+        game.watching = watching;
+        if game.check_flag() || changed {
+            game.version += 1;
+            self.changed.notify_waiters();
+        }
+        Ok(game.view(&code))
+    }
+
+    // The game once its version is past `since`, or as it is after LONG_POLL if nothing happens
+    pub async fn watch(&self, code: &str, since: u64, viewer: Viewer<'_>) -> Result<GameView, &'static str> {
+        let deadline = rocket::tokio::time::Instant::now() + LONG_POLL;
+        loop {
+            // ask to be woken before looking, so a change in between isn't missed
+            let changed = self.changed.notified();
+            let view = self.view(code, viewer)?;
+            if view.version > since {
+                return Ok(view);
+            }
+            // a running clock running out is news too, and nobody else would notice it
+            let runs_out = view.clock.as_ref().and_then(|clock| {
+                let seat = clock.running?;
+                Some(rocket::tokio::time::Instant::now() + Duration::from_millis(clock.remaining_ms[seat] + 1))
+            });
+            let wake = runs_out.map_or(deadline, |runs_out| runs_out.min(deadline));
+            if rocket::tokio::time::timeout_at(wake, changed).await.is_err() && wake == deadline {
+                return Ok(view);
+            }
+        }
+    }
+
+    // Play a move for the seat holding `mv.token`, if it is that seat's turn and the board hasn't changed since
+    pub fn play(&self, code: &str, mv: Move) -> Result<GameView, &'static str> {
+        let code = normalise_code(code);
+        let mut games = self.games.lock().expect("lobby lock poisoned");
+        let game = games.get_mut(&code).ok_or(NO_SUCH_GAME)?;
+        let seat = game.seat_of(&mv.token).ok_or("You are not playing in this game")?;
+        if game.seats.len() < 2 {
+            return Err("Wait for your opponent to join");
+        }
+        if game.check_flag() {
+            game.version += 1;
+            self.changed.notify_waiters();
+            return Err("Time ran out before the move arrived");
+        }
+        if mv.board_version != game.board_version {
+            return Err("The board changed before your move arrived");
+        }
+        if game.board.seat_to_move() != Some(seat) {
+            return Err("It is not your turn");
+        }
+        game.board.play(mv.col, mv.piece)?;
+        if let Some(clock) = game.clock.as_mut() {
+            match game.board.seat_to_move() {
+                Some(next) => clock.press(next, &SystemTimeSource),
+                None => clock.stop(&SystemTimeSource),
+            }
+        }
+        game.board_version += 1;
+        game.version += 1;
+        let now = Instant::now();
+        game.seats[seat].last_seen = now;
+        game.last_seen = now;
+        self.changed.notify_waiters();
+        Ok(game.view(&code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect4(control: Option<TimeControl>) -> NewGame {
+        NewGame { game: GameKind::ConnectFour, rows: 6, cols: 7, control }
+    }
+
+    // A lobby with one Connect Four game and both seats taken
+    fn started(lobby: &Lobby) -> (Joined, Joined) {
+        let first = lobby.create(connect4(None)).unwrap();
+        let second = lobby.join(&first.code).unwrap();
+        (first, second)
+    }
+
+    fn mv(seat: &Joined, board_version: u64, col: usize) -> Move {
+        Move { token: seat.token.clone(), board_version, col, piece: None }
+    }
+
+    #[test]
+    fn codes_ignore_case_and_spaces() {
+        let lobby = Lobby::new();
+        let first = lobby.create(connect4(None)).unwrap();
+        let typed = format!("  {}\n", first.code.to_lowercase());
+
+        let second = lobby.join(&typed).unwrap();
+        assert_eq!(second.code, first.code);
+        assert_eq!(lobby.spectate(&typed).unwrap().code, first.code);
+        assert_eq!(lobby.view(&typed, Viewer::Anonymous).unwrap().code, first.code);
+        let view = lobby.play(&typed, mv(&first, 0, 3)).unwrap();
+        assert_eq!(view.code, first.code);
+        assert_eq!(view.board_version, 1);
+
+        assert_eq!(lobby.view("ZZZZZ", Viewer::Anonymous).unwrap_err(), NO_SUCH_GAME);
+    }
+
+    #[test]
+    fn a_game_only_has_two_seats() {
+        let lobby = Lobby::new();
+        let (first, second) = started(&lobby);
+        assert_eq!((first.seat, second.seat), (0, 1));
+        assert_ne!(first.token, second.token);
+        assert_eq!(lobby.join(&first.code).unwrap_err(), "That game already has two players");
+    }
+
+    #[test]
+    fn moves_need_the_right_seat_turn_and_board() {
+        let lobby = Lobby::new();
+        let first = lobby.create(connect4(None)).unwrap();
+        assert_eq!(lobby.play(&first.code, mv(&first, 0, 3)).unwrap_err(), "Wait for your opponent to join");
+        let second = lobby.join(&first.code).unwrap();
+
+        let stranger = Joined { token: "not a seat".to_owned(), ..first.clone() };
+        assert_eq!(lobby.play(&first.code, mv(&stranger, 0, 3)).unwrap_err(), "You are not playing in this game");
+        assert_eq!(lobby.play(&first.code, mv(&second, 0, 3)).unwrap_err(), "It is not your turn");
+
+        lobby.play(&first.code, mv(&first, 0, 3)).unwrap();
+        // a move made on the board before that one
+        assert_eq!(lobby.play(&first.code, mv(&second, 0, 3)).unwrap_err(), "The board changed before your move arrived");
+        let view = lobby.play(&first.code, mv(&second, 1, 3)).unwrap();
+        assert_eq!(view.board_version, 2);
+        assert_eq!(view.board.moves(), 2);
+    }
+
+    #[test]
+    fn running_out_of_time_ends_the_game() {
+        let lobby = Lobby::new();
+        let (first, second) = started(&lobby);
+        // a clock far shorter than any create allows, so the test doesn't have to wait
+        {
+            let mut games = lobby.games.lock().unwrap();
+            let game = games.get_mut(&first.code).unwrap();
+            let mut clock = GameClock::new(TimeControl { initial_ms: 1, increment_ms: 0 }, &[0, 1]);
+            clock.start(0, &SystemTimeSource);
+            game.clock = Some(clock);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(lobby.play(&first.code, mv(&first, 0, 3)).unwrap_err(), "Time ran out before the move arrived");
+        let view = lobby.view(&first.code, Viewer::Player(&second.token)).unwrap();
+        match view.board {
+            OnlineBoard::ConnectFour(board) => assert_eq!(board.state, connect4::State::Won(connect4::Player::Yellow)),
+            OnlineBoard::TootOtto(_) => unreachable!("the game is Connect Four"),
+        }
+        assert_eq!(view.clock.unwrap().running, None);
+        assert_eq!(lobby.play(&first.code, mv(&second, 0, 3)).unwrap_err(), "It is not your turn");
+    }
+
+    #[test]
+    fn only_issued_spectator_ids_are_counted() {
+        let lobby = Lobby::new();
+        let (first, _) = started(&lobby);
+        let before = lobby.view(&first.code, Viewer::Anonymous).unwrap();
+        assert_eq!(before.spectators, 0);
+
+        let made_up = lobby.view(&first.code, Viewer::Spectator("0123456789abcdef")).unwrap();
+        assert_eq!(made_up.spectators, 0);
+        assert_eq!(made_up.version, before.version);
+
+        let watching = lobby.spectate(&first.code).unwrap();
+        let view = lobby.view(&first.code, Viewer::Spectator(&watching.spectator)).unwrap();
+        assert_eq!(view.spectators, 1);
+        let again = lobby.view(&first.code, Viewer::Spectator(&watching.spectator)).unwrap();
+        assert_eq!((again.spectators, again.version), (1, view.version));
+        assert_eq!(lobby.active()[0].spectators, 1);
+    }
+
+    #[test]
+    fn unplayable_time_controls_are_refused() {
+        let lobby = Lobby::new();
+        for control in [
+            TimeControl { initial_ms: u64::MAX, increment_ms: 1 },
+            TimeControl { initial_ms: 0, increment_ms: 0 },
+            TimeControl { initial_ms: 60_000, increment_ms: u64::MAX },
+        ] {
+            assert!(lobby.create(connect4(Some(control))).is_err(), "{:?} should be refused", control);
+        }
+        assert!(lobby.create(connect4(Some(TimeControl { initial_ms: 60_000, increment_ms: 1_000 }))).is_ok());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::ai::Ai;
+use crate::connect4::{self, Board};
+use crate::history::GameKind;
+use crate::toot_otto::{self, Board as TootBoard, Piece};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Longest forced wins looked for, in moves of the winning side. TOOT-OTTO has six times the moves
+// to try at every turn, so its searches stay shorter.
+const CONNECT4_MAX_MOVES: usize = 3;
+const TOOT_OTTO_MAX_MOVES: usize = 2;
+// Self-play moves before positions start being looked at, so puzzles aren't all openings
+const SKIP_PLIES: usize = 6;
+// How often a self-play move is random rather than the engine's, to get some mistakes to punish
+const RANDOM_MOVE_CHANCE: f64 = 0.4;
+// Search depth of the engine in self-play; it only has to play plausibly
+const SELF_PLAY_DEPTH: u32 = 4;
+
+// A move in either game; Connect Four moves have no piece
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Move {
+    pub col: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub piece: Option<Piece>,
+}
+
+// A winning move, the defence that holds out longest, and the winning moves after that.
+// There's no reply when the move wins on the spot, and nothing `then` when the reply loses on its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    pub play: Move,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply: Option<Move>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<Line>,
+}
+
+// A position from a self-play game where the player to move can force a win
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub game: GameKind,
+    pub rows: usize,
+    pub cols: usize,
+    pub opening: Vec<Move>, // The moves that led to the position, from an empty board
+    pub moves: usize,       // The win takes this many moves of the player to move
+    pub solution: Vec<Line>, // Every move that wins in time
+}
+
+// What a move did, for the player who made it
+enum Outcome<G> {
+    Won,
+    Lost, // TOOT-OTTO only: the move spelled the opponent's word
+    Drawn,
+    Next(G),
+}
+
+// The little the search needs from each game
+trait Game: Sized {
+    fn moves(&self) -> Vec<Move>;
+    fn play(&self, mv: Move) -> Outcome<Self>;
+}
+
+impl Game for Board {
+    fn moves(&self) -> Vec<Move> {
+        (0..self.cols)
+            .filter(|&col| self.grid[0][col] == connect4::Cell::Empty)
+            .map(|col| Move { col, piece: None })
+            .collect()
+    }
+
+    fn play(&self, mv: Move) -> Outcome<Board> {
+        let mover = self.current_turn;
+        let mut next = self.clone();
+        if next.insert_disc(mv.col).is_err() {
+            return Outcome::Drawn;
+        }
+        match next.state {
+            connect4::State::Won(player) if player == mover => Outcome::Won,
+            connect4::State::Won(_) => Outcome::Lost,
+            connect4::State::Draw => Outcome::Drawn,
+            connect4::State::Running => Outcome::Next(next),
+        }
+    }
+}
+
+impl Game for TootBoard {
+    fn moves(&self) -> Vec<Move> {
+        (0..self.cols)
+            .filter(|&col| self.grid[0][col] == toot_otto::Cell::Empty)
+            .flat_map(|col| {
+                [Piece::T, Piece::O]
+                    .into_iter()
+                    .filter(|&piece| self.pieces_left(self.current_turn, piece) > 0)
+                    .map(move |piece| Move { col, piece: Some(piece) })
+            })
+            .collect()
+    }
+
+    fn play(&self, mv: Move) -> Outcome<TootBoard> {
+        let mover = self.current_turn;
+        let mut next = self.clone();
+        let Some(piece) = mv.piece else {
+            return Outcome::Drawn;
+        };
+        if next.insert_piece(mv.col, piece).is_err() {
+            return Outcome::Drawn;
+        }
+        match next.state {
+            toot_otto::State::Won(player) if player == mover => Outcome::Won,
+            toot_otto::State::Won(_) => Outcome::Lost,
+            toot_otto::State::Draw => Outcome::Drawn,
+            toot_otto::State::Running => Outcome::Next(next),
+        }
+    }
+}
+
+// Whether the player to move can force a win within `n` of their own moves
+fn wins_within<G: Game>(game: &G, n: usize) -> bool {
+    n > 0 && game.moves().into_iter().any(|mv| wins_with(game, mv, n))
+}
+
+// Whether `mv` forces a win within `n` moves, whatever the opponent replies
+fn wins_with<G: Game>(game: &G, mv: Move, n: usize) -> bool {
+    match game.play(mv) {
+        Outcome::Won => true,
+        Outcome::Lost | Outcome::Drawn => false,
+        Outcome::Next(after) => {
+            let replies = after.moves();
+            n > 1
+                && !replies.is_empty()
+                && replies.into_iter().all(|reply| match after.play(reply) {
+                    Outcome::Won | Outcome::Drawn => false,
+                    Outcome::Lost => true,
+                    Outcome::Next(next) => wins_within(&next, n - 1),
+                })
+        }
+    }
+}
+
+// Every winning line within `n` moves
+fn solution<G: Game>(game: &G, n: usize) -> Vec<Line> {
+    game.moves()
+        .into_iter()
+        .filter(|&mv| wins_with(game, mv, n))
+        .map(|play| match game.play(play) {
+            Outcome::Next(after) => {
+                // the reply that keeps the game going longest; failing that, one that loses on its own
+                let defences: Vec<(Move, G)> = after.moves().into_iter()
+                    .filter_map(|reply| match after.play(reply) {
+                        Outcome::Next(next) => Some((reply, next)),
+                        _ => None,
+                    })
+                    .collect();
+                let longest = defences.into_iter().max_by_key(|(_, next)| (1..n).find(|&k| wins_within(next, k)));
+                match longest {
+                    Some((reply, next)) => Line { play, reply: Some(reply), then: solution(&next, n - 1) },
+                    None => Line { play, reply: after.moves().first().copied(), then: Vec::new() },
+                }
+            }
+            _ => Line { play, reply: None, then: Vec::new() },
+        })
+        .collect()
+}
+
+// The puzzle in this position, if the player to move has a forced win that isn't a single move
+fn puzzle_at<G: Game>(game: &G, max_moves: usize) -> Option<(usize, Vec<Line>)> {
+    if wins_within(game, 1) {
+        return None;
+    }
+    let moves = (2..=max_moves).find(|&n| wins_within(game, n))?;
+    Some((moves, solution(game, moves)))
+}
+
+// Play a Connect Four game against itself, stopping at the first puzzle
+fn connect4_puzzle(ai: &mut Ai, seen: &mut HashSet<String>) -> Option<Puzzle> {
+    let mut rng = rand::thread_rng();
+    let mut board = Board::new(6, 7);
+    while board.state == connect4::State::Running {
+        if board.moves.len() >= SKIP_PLIES && seen.insert(format!("{:?}", board.grid)) {
+            if let Some((moves, solution)) = puzzle_at(&board, CONNECT4_MAX_MOVES) {
+                let opening = board.moves.iter().map(|&(_, col)| Move { col, piece: None }).collect();
+                return Some(Puzzle { game: GameKind::ConnectFour, rows: board.rows, cols: board.cols, opening, moves, solution });
+            }
+        }
+        let played = if rng.gen_bool(RANDOM_MOVE_CHANCE) { board.computer_move() } else { ai.play(&mut board) };
+        played.ok()?;
+    }
+    None
+}
+
+// The same for TOOT-OTTO, where both sides play at random
+fn toot_otto_puzzle(seen: &mut HashSet<String>) -> Option<Puzzle> {
+    let mut board = TootBoard::new(4, 6);
+    while board.state == toot_otto::State::Running {
+        if board.moves.len() >= SKIP_PLIES && seen.insert(format!("{:?}", board.grid)) {
+            if let Some((moves, solution)) = puzzle_at(&board, TOOT_OTTO_MAX_MOVES) {
+                let opening = board.moves.iter().map(|&(_, col, piece)| Move { col, piece: Some(piece) }).collect();
+                return Some(Puzzle { game: GameKind::TootOtto, rows: board.rows, cols: board.cols, opening, moves, solution });
+            }
+        }
+        board.computer_move().ok()?;
+    }
+    None
+}
+
+// About `count` puzzles, half of each game, found in self-play games
+pub fn generate(count: usize) -> Vec<Puzzle> {
+    let mut ai = Ai::new(SELF_PLAY_DEPTH);
+    let mut seen = HashSet::new();
+    let (mut connect4, mut toot_otto) = (Vec::new(), Vec::new());
+    // self-play games don't always have a puzzle in them, but give up eventually
+    for _ in 0..count * 50 {
+        if connect4.len() < count.div_ceil(2) {
+            connect4.extend(connect4_puzzle(&mut ai, &mut seen));
+        } else if toot_otto.len() < count / 2 {
+            toot_otto.extend(toot_otto_puzzle(&mut seen));
+        } else {
+            break;
+        }
+    }
+    // alternate the games, so the daily puzzle switches between them
+    let mut puzzles = Vec::with_capacity(connect4.len() + toot_otto.len());
+    let mut toot_otto = toot_otto.into_iter();
+    for puzzle in connect4 {
+        puzzles.push(puzzle);
+        puzzles.extend(toot_otto.next());
+    }
+    puzzles.extend(toot_otto);
+    puzzles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(cols: &[usize]) -> Board {
+        let mut board = Board::new(6, 7);
+        for &col in cols {
+            board.insert_disc(col).unwrap();
+        }
+        board
+    }
+
+    // Red has columns 3 and 4 on the bottom row, with Yellow on top of both: making three in
+    // a row with both ends open wins on the next move whatever Yellow does
+    fn open_two() -> Board {
+        board(&[3, 3, 2, 2])
+    }
+
+    // Every reply in the lines is a legal move that leaves the game going, and every line ends in a win.
+    // The one exception is a TOOT-OTTO reply when every reply spells the winner's word: the puzzle
+    // shows one of them, and the line ends there.
+    fn check_lines<G: Game>(game: &G, lines: &[Line]) {
+        for line in lines {
+            assert!(game.moves().contains(&line.play), "{:?} isn't a legal move", line.play);
+            match (game.play(line.play), line.reply) {
+                (Outcome::Won, None) => {}
+                (Outcome::Next(after), Some(reply)) => {
+                    assert!(after.moves().contains(&reply), "{:?} isn't a legal reply", reply);
+                    match after.play(reply) {
+                        Outcome::Next(next) => check_lines(&next, &line.then),
+                        Outcome::Lost => {
+                            assert!(line.then.is_empty());
+                            let forced = after.moves().into_iter().all(|mv| matches!(after.play(mv), Outcome::Lost));
+                            assert!(forced, "the reply {:?} loses although another keeps the game going", reply);
+                        }
+                        _ => panic!("the reply {:?} ends the game", reply),
+                    }
+                }
+                _ => panic!("{:?} neither wins nor has a reply", line),
+            }
+        }
+    }
+
+    #[test]
+    fn a_win_in_two_is_found() {
+        let board = open_two();
+        assert!(!wins_within(&board, 1));
+        assert!(wins_within(&board, 2));
+
+        let (moves, solution) = puzzle_at(&board, CONNECT4_MAX_MOVES).expect("Red can force a win");
+        assert_eq!(moves, 2);
+        let plays: Vec<usize> = solution.iter().map(|line| line.play.col).collect();
+        assert_eq!(plays, vec![1, 4]);
+        assert!(!wins_with(&board, Move { col: 5, piece: None }, 2));
+        check_lines(&board, &solution);
+    }
+
+    #[test]
+    fn a_win_in_one_is_not_a_puzzle() {
+        let board = board(&[0, 1, 0, 1, 0, 6]);
+        assert!(wins_within(&board, 1));
+        assert_eq!(puzzle_at(&board, CONNECT4_MAX_MOVES), None);
+    }
+
+    #[test]
+    fn no_forced_win_is_not_a_puzzle() {
+        assert_eq!(puzzle_at(&board(&[3, 3]), CONNECT4_MAX_MOVES), None);
+    }
+
+    #[test]
+    fn self_play_puzzles_have_legal_replies() {
+        let mut ai = Ai::new(SELF_PLAY_DEPTH);
+        let mut seen = HashSet::new();
+        let puzzles: Vec<Puzzle> = (0..20).filter_map(|_| connect4_puzzle(&mut ai, &mut seen)).take(3).collect();
+        for puzzle in &puzzles {
+            let mut board = Board::new(puzzle.rows, puzzle.cols);
+            for mv in &puzzle.opening {
+                board.insert_disc(mv.col).unwrap();
+            }
+            assert_eq!(puzzle_at(&board, CONNECT4_MAX_MOVES).map(|(moves, _)| moves), Some(puzzle.moves));
+            check_lines(&board, &puzzle.solution);
+        }
+
+        let puzzles: Vec<Puzzle> = (0..20).filter_map(|_| toot_otto_puzzle(&mut seen)).take(3).collect();
+        for puzzle in &puzzles {
+            let mut board = TootBoard::new(puzzle.rows, puzzle.cols);
+            for mv in &puzzle.opening {
+                board.insert_piece(mv.col, mv.piece.unwrap()).unwrap();
+            }
+            check_lines(&board, &puzzle.solution);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::connect4::{Board, Cell, Player};
+use crate::toot_otto::{Board as TootBoard, Cell as TootCell, Piece};
+
+const RESET: &str = "\x1b[0m";
+const HIGHLIGHT_LAST: &str = "\x1b[1;4m"; // bold and underlined
+const HIGHLIGHT_WIN: &str = "\x1b[7m"; // inverse video
+
+// Renders boards for the terminal. The result is a plain String so it can be printed,
+// redrawn in place by a full-screen UI, or compared in tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Renderer {
+    pub color: bool,   // ANSI colours and highlights, otherwise markers like [R] and *R*
+    pub unicode: bool, // box-drawing grid and round discs instead of ASCII
+}
+
+impl Renderer {
+    pub fn connect4(&self, board: &Board) -> String {
+        let winning = board.winning_line().unwrap_or_default();
+        self.grid(board.rows, board.cols, board.last_move, &winning, |row, col| {
+            match board.grid[row][col] {
+                Cell::Empty => None,
+                Cell::Occupied(player) => Some(disc(player)),
+            }
+        })
+    }
+
+    pub fn toot_otto(&self, board: &TootBoard) -> String {
+        let winning = board.winning_line().unwrap_or_default();
+        self.grid(board.rows, board.cols, board.last_move, &winning, |row, col| {
+            match board.grid[row][col] {
+                TootCell::Empty => None,
+                TootCell::Occupied(Piece::T) => Some(('T', "\x1b[36m")),
+                TootCell::Occupied(Piece::O) => Some(('O', "\x1b[35m")),
+            }
+        })
+    }
+
+    // Lay out any rows x cols board with 1-based column numbers above it.
+    // `piece` gives the letter and ANSI colour of an occupied cell.
+    fn grid(
+        &self,
+        rows: usize,
+        cols: usize,
+        last_move: Option<(usize, usize)>,
+        winning: &[(usize, usize)],
+        piece: impl Fn(usize, usize) -> Option<(char, &'static str)>,
+    ) -> String {
+        let mut out = String::new();
+
+        // Column headers line up with the 3-character wide cells (plus the borders in box mode)
+        let sep = if self.unicode { " " } else { "" };
+        let headers: Vec<String> = (1..=cols).map(|col| format!("{:^3}", col)).collect();
+        out += &format!("{}{}\n", sep, headers.join(sep));
+
+        if self.unicode {
+            out += &border('┌', '┬', '┐', cols);
+        }
+        for row in 0..rows {
+            let cells: Vec<String> = (0..cols)
+                .map(|col| {
+                    let is_last = last_move == Some((row, col));
+                    let is_winning = winning.contains(&(row, col));
+                    self.cell(piece(row, col), is_last, is_winning)
+                })
+                .collect();
+            if self.unicode {
+                out += &format!("│{}│\n", cells.join("│"));
+                if row + 1 < rows {
+                    out += &border('├', '┼', '┤', cols);
+                }
+            } else {
+                out += &format!("{}\n", cells.join(""));
+            }
+        }
+        if self.unicode {
+            out += &border('└', '┴', '┘', cols);
+        }
+        out
+    }
+
+    // One 3-character wide cell
+    fn cell(&self, piece: Option<(char, &'static str)>, is_last: bool, is_winning: bool) -> String {
+        let Some((letter, colour)) = piece else {
+            return if self.unicode { "   ".to_owned() } else { " . ".to_owned() };
+        };
+        // coloured Connect Four discs can be drawn as round discs, letters are kept for TOOT-OTTO
+        let glyph = if self.color && self.unicode && !matches!(letter, 'T' | 'O') {
+            '●'
+        } else {
+            letter
+        };
+
+        if !self.color {
+            return match (is_winning, is_last) {
+                (true, _) => format!("*{}*", glyph),
+                (false, true) => format!("[{}]", glyph),
+                (false, false) => format!(" {} ", glyph),
+            };
+        }
+        let last = if is_last { HIGHLIGHT_LAST } else { "" };
+        let disc = format!("{}{}{}{}", colour, last, glyph, RESET);
+        if is_winning {
+            // the reset after the disc ends the inverse video, so re-apply it for the padding
+            format!("{} {}{} {}", HIGHLIGHT_WIN, disc, HIGHLIGHT_WIN, RESET)
+        } else {
+            format!(" {} ", disc)
+        }
+    }
+}
+
+// Letter and ANSI colour of each Connect Four player
+fn disc(player: Player) -> (char, &'static str) {
+    match player {
+        Player::Red => ('R', "\x1b[31m"),
+        Player::Yellow => ('Y', "\x1b[33m"),
+        Player::Green => ('G', "\x1b[32m"),
+        Player::Blue => ('B', "\x1b[34m"),
+    }
+}
+
+// A horizontal box-drawing border, e.g. ┌───┬───┐
+fn border(left: char, middle: char, right: char, cols: usize) -> String {
+    let segments = vec!["───"; cols].join(&middle.to_string());
+    format!("{}{}{}\n", left, segments, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: Renderer = Renderer { color: false, unicode: false };
+    const BOX: Renderer = Renderer { color: false, unicode: true };
+    const COLOUR: Renderer = Renderer { color: true, unicode: false };
+
+    fn board(rows: usize, cols: usize, moves: &[usize]) -> Board {
+        let mut board = Board::new(rows, cols);
+        for &col in moves {
+            board.insert_disc(col).unwrap();
+        }
+        board
+    }
+
+    // Red stacks four in column 1 while Yellow plays column 2
+    fn red_wins() -> Board {
+        board(4, 4, &[0, 1, 0, 1, 0, 1, 0])
+    }
+
+    #[test]
+    fn plain_board_has_headers_dots_and_marks_the_last_move() {
+        assert_eq!(
+            PLAIN.connect4(&board(3, 4, &[1])),
+            concat!(
+                " 1  2  3  4 \n",
+                " .  .  .  . \n",
+                " .  .  .  . \n",
+                " . [R] .  . \n",
+            )
+        );
+    }
+
+    #[test]
+    fn unicode_board_is_drawn_in_boxes() {
+        assert_eq!(
+            BOX.connect4(&board(2, 2, &[0])),
+            concat!(
+                "  1   2 \n",
+                "┌───┬───┐\n",
+                "│   │   │\n",
+                "├───┼───┤\n",
+                "│[R]│   │\n",
+                "└───┴───┘\n",
+            )
+        );
+    }
+
+    #[test]
+    fn colour_is_only_used_when_turned_on() {
+        let board = board(3, 3, &[0, 1]);
+        assert!(!PLAIN.connect4(&board).contains('\x1b'));
+        assert!(!BOX.connect4(&board).contains('\x1b'));
+
+        let coloured = COLOUR.connect4(&board);
+        assert!(coloured.contains(" \x1b[31mR\x1b[0m "), "Red is red: {:?}", coloured);
+        assert!(coloured.contains(" \x1b[33m\x1b[1;4mY\x1b[0m "), "the last move is highlighted: {:?}", coloured);
+    }
+
+    #[test]
+    fn coloured_unicode_draws_round_discs_but_keeps_letters() {
+        let renderer = Renderer { color: true, unicode: true };
+        assert!(renderer.connect4(&board(2, 2, &[0])).contains('●'));
+
+        let mut toot = TootBoard::new(4, 6);
+        toot.insert_piece(0, Piece::T).unwrap();
+        toot.insert_piece(1, Piece::O).unwrap();
+        let drawn = renderer.toot_otto(&toot);
+        assert!(!drawn.contains('●'));
+        assert!(drawn.contains("\x1b[36mT\x1b[0m"));
+        assert!(drawn.contains("\x1b[35m\x1b[1;4mO\x1b[0m"));
+    }
+
+    #[test]
+    fn winning_line_is_starred_without_colour() {
+        let drawn = PLAIN.connect4(&red_wins());
+        assert_eq!(drawn.matches("*R*").count(), 4);
+        assert_eq!(drawn.matches(" Y ").count(), 3);
+        // the winning move is part of the line, so it isn't also bracketed
+        assert!(!drawn.contains('['));
+    }
+
+    #[test]
+    fn winning_line_is_inverse_video_with_colour() {
+        let drawn = COLOUR.connect4(&red_wins());
+        // inverse video before each winning disc, and again after it for the padding
+        assert_eq!(drawn.matches(HIGHLIGHT_WIN).count(), 8);
+        assert!(drawn.contains(&format!("{} \x1b[31mR{}{} {}", HIGHLIGHT_WIN, RESET, HIGHLIGHT_WIN, RESET)));
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Serialize, Deserialize};
+use rand::Rng; // Import the Rng trait to use random number generation
+use rand::seq::SliceRandom;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Piece {
@@ -32,6 +34,10 @@ pub struct Board {
     pub rows: usize,
     pub cols: usize,
     pub state: State,
+    #[serde(default)]
+    pub last_move: Option<(usize, usize)>, // Track the last move as (row, col)
+    #[serde(default)]
+    pub moves: Vec<(Player, usize, Piece)>, // Every move so far as (player, column, piece), oldest first
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -49,30 +55,72 @@ impl Board {
             rows,
             cols,
             state: State::Running,
-            
+            last_move: None,
+            moves: Vec::new(),
         }
     }
 
 
-    pub fn display(&self) {
-        for row in &self.grid {
-            for cell in row {
-                match cell {
-                    Cell::Empty => print!(" . "),
-                    Cell::Occupied(piece) => match piece {
-                        Piece::T => print!(" T "),
-                        Piece::O => print!(" O "),
-                    },
+    // The cells spelling TOOT or OTTO on the lines through the last move, once the game is over
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        if self.state == State::Running {
+            return None;
+        }
+        let (row, col) = self.last_move?;
+
+        let mut cells = Vec::new();
+        for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            // back up to the edge of the board, then collect the whole line through the last move
+            let (mut r, mut c) = (row as isize, col as isize);
+            while self.on_board(r - dr, c - dc) {
+                r -= dr;
+                c -= dc;
+            }
+            let mut line = Vec::new();
+            while self.on_board(r, c) {
+                line.push((r as usize, c as usize));
+                r += dr;
+                c += dc;
+            }
+
+            let letters: String = line.iter().map(|&(r, c)| match self.grid[r][c] {
+                Cell::Occupied(Piece::T) => 'T',
+                Cell::Occupied(Piece::O) => 'O',
+                Cell::Empty => '.',
+            }).collect();
+            for start in 0..letters.len().saturating_sub(3) {
+                if matches!(&letters[start..start + 4], "TOOT" | "OTTO") {
+                    cells.extend_from_slice(&line[start..start + 4]);
                 }
             }
-            println!();
         }
+
+        cells.sort();
+        cells.dedup();
+        if cells.is_empty() { None } else { Some(cells) }
     }
 
-    pub fn computer_move(&mut self) -> Result<(), &'static str> {
-        // TODO:
-        self.insert_piece(1, Piece::O);
-        Ok(())
+    fn on_board(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && row < self.rows as isize && col < self.cols as isize
+    }
+
+    // Play a random piece in a random column, returning the column and piece played
+    pub fn computer_move(&mut self) -> Result<(usize, Piece), &'static str> {
+        let mut rng = rand::thread_rng();
+        let mut attempts = 0;
+        loop {
+            let col = rng.gen_range(0..self.cols);
+            let pieces = [Piece::T, Piece::O]; // Array of pieces
+            let piece = *pieces.choose(&mut rng).expect("Failed to select a random piece");
+
+            if self.insert_piece(col, piece).is_ok() {
+                return Ok((col, piece));
+            }
+            attempts += 1;
+            if attempts > 100 { // Prevents an infinite loop
+                return Err("Failed to make a move after multiple attempts.");
+            }
+        }
     }
 
 
@@ -82,12 +130,19 @@ impl Board {
         if col >= self.cols {
             return Err("Column out of bounds");
         }
+        if self.pieces_left(self.current_turn, piece) == 0 {
+            return Err(match piece {
+                Piece::T => "No T pieces left",
+                Piece::O => "No O pieces left",
+            });
+        }
 
         // Attempt to place the piece in the lowest empty cell in the specified column
         for row in (0..self.rows).rev() {
             if matches!(self.grid[row][col], Cell::Empty) {
                 self.grid[row][col] = Cell::Occupied(piece);
-
+                self.last_move = Some((row, col));
+                self.moves.push((self.current_turn, col, piece));
                 match self.check_win(row, col) {
                     Some(Winner::Player(player)) => {
                         self.state = State::Won(player);
@@ -117,6 +172,40 @@ impl Board {
     }
 
 
+    // Take back the last move, handing the turn back to whoever made it
+    pub fn undo(&mut self) -> Result<(), &'static str> {
+        let (player, col, _) = self.moves.pop().ok_or("There is no move to undo")?;
+        // the last piece dropped in a column is the topmost one
+        if let Some(row) = (0..self.rows).find(|&row| self.grid[row][col] != Cell::Empty) {
+            self.grid[row][col] = Cell::Empty;
+        }
+        self.current_turn = player;
+        self.state = State::Running;
+        self.last_move = self.moves.last().map(|&(_, col, _)| {
+            let row = (0..self.rows).find(|&row| self.grid[row][col] != Cell::Empty).unwrap_or(0);
+            (row, col)
+        });
+        Ok(())
+    }
+
+    // Pieces of one letter a player still has. As in the official game each player
+    // gets an equal number of Ts and Os, a quarter of the board each (6 of each on 4x6).
+    pub fn pieces_left(&self, player: Player, piece: Piece) -> usize {
+        let share = (self.rows * self.cols).div_ceil(4);
+        let played = self.moves.iter().filter(|&&(p, _, q)| p == player && q == piece).count();
+        share.saturating_sub(played)
+    }
+
+    // A player ran out of time, so their opponent wins
+    pub fn flag(&mut self, player: Player) {
+        if self.state == State::Running {
+            self.state = State::Won(match player {
+                Player::Toot => Player::Otto,
+                Player::Otto => Player::Toot,
+            });
+        }
+    }
+
     // Switch the current player's turn
     pub fn switch_turn(&mut self) {
         self.current_turn = match self.current_turn {
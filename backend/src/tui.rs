@@ -0,0 +1,300 @@
+use crate::ai::{Ai, DEFAULT_DEPTH};
+use crate::connect4::{Board, Player, State};
+use crate::render::Renderer;
+use crate::toot_otto::{Board as TootBoard, Piece, Player as TootPlayer, State as TootState};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+
+const HELP: &str = "<-/-> column  Enter drop  t/o letter  u undo  n new game  g switch game  p players  r turn order  c computer  q quit";
+
+enum Game {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+struct App {
+    game: Game,
+    cursor: usize,           // Column the next piece will be dropped in
+    piece: Piece,            // Letter to drop in TOOT-OTTO
+    players: usize,          // Number of Connect Four players
+    turn_order: Vec<Player>, // The order they take turns in
+    vs_computer: bool,       // The computer plays Yellow / OTTO in two-player games
+    message: String,         // Feedback on the last key press
+    ai: Ai,
+}
+
+// Puts the terminal back to normal when the UI exits, even on a panic
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Run the full-screen game UI until the user quits
+pub fn run(renderer: Renderer) -> io::Result<()> {
+    let mut app = App {
+        game: Game::ConnectFour(Board::new(6, 7)),
+        cursor: 0,
+        piece: Piece::T,
+        players: 2,
+        turn_order: Player::ALL[..2].to_vec(),
+        vs_computer: false,
+        message: String::new(),
+        ai: Ai::new(DEFAULT_DEPTH),
+    };
+
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    let mut out = io::stdout();
+    execute!(out, EnterAlternateScreen, Hide)?;
+
+    loop {
+        app.draw(&renderer, &mut out)?;
+        if let Event::Key(key) = event::read()? {
+            // some terminals also report key releases
+            if key.kind == KeyEventKind::Press && !app.handle(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl App {
+    fn new_game(&mut self) {
+        self.game = match self.game {
+            Game::ConnectFour(_) => {
+                let (rows, cols) = Board::dimensions_for(self.players);
+                let board = Board::with_players(rows, cols, self.turn_order.clone())
+                    .expect("every seated player once is always a valid turn order");
+                Game::ConnectFour(board)
+            }
+            Game::TootOtto(_) => Game::TootOtto(TootBoard::new(4, 6)),
+        };
+        self.cursor = 0;
+        self.message = "New game".to_owned();
+        // with Yellow first in the turn order the computer opens
+        self.computer_reply();
+    }
+
+    fn moves_played(&self) -> usize {
+        match &self.game {
+            Game::ConnectFour(board) => board.moves.len(),
+            Game::TootOtto(board) => board.moves.len(),
+        }
+    }
+
+    fn cols(&self) -> usize {
+        match &self.game {
+            Game::ConnectFour(board) => board.cols,
+            Game::TootOtto(board) => board.cols,
+        }
+    }
+
+    fn computer_to_move(&self) -> bool {
+        if !self.vs_computer {
+            return false;
+        }
+        match &self.game {
+            Game::ConnectFour(board) => {
+                board.state == State::Running && board.turn_order.len() == 2 && board.current_turn == Player::Yellow
+            }
+            Game::TootOtto(board) => board.state == TootState::Running && board.current_turn == TootPlayer::Otto,
+        }
+    }
+
+    // Handle one key press, returning false when the user wants to quit
+    fn handle(&mut self, key: KeyCode) -> bool {
+        self.message.clear();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor = (self.cursor + 1).min(self.cols() - 1),
+            KeyCode::Char('t') => self.piece = Piece::T,
+            KeyCode::Char('o') => self.piece = Piece::O,
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Down => self.drop_piece(),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('n') => self.new_game(),
+            KeyCode::Char('g') => {
+                self.game = match self.game {
+                    Game::ConnectFour(_) => Game::TootOtto(TootBoard::new(4, 6)),
+                    Game::TootOtto(_) => Game::ConnectFour(Board::new(6, 7)),
+                };
+                self.new_game();
+            }
+            KeyCode::Char('p') => {
+                if let Game::ConnectFour(_) = self.game {
+                    self.players = if self.players >= 4 { 2 } else { self.players + 1 };
+                    self.turn_order = Player::ALL[..self.players].to_vec();
+                    self.new_game();
+                    self.message = format!("New {}-player game", self.players);
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Game::ConnectFour(_) = self.game {
+                    self.turn_order = next_turn_order(&self.turn_order);
+                    self.new_game();
+                    let names: Vec<String> = self.turn_order.iter().map(|p| format!("{:?}", p)).collect();
+                    self.message = format!("New game, turn order {}", names.join(", "));
+                }
+            }
+            KeyCode::Char('c') => {
+                self.vs_computer = !self.vs_computer;
+                self.message = if self.vs_computer { "Computer opponent on" } else { "Computer opponent off" }.to_owned();
+                self.computer_reply();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn drop_piece(&mut self) {
+        let result = match &mut self.game {
+            Game::ConnectFour(board) if board.state == State::Running => board.insert_disc(self.cursor),
+            Game::TootOtto(board) if board.state == TootState::Running => board.insert_piece(self.cursor, self.piece),
+            _ => Err("The game is over, press n for a new game"),
+        };
+        match result {
+            Ok(()) => self.computer_reply(),
+            Err(e) => self.message = e.to_owned(),
+        }
+    }
+
+    fn computer_reply(&mut self) {
+        if !self.computer_to_move() {
+            return;
+        }
+        let result = match &mut self.game {
+            Game::ConnectFour(board) => self.ai.play(board).map(|col| format!("Computer played column {}", col + 1)),
+            Game::TootOtto(board) => board
+                .computer_move()
+                .map(|(col, piece)| format!("Computer played {:?} in column {}", piece, col + 1)),
+        };
+        self.message = result.unwrap_or_else(|e| e.to_owned());
+    }
+
+    // Take back the last move. Against the computer, keep going until it is the human's turn again,
+    // or back to the start, where the computer plays its opening move again if it goes first.
+    fn undo(&mut self) {
+        loop {
+            let result = match &mut self.game {
+                Game::ConnectFour(board) => board.undo(),
+                Game::TootOtto(board) => board.undo(),
+            };
+            if let Err(e) = result {
+                self.message = e.to_owned();
+                return;
+            }
+            if !self.computer_to_move() {
+                break;
+            }
+            if self.moves_played() == 0 {
+                self.computer_reply();
+                return;
+            }
+        }
+        self.message = "Move undone".to_owned();
+    }
+
+    fn draw(&self, renderer: &Renderer, out: &mut impl Write) -> io::Result<()> {
+        let (title, board_text, mut side) = match &self.game {
+            Game::ConnectFour(board) => ("Connect Four", renderer.connect4(board), self.connect4_pane(board)),
+            Game::TootOtto(board) => ("TOOT-OTTO", renderer.toot_otto(board), self.toot_otto_pane(board)),
+        };
+        let title = if self.vs_computer { format!("{} (vs computer)", title) } else { title.to_owned() };
+
+        // cells are 3 characters wide, plus a border before each one in box-drawing mode
+        let (cell_width, first_cell) = if renderer.unicode { (4, 1) } else { (3, 0) };
+        let board_width = self.cols() * cell_width + first_cell;
+        let marker = format!("{}{}", " ".repeat(first_cell + self.cursor * cell_width + 1), if renderer.unicode { "▼" } else { "v" });
+
+        let mut left = vec![title, marker];
+        left.extend(board_text.lines().map(str::to_owned));
+        side.insert(0, String::new());
+        side.insert(0, String::new());
+
+        queue!(out, Clear(ClearType::All))?;
+        for (y, line) in left.iter().enumerate() {
+            queue!(out, MoveTo(0, y as u16), Print(line))?;
+        }
+        for (y, line) in side.iter().enumerate() {
+            queue!(out, MoveTo(board_width as u16 + 4, y as u16), Print(line))?;
+        }
+        let bottom = left.len().max(side.len()) as u16 + 1;
+        queue!(out, MoveTo(0, bottom), Print(&self.message), MoveTo(0, bottom + 1), Print(HELP))?;
+        out.flush()
+    }
+
+    // Turn, pieces left and move history for Connect Four
+    fn connect4_pane(&self, board: &Board) -> Vec<String> {
+        let mut pane = vec![match board.state {
+            State::Running => format!("Turn: {:?}", board.current_turn),
+            State::Won(player) => format!("{:?} wins! Press n for a new game", player),
+            State::Draw => "Draw! Press n for a new game".to_owned(),
+        }];
+        pane.push("Discs left:".to_owned());
+        for &player in &board.turn_order {
+            pane.push(format!("  {:?}: {}", player, board.discs_left(player)));
+        }
+        pane.push(String::new());
+        pane.push("Moves:".to_owned());
+        let moves: Vec<String> = board.moves.iter().map(|(player, col)| format!("{:?} {}", player, col + 1)).collect();
+        pane.extend(history(&moves, board.rows));
+        pane
+    }
+
+    // Turn, letter selection, pieces left and move history for TOOT-OTTO
+    fn toot_otto_pane(&self, board: &TootBoard) -> Vec<String> {
+        let mut pane = vec![match board.state {
+            TootState::Running => format!("Turn: {:?}, dropping {:?}", board.current_turn, self.piece),
+            TootState::Won(player) => format!("{:?} wins! Press n for a new game", player),
+            TootState::Draw => "Draw! Press n for a new game".to_owned(),
+        }];
+        pane.push("Pieces left:".to_owned());
+        for player in [TootPlayer::Toot, TootPlayer::Otto] {
+            pane.push(format!(
+                "  {:?}: T {}  O {}",
+                player,
+                board.pieces_left(player, Piece::T),
+                board.pieces_left(player, Piece::O)
+            ));
+        }
+        pane.push(String::new());
+        pane.push("Moves:".to_owned());
+        let moves: Vec<String> = board
+            .moves
+            .iter()
+            .map(|(player, col, piece)| format!("{:?} {:?} {}", player, piece, col + 1))
+            .collect();
+        pane.extend(history(&moves, board.rows));
+        pane
+    }
+}
+
+// The next ordering of the same players, so pressing r enough times visits every turn order
+fn next_turn_order(order: &[Player]) -> Vec<Player> {
+    let mut seats: Vec<usize> = order.iter().map(|p| Player::ALL.iter().position(|q| q == p).unwrap_or(0)).collect();
+    // the next permutation of the seat numbers, wrapping round to seating order after the last one
+    match (1..seats.len()).rev().find(|&i| seats[i - 1] < seats[i]) {
+        Some(i) => {
+            let j = (i..seats.len()).rev().find(|&j| seats[j] > seats[i - 1]).unwrap_or(i);
+            seats.swap(i - 1, j);
+            seats[i..].reverse();
+        }
+        None => seats.sort(),
+    }
+    seats.into_iter().map(|seat| Player::ALL[seat]).collect()
+}
+
+// The most recent moves, numbered from the start of the game, fitting in `max` lines
+fn history(moves: &[String], max: usize) -> Vec<String> {
+    let skip = moves.len().saturating_sub(max);
+    moves.iter().enumerate().skip(skip).map(|(i, m)| format!("{:>3}. {}", i + 1, m)).collect()
+}
@@ -0,0 +1,386 @@
+use crate::book::Book;
+use crate::clock::TimeSource;
+use crate::connect4::{Board, Cell, State};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Score of a win on the very first move; later wins score a little less, so sooner wins are preferred
+const WIN: i32 = 1_000_000;
+// How far ahead the computer player looks at most when the opening book runs out
+pub const DEFAULT_DEPTH: u32 = 12;
+// Forget the transposition table once it gets this big; browsers have less memory to spare than the CLI
+const MAX_TABLE_SIZE: usize = 1_000_000;
+// How many positions to search between looks at the clock
+const NODES_PER_TIME_CHECK: u32 = 4096;
+
+// How strong the computer player is
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum Difficulty {
+    #[default]
+    None, // no computer player
+    Easy,
+    Hard,
+}
+
+// A two-player Connect Four position packed into bitboards, for fast search.
+// Each column takes rows + 1 bits (the extra bit is a sentinel that keeps columns apart),
+// with bit 0 of a column being its bottom cell.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    current: u64, // discs of the player to move
+    mask: u64,    // all discs
+    moves: usize,
+    rows: usize,
+    cols: usize,
+    bottom: u64,     // bottom cell of every column
+    board_mask: u64, // every playable cell
+}
+
+impl Position {
+    pub fn new(rows: usize, cols: usize) -> Option<Position> {
+        if rows == 0 || cols == 0 || (rows + 1) * cols > 64 {
+            return None;
+        }
+        let bottom = (0..cols).fold(0u64, |bits, col| bits | 1 << (col * (rows + 1)));
+        Some(Position {
+            current: 0,
+            mask: 0,
+            moves: 0,
+            rows,
+            cols,
+            bottom,
+            board_mask: bottom * ((1 << rows) - 1),
+        })
+    }
+
+    // Only running two-player games on boards that fit in 64 bits can be searched
+    pub fn from_board(board: &Board) -> Option<Position> {
+        if board.turn_order.len() != 2 || board.state != State::Running {
+            return None;
+        }
+        let mut pos = Position::new(board.rows, board.cols)?;
+        for (row, line) in board.grid.iter().enumerate() {
+            for (col, cell) in line.iter().enumerate() {
+                if let Cell::Occupied(player) = *cell {
+                    let bit = 1u64 << (col * (board.rows + 1) + board.rows - 1 - row);
+                    pos.mask |= bit;
+                    pos.moves += 1;
+                    if player == board.current_turn {
+                        pos.current |= bit;
+                    }
+                }
+            }
+        }
+        Some(pos)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    // Uniquely identifies the position for a given board size
+    pub fn key(&self) -> u64 {
+        self.current + self.mask
+    }
+
+    pub fn can_play(&self, col: usize) -> bool {
+        col < self.cols && self.mask & self.top_cell(col) == 0
+    }
+
+    // Drop a disc for the player to move; the column must not be full
+    pub fn play(&mut self, col: usize) {
+        self.current ^= self.mask;
+        self.mask |= self.mask + self.bottom_cell(col);
+        self.moves += 1;
+    }
+
+    pub fn is_winning_move(&self, col: usize) -> bool {
+        self.winning_cells(self.current) & self.possible() & self.column(col) != 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.moves == self.rows * self.cols
+    }
+
+    // Columns from the centre outwards, which is usually the best order to try moves in
+    pub fn move_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.cols).collect();
+        order.sort_by_key(|&col| (2 * col as isize - self.cols as isize + 1).abs());
+        order
+    }
+
+    // The cells each move would fill
+    fn possible(&self) -> u64 {
+        (self.mask + self.bottom) & self.board_mask
+    }
+
+    // Empty cells that would complete a line of four for `discs`
+    fn winning_cells(&self, discs: u64) -> u64 {
+        let h = self.rows + 1;
+        // vertical
+        let mut cells = (discs << 1) & (discs << 2) & (discs << 3);
+        // horizontal and both diagonals
+        for shift in [h, h - 1, h + 1] {
+            let mut pair = (discs << shift) & (discs << (2 * shift));
+            cells |= pair & (discs << (3 * shift));
+            cells |= pair & (discs >> shift);
+            pair = (discs >> shift) & (discs >> (2 * shift));
+            cells |= pair & (discs << shift);
+            cells |= pair & (discs >> (3 * shift));
+        }
+        cells & (self.board_mask ^ self.mask)
+    }
+
+    fn column(&self, col: usize) -> u64 {
+        ((1u64 << self.rows) - 1) << (col * (self.rows + 1))
+    }
+
+    fn bottom_cell(&self, col: usize) -> u64 {
+        1u64 << (col * (self.rows + 1))
+    }
+
+    fn top_cell(&self, col: usize) -> u64 {
+        1u64 << (self.rows - 1 + col * (self.rows + 1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+// Depth-limited negamax with alpha-beta pruning and a transposition table.
+// Wins and losses within the search horizon are found exactly; beyond it positions are
+// scored by counting open threats.
+#[derive(Default)]
+pub struct Solver {
+    table: HashMap<u64, (u32, i32, Bound)>, // key -> (depth searched, score, bound)
+    nodes: u32,
+    stopped: bool, // The current search ran out of time, so its scores can't be trusted
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    // Best column for the player to move, looking `depth` plies ahead
+    pub fn best_move(&mut self, pos: &Position, depth: u32) -> Option<usize> {
+        self.search(pos, depth, &|| false)
+    }
+
+    // Search one ply deeper at a time until `max_depth` or the time budget runs out, and answer
+    // with the deepest search that finished. The one-ply search always finishes, so there is
+    // always a move while any column is open.
+    pub fn best_move_within(&mut self, pos: &Position, max_depth: u32, budget: Duration, time: &impl TimeSource) -> Option<usize> {
+        let deadline = time.now() + budget;
+        let out_of_time = || time.now() >= deadline;
+        let mut best = self.search(pos, 1, &|| false);
+        for depth in 2..=max_depth {
+            if out_of_time() {
+                break;
+            }
+            match self.search(pos, depth, &out_of_time) {
+                Some(col) if !self.stopped => best = Some(col),
+                _ => break,
+            }
+        }
+        best
+    }
+
+    fn search(&mut self, pos: &Position, depth: u32, out_of_time: &dyn Fn() -> bool) -> Option<usize> {
+        if self.table.len() > MAX_TABLE_SIZE {
+            self.table.clear();
+        }
+        self.nodes = 0;
+        self.stopped = false;
+        let mut best = None;
+        let mut alpha = -2 * WIN;
+        for col in pos.move_order() {
+            if !pos.can_play(col) {
+                continue;
+            }
+            if pos.is_winning_move(col) {
+                return Some(col);
+            }
+            let mut next = *pos;
+            next.play(col);
+            let score = -self.negamax(&next, depth.saturating_sub(1), -2 * WIN, -alpha, out_of_time);
+            if self.stopped {
+                return None;
+            }
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(col);
+            }
+        }
+        best
+    }
+
+    fn negamax(&mut self, pos: &Position, depth: u32, mut alpha: i32, mut beta: i32, out_of_time: &dyn Fn() -> bool) -> i32 {
+        self.nodes += 1;
+        if self.stopped || (self.nodes.is_multiple_of(NODES_PER_TIME_CHECK) && out_of_time()) {
+            self.stopped = true;
+            return 0;
+        }
+        let possible = pos.possible();
+        // the player to move wins right away
+        if pos.winning_cells(pos.current) & possible != 0 {
+            return WIN - pos.moves as i32;
+        }
+        if pos.is_full() {
+            return 0;
+        }
+
+        // moves that do not hand the opponent a win, either directly or by filling the cell below one
+        let opponent_wins = pos.winning_cells(pos.current ^ pos.mask);
+        let forced = opponent_wins & possible;
+        let mut candidates = possible & !(opponent_wins >> 1);
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                // two threats at once cannot both be blocked
+                return -(WIN - pos.moves as i32 - 1);
+            }
+            candidates &= forced;
+        }
+        if candidates == 0 {
+            return -(WIN - pos.moves as i32 - 1);
+        }
+        if depth == 0 {
+            return self.evaluate(pos);
+        }
+
+        let key = pos.key();
+        if let Some(&(searched, score, bound)) = self.table.get(&key) {
+            if searched >= depth {
+                match bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return score;
+                }
+            }
+        }
+
+        let alpha_start = alpha;
+        let mut best = -2 * WIN;
+        for col in pos.move_order() {
+            if candidates & pos.column(col) == 0 {
+                continue;
+            }
+            let mut next = *pos;
+            next.play(col);
+            let score = -self.negamax(&next, depth - 1, -beta, -alpha, out_of_time);
+            if self.stopped {
+                return 0; // the score is meaningless, and must not go in the table
+            }
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= alpha_start {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, (depth, best, bound));
+        best
+    }
+
+    // Heuristic score for the player to move: open threats count most, then centre control
+    fn evaluate(&self, pos: &Position) -> i32 {
+        let opponent = pos.current ^ pos.mask;
+        let threats = pos.winning_cells(pos.current).count_ones() as i32
+            - pos.winning_cells(opponent).count_ones() as i32;
+        let centre = pos.column(pos.cols / 2);
+        let centre_discs = (pos.current & centre).count_ones() as i32 - (opponent & centre).count_ones() as i32;
+        threats * 10 + centre_discs * 3
+    }
+}
+
+// The computer player: the opening book first, then search
+pub struct Ai {
+    book: Book,
+    solver: Solver,
+    depth: u32,
+}
+
+impl Ai {
+    pub fn new(depth: u32) -> Ai {
+        Ai {
+            book: Book::bundled(),
+            solver: Solver::new(),
+            depth,
+        }
+    }
+
+    // The column the computer wants to play within `budget`, if the position can be searched
+    pub fn choose(&mut self, board: &Board, budget: Duration, time: &impl TimeSource) -> Option<usize> {
+        let pos = Position::from_board(board)?;
+        self.book
+            .lookup(&pos)
+            .or_else(|| self.solver.best_move_within(&pos, self.depth, budget, time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A clock that never moves, so only the budget decides how deep the search goes
+    struct StoppedClock;
+
+    impl TimeSource for StoppedClock {
+        fn now(&self) -> Duration {
+            Duration::ZERO
+        }
+    }
+
+    fn position(moves: &[usize]) -> Position {
+        let mut board = Board::new(6, 7);
+        for &col in moves {
+            board.insert_disc(col).unwrap();
+        }
+        Position::from_board(&board).unwrap()
+    }
+
+    #[test]
+    fn boards_too_big_for_64_bits_are_refused() {
+        assert!(Position::new(6, 7).is_some());
+        assert!(Position::new(7, 9).is_none());
+        assert!(Position::new(8, 10).is_none());
+    }
+
+    #[test]
+    fn the_solver_takes_a_win_in_one() {
+        let pos = position(&[0, 1, 0, 1, 0, 6]);
+        assert_eq!(Solver::new().best_move(&pos, 8), Some(0));
+        // even with no time at all, the one-ply search finishes and sees the win
+        assert_eq!(Solver::new().best_move_within(&pos, 8, Duration::ZERO, &StoppedClock), Some(0));
+    }
+
+    #[test]
+    fn the_solver_blocks_a_loss_in_one() {
+        let pos = position(&[0, 1, 0, 1, 0]);
+        assert_eq!(Solver::new().best_move(&pos, 8), Some(0));
+        assert_eq!(Solver::new().best_move_within(&pos, 8, Duration::from_secs(1), &StoppedClock), Some(0));
+    }
+}
@@ -0,0 +1,7 @@
+// Entry point of the Web Worker that runs the computer players off the main thread
+use gloo_worker::Registrable;
+use yew_app::worker::AiWorker;
+
+fn main() {
+    AiWorker::registrar().register();
+}
@@ -0,0 +1,113 @@
+use crate::ai::Position;
+
+// Book file layout: the magic bytes, a version byte, the board rows and columns, then one
+// little-endian u64 per position sorted by key. The low 56 bits of an entry hold the position
+// key and the top byte holds the best reply's column, so a 6x7 book costs 8 bytes a position.
+const MAGIC: &[u8; 4] = b"C4BK";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 7;
+const KEY_MASK: u64 = (1 << 56) - 1;
+
+// A copy of the backend's book, built with `backend gen-book`
+const BUNDLED: &[u8] = include_bytes!("../book/connect4.book");
+
+// Precomputed best replies for the opening of one board size
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    rows: usize,
+    cols: usize,
+    entries: Vec<u64>,
+}
+
+impl Book {
+    // The bundled 6x7 book; an unreadable book just means the engine searches every move
+    pub fn bundled() -> Book {
+        Book::from_bytes(BUNDLED).unwrap_or_default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, &'static str> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err("Not an opening book");
+        }
+        if bytes[4] != VERSION {
+            return Err("Unsupported opening book version");
+        }
+        let body = &bytes[HEADER_LEN..];
+        if !body.len().is_multiple_of(8) {
+            return Err("Truncated opening book");
+        }
+        let entries: Vec<u64> = body
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("chunks are 8 bytes")))
+            .collect();
+        if entries.windows(2).any(|pair| pair[0] & KEY_MASK >= pair[1] & KEY_MASK) {
+            return Err("Opening book entries are not sorted");
+        }
+        Ok(Book {
+            rows: bytes[5] as usize,
+            cols: bytes[6] as usize,
+            entries,
+        })
+    }
+
+    // The book move for a position, if the book covers it
+    pub fn lookup(&self, pos: &Position) -> Option<usize> {
+        if pos.rows() != self.rows || pos.cols() != self.cols {
+            return None;
+        }
+        let i = self
+            .entries
+            .binary_search_by_key(&pos.key(), |entry| entry & KEY_MASK)
+            .ok()?;
+        Some((self.entries[i] >> 56) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 6x7 book file with the given version and entries
+    fn book_bytes(version: u8, entries: &[u64]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[version, 6, 7]);
+        for entry in entries {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn books_are_read_back_as_written() {
+        let entries = [1 | 3 << 56, 42 | 2 << 56, KEY_MASK | 6 << 56];
+        let book = Book::from_bytes(&book_bytes(VERSION, &entries)).unwrap();
+        assert_eq!((book.rows, book.cols), (6, 7));
+        assert_eq!(book.entries, entries);
+    }
+
+    #[test]
+    fn bad_books_are_rejected() {
+        let bytes = book_bytes(VERSION, &[1 | 3 << 56, 2 | 4 << 56]);
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(Book::from_bytes(&bad_magic).unwrap_err(), "Not an opening book");
+        assert_eq!(Book::from_bytes(&bytes[..3]).unwrap_err(), "Not an opening book");
+        assert_eq!(Book::from_bytes(&book_bytes(VERSION + 1, &[])).unwrap_err(), "Unsupported opening book version");
+        assert_eq!(Book::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), "Truncated opening book");
+        assert_eq!(
+            Book::from_bytes(&book_bytes(VERSION, &[2 | 4 << 56, 1 | 3 << 56])).unwrap_err(),
+            "Opening book entries are not sorted"
+        );
+    }
+
+    #[test]
+    fn the_bundled_book_knows_the_opening() {
+        let book = Book::bundled();
+        let start = Position::new(6, 7).unwrap();
+        let col = book.lookup(&start).expect("the empty board is in the book");
+        let mut reply = start;
+        reply.play(col);
+        assert!(book.lookup(&reply).is_some(), "the book answers every first move of the opponent");
+        assert_eq!(book.lookup(&Position::new(5, 6).unwrap()), None);
+    }
+}
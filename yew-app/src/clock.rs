@@ -0,0 +1,112 @@
+use serde::{Serialize, Deserialize};
+use std::time::Duration;
+
+// Where the clocks get the current time from. Games never read the time themselves,
+// so anything that counts time (the system clock, a browser, a fake in tests) can drive them.
+pub trait TimeSource {
+    // Time elapsed since the Unix epoch
+    fn now(&self) -> Duration;
+}
+
+// The browser's wall clock (std::time is not available on wasm32)
+pub struct BrowserTimeSource;
+
+impl TimeSource for BrowserTimeSource {
+    fn now(&self) -> Duration {
+        Duration::from_millis(js_sys::Date::now() as u64)
+    }
+}
+
+// Chess-style time control: every player starts with `initial_ms` and gains `increment_ms` per move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub initial_ms: u64,
+    pub increment_ms: u64,
+}
+
+// One clock per player. Only the player to move has their clock running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameClock<P> {
+    pub control: TimeControl,
+    players: Vec<P>,
+    remaining_ms: Vec<u64>,
+    running: Option<P>,
+    turn_started_ms: u64, // When the running clock was last started, in ms since the epoch
+}
+
+impl<P: Copy + PartialEq> GameClock<P> {
+    pub fn new(control: TimeControl, players: &[P]) -> GameClock<P> {
+        GameClock {
+            control,
+            players: players.to_vec(),
+            remaining_ms: vec![control.initial_ms; players.len()],
+            running: None,
+            turn_started_ms: 0,
+        }
+    }
+
+    // Start the clock of the player to move
+    pub fn start(&mut self, player: P, time: &impl TimeSource) {
+        self.running = Some(player);
+        self.turn_started_ms = time.now().as_millis() as u64;
+    }
+
+    // The running player has moved: charge their thinking time, add the increment and start `next`
+    pub fn press(&mut self, next: P, time: &impl TimeSource) {
+        if let Some(mover) = self.running {
+            let now = time.now().as_millis() as u64;
+            if let Some(i) = self.index(mover) {
+                let spent = now.saturating_sub(self.turn_started_ms);
+                self.remaining_ms[i] = self.remaining_ms[i].saturating_sub(spent).saturating_add(self.control.increment_ms);
+            }
+        }
+        self.start(next, time);
+    }
+
+    // Freeze all clocks, e.g. when the game is over
+    pub fn stop(&mut self, time: &impl TimeSource) {
+        if let Some(player) = self.running {
+            let left = self.remaining(player, time);
+            if let Some(i) = self.index(player) {
+                self.remaining_ms[i] = left.as_millis() as u64;
+            }
+        }
+        self.running = None;
+    }
+
+    // Time left for a player, counting the thinking time of a running clock
+    pub fn remaining(&self, player: P, time: &impl TimeSource) -> Duration {
+        let Some(i) = self.index(player) else {
+            return Duration::ZERO;
+        };
+        let mut left = self.remaining_ms[i];
+        if self.running == Some(player) {
+            let spent = (time.now().as_millis() as u64).saturating_sub(self.turn_started_ms);
+            left = left.saturating_sub(spent);
+        }
+        Duration::from_millis(left)
+    }
+
+    // The player whose time has run out, if any
+    pub fn flagged(&self, time: &impl TimeSource) -> Option<P> {
+        self.running.filter(|&player| self.remaining(player, time).is_zero())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    fn index(&self, player: P) -> Option<usize> {
+        self.players.iter().position(|&p| p == player)
+    }
+}
+
+// Formats a remaining time as m:ss, with tenths of a second in the last ten seconds
+pub fn format_remaining(time: Duration) -> String {
+    let ms = time.as_millis();
+    if ms < 10_000 {
+        format!("0:{:02}.{}", ms / 1000, (ms % 1000) / 100)
+    } else {
+        format!("{}:{:02}", ms / 60_000, (ms / 1000) % 60)
+    }
+}
@@ -7,6 +7,18 @@ use rand::seq::SliceRandom;
 pub enum Player {
     Red,
     Yellow,
+    Green,
+    Blue,
+}
+
+impl Player {
+    // Every player colour in seating order, used to build turn orders for 3-4 player games
+    pub const ALL: [Player; 4] = [Player::Red, Player::Yellow, Player::Green, Player::Blue];
+}
+
+// Two-player games (and saves made before multi-player support) go Red then Yellow
+fn default_turn_order() -> Vec<Player> {
+    vec![Player::Red, Player::Yellow]
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -23,6 +35,10 @@ pub struct Board {
     pub cols: usize,
     pub state: State,
     pub last_move: Option<(usize, usize)>, // Track the last move as (row, col)
+    #[serde(default = "default_turn_order")]
+    pub turn_order: Vec<Player>, // Players in the order they take turns
+    #[serde(default)]
+    pub moves: Vec<(Player, usize)>, // Every move so far as (player, column), oldest first
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -33,7 +49,7 @@ pub enum State {
 }
 
 impl Board {
-    // Initialize a new game board
+    // Initialize a new two-player game board
     pub fn new(rows: usize, cols: usize) -> Board {
         Board {
             grid: vec![vec![Cell::Empty; cols]; rows],
@@ -42,6 +58,32 @@ impl Board {
             cols,
             state: State::Running,
             last_move: None,
+            turn_order: default_turn_order(),
+            moves: Vec::new(),
+        }
+    }
+
+    // Initialize a board for any number of players, who move in the given order
+    pub fn with_players(rows: usize, cols: usize, turn_order: Vec<Player>) -> Result<Board, &'static str> {
+        if turn_order.len() < 2 {
+            return Err("At least two players are needed");
+        }
+        if turn_order.iter().enumerate().any(|(i, p)| turn_order[..i].contains(p)) {
+            return Err("A player can only appear once in the turn order");
+        }
+
+        let mut board = Board::new(rows, cols);
+        board.current_turn = turn_order[0];
+        board.turn_order = turn_order;
+        Ok(board)
+    }
+
+    // Suggested board size for a number of players, so 3-4 player games have room to move
+    pub fn dimensions_for(players: usize) -> (usize, usize) {
+        match players {
+            0..=2 => (6, 7),
+            3 => (7, 9),
+            _ => (8, 10),
         }
     }
 
@@ -100,6 +142,38 @@ impl Board {
         Ok(())
     }
 
+    // The cells of the line of four (or more) through the winning move, once the game is won
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        if !matches!(self.state, State::Won(_)) {
+            return None;
+        }
+        let (row, col) = self.last_move?;
+        let player = self.grid[row][col];
+
+        for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            let mut line = vec![(row, col)];
+            // walk away from the last move in both directions while the discs match
+            for sign in [-1, 1] {
+                let (mut r, mut c) = (row as isize, col as isize);
+                loop {
+                    r += dr * sign;
+                    c += dc * sign;
+                    if r < 0 || c < 0 || r >= self.rows as isize || c >= self.cols as isize
+                        || self.grid[r as usize][c as usize] != player
+                    {
+                        break;
+                    }
+                    line.push((r as usize, c as usize));
+                }
+            }
+            if line.len() >= 4 {
+                line.sort();
+                return Some(line);
+            }
+        }
+        None
+    }
+
     // Insert a disc into the specified column
     pub fn insert_disc(&mut self, col: usize) -> Result<(), &'static str> {
         if col >= self.cols {
@@ -110,6 +184,7 @@ impl Board {
             if let Cell::Empty = self.grid[row][col] {
                 self.grid[row][col] = Cell::Occupied(self.current_turn);
                 self.last_move = Some((row, col));
+                self.moves.push((self.current_turn, col));
                 if self.check_win(row, col) {
                     self.state = State::Won(self.current_turn);
                 } else if self.is_draw() {
@@ -137,12 +212,25 @@ impl Board {
     }
     
 
-    // Switch the current player's turn
+    // A player ran out of time. With two players the opponent wins; in bigger games
+    // the flagged player drops out and the last one standing wins.
+    pub fn flag(&mut self, player: Player) {
+        if self.state != State::Running || !self.turn_order.contains(&player) {
+            return;
+        }
+        if self.current_turn == player {
+            self.switch_turn();
+        }
+        self.turn_order.retain(|&p| p != player);
+        if self.turn_order.len() == 1 {
+            self.state = State::Won(self.turn_order[0]);
+        }
+    }
+
+    // Pass the turn to the next player in the turn order
     pub fn switch_turn(&mut self) {
-        self.current_turn = match self.current_turn {
-            Player::Red => Player::Yellow,
-            Player::Yellow => Player::Red,
-        };
+        let current = self.turn_order.iter().position(|&p| p == self.current_turn).unwrap_or(0);
+        self.current_turn = self.turn_order[(current + 1) % self.turn_order.len()];
     }
 
     // if the board is full it is a draw.
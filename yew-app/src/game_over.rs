@@ -0,0 +1,111 @@
+use crate::settings::Settings;
+use serde::{Deserialize, Serialize};
+use web_sys::window;
+use yew::prelude::*;
+
+// Wins per seat and draws, kept for as long as the browser tab is open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SessionScore {
+    wins: Vec<u32>, // indexed by seat: Player1 is 0, Player2 is 1, ...
+    draws: u32,
+}
+
+impl SessionScore {
+    // The score saved under `key` in sessionStorage, or a fresh one
+    pub fn load(key: &str) -> SessionScore {
+        window()
+            .and_then(|w| w.session_storage().ok().flatten())
+            .and_then(|storage| storage.get_item(key).ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, key: &str) {
+        let storage = window().and_then(|w| w.session_storage().ok().flatten());
+        if let (Some(storage), Ok(json)) = (storage, serde_json::to_string(self)) {
+            let _ = storage.set_item(key, &json);
+        }
+    }
+
+    // Count a finished game: the winner's seat, or None for a draw
+    pub fn record(&mut self, winner: Option<usize>) {
+        match winner {
+            Some(seat) => {
+                if self.wins.len() <= seat {
+                    self.wins.resize(seat + 1, 0);
+                }
+                self.wins[seat] += 1;
+            }
+            None => self.draws += 1,
+        }
+    }
+
+    pub fn wins(&self, seat: usize) -> u32 {
+        self.wins.get(seat).copied().unwrap_or(0)
+    }
+
+    pub fn draws(&self) -> u32 {
+        self.draws
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GameOverProps {
+    pub headline: AttrValue,        // e.g. "Player1 wins!"
+    pub icon: Option<AttrValue>,    // The winner's icon, if the game has icons
+    pub score: Vec<(AttrValue, u32)>, // Each player's name and wins this session
+    pub draws: u32,
+    pub sizes: Vec<(usize, usize)>, // Board sizes to offer, as (rows, cols)
+    pub size: (usize, usize),       // The size just played
+    pub swap_label: AttrValue,      // Who would start if sides are swapped
+    pub on_rematch: Callback<()>,
+    pub on_swap: Callback<()>,
+    pub on_size: Callback<(usize, usize)>,
+    pub on_close: Callback<()>,
+}
+
+// Announces the result and offers the next game
+#[function_component(GameOverDialog)]
+pub fn game_over_dialog(props: &GameOverProps) -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    html! {
+        <div class="modal-backdrop">
+            <div class="modal" role="dialog" aria-modal="true" aria-labelledby="game-over-title">
+                <h2 id="game-over-title" class="text_titles">
+                    {
+                        match &props.icon {
+                            Some(icon) => html! { <img src={icon.clone()} width="60" height="60" alt="" /> },
+                            None => html! {},
+                        }
+                    }
+                    { props.headline.clone() }
+                </h2>
+                <p>
+                    {
+                        props.score.iter()
+                            .map(|(name, wins)| format!("{}: {}", name, wins))
+                            .chain(std::iter::once(lang.f("game_over.draws", &[("draws", &props.draws)])))
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    }
+                </p>
+                <div>
+                    <button onclick={props.on_rematch.reform(|_| ())}>{ lang.t("game_over.rematch") }</button>
+                    <button onclick={props.on_swap.reform(|_| ())}>{ lang.f("game_over.swap", &[("player", &props.swap_label)]) }</button>
+                </div>
+                <div>
+                    <text>{ lang.t("game_over.new_board") }</text>
+                    {
+                        for props.sizes.iter().map(|&(rows, cols)| html! {
+                            <button onclick={props.on_size.reform(move |_| (rows, cols))} disabled={props.size == (rows, cols)}>
+                                { format!("{}x{}", rows, cols) }
+                            </button>
+                        })
+                    }
+                </div>
+                <button onclick={props.on_close.reform(|_| ())}>{ lang.t("game_over.close") }</button>
+            </div>
+        </div>
+    }
+}
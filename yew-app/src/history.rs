@@ -0,0 +1,116 @@
+use crate::i18n::Language;
+use crate::API_URL;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{window, Storage};
+use yew_app::{connect4, toot_otto};
+
+// localStorage key of this browser's own copy of the history, used when the backend can't be reached
+const STORAGE_KEY: &str = "gameHistory";
+// Only the most recent games are kept in the browser
+const MAX_GAMES: usize = 1000;
+
+// These match the backend's history.rs, which is what the API sends and expects
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GameKind {
+    ConnectFour,
+    TootOtto,
+}
+
+impl GameKind {
+    pub fn name(self, lang: Language) -> &'static str {
+        lang.t(match self {
+            GameKind::ConnectFour => "game.connect4",
+            GameKind::TootOtto => "game.toot_otto",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub game: GameKind,
+    pub players: Vec<String>,   // In seating order; Player1 first
+    pub winner: Option<String>, // None for a draw
+    pub date_ms: u64,           // When the game ended, in ms since the epoch
+    pub moves: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Replay>, // Missing for games recorded before replays were saved
+}
+
+// Every move of a game, enough to play it again from an empty board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Replay {
+    ConnectFour {
+        rows: usize,
+        cols: usize,
+        turn_order: Vec<connect4::Player>,
+        moves: Vec<(connect4::Player, usize)>, // (player, column), oldest first
+    },
+    TootOtto {
+        rows: usize,
+        cols: usize,
+        moves: Vec<(toot_otto::Player, usize, toot_otto::Piece)>, // (player, column, piece), oldest first
+    },
+}
+
+impl Replay {
+    pub fn plies(&self) -> usize {
+        match self {
+            Replay::ConnectFour { moves, .. } => moves.len(),
+            Replay::TootOtto { moves, .. } => moves.len(),
+        }
+    }
+}
+
+// Where a list of games came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Server,
+    Browser, // the backend couldn't be reached
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+fn load_local() -> Vec<GameRecord> {
+    storage()
+        .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_local(games: &[GameRecord]) {
+    let storage = storage();
+    if let (Some(storage), Ok(json)) = (storage, serde_json::to_string(games)) {
+        let _ = storage.set_item(STORAGE_KEY, &json);
+    }
+}
+
+// Save a finished game in this browser, and send it to the backend if it's running
+pub fn record_game(record: GameRecord) {
+    let mut games = load_local();
+    games.push(record.clone());
+    let excess = games.len().saturating_sub(MAX_GAMES);
+    games.drain(..excess);
+    save_local(&games);
+
+    spawn_local(async move {
+        // offline is fine, the game is already in the browser's copy
+        if let Ok(request) = Request::post(&format!("{}/history", API_URL)).json(&record) {
+            let _ = request.send().await;
+        }
+    });
+}
+
+// Every game, oldest first: from the backend when it answers, otherwise from this browser
+pub async fn load_history() -> (Vec<GameRecord>, Source) {
+    match Request::get(&format!("{}/history", API_URL)).send().await {
+        Ok(response) if response.ok() => match response.json::<Vec<GameRecord>>().await {
+            Ok(games) => (games, Source::Server),
+            Err(_) => (load_local(), Source::Browser),
+        },
+        _ => (load_local(), Source::Browser),
+    }
+}
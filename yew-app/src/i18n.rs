@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+use web_sys::{console, window};
+
+// The text of every page, one catalog per language. Keys are grouped by page, e.g. "home.title".
+// Adding a language is a new variant here, its catalog in i18n/ and its plural rule.
+const ENGLISH: &str = include_str!("../i18n/en.json");
+const FRENCH: &str = include_str!("../i18n/fr.json");
+
+// What a key missing from a catalog is looked up in instead
+const FALLBACK: Language = Language::English;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+// A message, or a message with a form for each plural category
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Text(String),
+    Plural { one: String, other: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Catalog {
+    #[serde(default)]
+    messages: HashMap<String, Entry>,
+    // Text that is kept in English and translated where it is shown, by its English text: error
+    // messages, which may come from the game engines or the backend, and player names saved in
+    // the game history
+    #[serde(default)]
+    english: HashMap<String, String>,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::French];
+
+    // Each language's name in itself, so it can be found whatever the page is showing
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+
+    // The BCP 47 code, for the page's lang attribute
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+        }
+    }
+
+    // The browser's preferred language if there is a catalog for it, and the fallback otherwise
+    pub fn from_browser() -> Language {
+        let preferred = window().and_then(|window| window.navigator().language()).unwrap_or_default();
+        Language::ALL
+            .into_iter()
+            .find(|language| preferred.split('-').next() == Some(language.code()))
+            .unwrap_or(FALLBACK)
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        static ENGLISH_CATALOG: OnceLock<Catalog> = OnceLock::new();
+        static FRENCH_CATALOG: OnceLock<Catalog> = OnceLock::new();
+        let (cell, json) = match self {
+            Language::English => (&ENGLISH_CATALOG, ENGLISH),
+            Language::French => (&FRENCH_CATALOG, FRENCH),
+        };
+        cell.get_or_init(|| {
+            serde_json::from_str(json).unwrap_or_else(|e| {
+                console::error_1(&format!("The {} catalog can't be read: {}", self.name(), e).into());
+                Catalog::default()
+            })
+        })
+    }
+
+    // Which plural form `n` takes. French counts 0 and 1 as singular, English only 1.
+    fn is_one(self, n: usize) -> bool {
+        match self {
+            Language::English => n == 1,
+            Language::French => n <= 1,
+        }
+    }
+
+    fn entry(self, key: &str) -> Option<&'static Entry> {
+        self.catalog().messages.get(key).or_else(|| {
+            let entry = FALLBACK.catalog().messages.get(key);
+            if self != FALLBACK || entry.is_none() {
+                console::warn_1(&format!("No {} text for \"{}\"", self.name(), key).into());
+            }
+            entry
+        })
+    }
+
+    // The text for `key`, or the key itself if no catalog has it
+    pub fn t(self, key: &'static str) -> &'static str {
+        match self.entry(key) {
+            Some(Entry::Text(text)) | Some(Entry::Plural { other: text, .. }) => text,
+            None => key,
+        }
+    }
+
+    // The text for `key` with each {name} filled in
+    pub fn f(self, key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+        fill(self.t(key), args)
+    }
+
+    // The form of `key` for `n` things, with {n} and each {name} filled in
+    pub fn plural(self, key: &'static str, n: usize, args: &[(&str, &dyn Display)]) -> String {
+        let text = match self.entry(key) {
+            Some(Entry::Plural { one, other }) => if self.is_one(n) { one } else { other },
+            Some(Entry::Text(text)) => text,
+            None => key,
+        };
+        fill(&text.replace("{n}", &n.to_string()), args)
+    }
+
+    // Text kept in English, translated if the catalog knows it
+    pub fn translate(self, english: &str) -> String {
+        self.catalog().english.get(english).cloned().unwrap_or_else(|| english.to_owned())
+    }
+}
+
+fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(text.to_owned(), |text, (name, value)| text.replace(&format!("{{{}}}", name), &value.to_string()))
+}
@@ -0,0 +1,32 @@
+// Icons the players can pick for their Connect Four discs. The images live in static/, which
+// trunk copies next to the app (see index.html). Adding an icon is a new line here and its png.
+pub struct Icon {
+    pub id: &'static str, // Saved in the settings, so don't rename it once released
+    pub name: &'static str, // The key of its name in the catalogs
+    pub url: &'static str,
+}
+
+pub const ICONS: &[Icon] = &[
+    Icon { id: "armor", name: "icon.armor", url: "/static/armor.png" },
+    Icon { id: "sword", name: "icon.sword", url: "/static/sword.png" },
+    Icon { id: "bow", name: "icon.bow", url: "/static/bow.png" },
+    Icon { id: "gem", name: "icon.gem", url: "/static/gem.png" },
+    Icon { id: "heart", name: "icon.heart", url: "/static/heart.png" },
+];
+
+pub const DEFAULT_PLAYER_ICON: &str = "armor";
+pub const DEFAULT_COMP_ICON: &str = "gem";
+
+pub fn find(id: &str) -> Option<&'static Icon> {
+    ICONS.iter().find(|icon| icon.id == id)
+}
+
+// The icon called `id`, or the one called `fallback` if there is no such icon (any more)
+pub fn find_or(id: &str, fallback: &str) -> &'static Icon {
+    find(id).or_else(|| find(fallback)).unwrap_or(&ICONS[0])
+}
+
+// Icons for extra players: the first ones nobody has picked yet
+pub fn unpicked<'a>(picked: &'a [&str]) -> impl Iterator<Item = &'static Icon> + 'a {
+    ICONS.iter().filter(move |icon| !picked.contains(&icon.id))
+}
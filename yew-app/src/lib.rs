@@ -0,0 +1,7 @@
+// Game logic shared by the web app and the AI worker, which are built as separate binaries
+pub mod ai;
+pub mod book;
+pub mod clock;
+pub mod connect4;
+pub mod toot_otto;
+pub mod worker;
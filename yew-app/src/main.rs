@@ -1,18 +1,62 @@
 use yew::prelude::*;
 // use yew::events::InputData;
 use yew_router::prelude::*;
-mod connect4;
-use connect4::{Board, Cell, Player, State};
+use yew_app::connect4::{self, Board, Cell, Player, State};
 
-mod toot_otto;
-use toot_otto::{
+use yew_app::toot_otto::{self,
     Board as TootBoard, Cell as TootCell, Piece, Player as TootPlayer, State as TootState,
 };
 
+use yew_app::clock::{format_remaining, BrowserTimeSource, GameClock, TimeControl};
+use yew_app::worker::{AiRequest, AiResponse, AiWorker, AI_WORKER_LOADER};
+use gloo_timers::callback::{Interval, Timeout};
+use gloo_worker::{Spawnable, WorkerBridge};
+use std::rc::Rc;
+
+mod game_over;
+use game_over::{GameOverDialog, SessionScore};
+
+mod history;
+use history::{record_game, GameKind, GameRecord, Replay};
+
+mod scores;
+use scores::{History, Scores};
+
+mod online;
+use online::OnlinePlay;
+
+mod replay;
+use replay::ReplayViewer;
+
+mod sandbox;
+use sandbox::Sandbox;
+
+mod puzzles;
+use puzzles::Puzzles;
+
+mod tutorial;
+use tutorial::Tutorial;
+
+mod icons;
+use icons::ICONS;
+
+mod saved_game;
+use saved_game::{CONNECT4_GAME_KEY, TOOT_OTTO_GAME_KEY};
+
+mod settings;
+use settings::{Difficulty, FirstMover, PlayerAsTootOtto, Settings, SettingsAction, SettingsProvider, Theme};
+
+mod sound;
+use sound::{MuteToggle, Sound};
+
+mod pwa;
+
+mod i18n;
+use i18n::Language;
+
 use serde::{Deserialize, Serialize};
 use serde_json::*;
 use std::io::{self, Write};
-use web_sys::window;
 
 #[derive(Clone, Routable, PartialEq)]
 enum Route {
@@ -24,156 +68,98 @@ enum Route {
     TootOttoGame,
     #[at("/instructions")]
     Instructions,
+    #[at("/scores")]
+    Scores,
+    #[at("/history")]
+    History,
+    #[at("/replay")]
+    Replay,
+    #[at("/replay/:date_ms")]
+    ReplayGame { date_ms: u64 },
+    #[at("/sandbox")]
+    Sandbox,
+    #[at("/sandbox/:position")]
+    SandboxPosition { position: String },
+    #[at("/puzzles")]
+    Puzzles,
+    #[at("/online")]
+    Online,
     #[not_found]
     #[at("/404")]
     NotFound,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
-enum Difficulty {
-    None,
-    Easy,
-    Hard,
-}
-
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
-enum PlayerIcon {
-    Option1,
-    Option2,
-}
-const ARMOR_IMG_URL: &str =
-    "https://raw.githubusercontent.com/kooner27/421_projects/main/yew-app/static/armor.png";
-const SWORD_IMG_URL: &str =
-    "https://raw.githubusercontent.com/kooner27/421_projects/main/yew-app/static/sword.png";
-
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
-enum CompIcon {
-    Option3,
-    Option4,
-}
-const GEM_IMG_URL: &str =
-    "https://raw.githubusercontent.com/kooner27/421_projects/main/yew-app/static/gem.png";
-const HEART_IMG_URL: &str =
-    "https://raw.githubusercontent.com/kooner27/421_projects/main/yew-app/static/heart.png";
-
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
-enum PlayerAsTootOtto {
-    PlayerToot,
-    PlayerOtto,
-}
-
-#[derive(Properties, Clone, PartialEq, Serialize, Deserialize)]
-struct AppState {
-    difficulty: Difficulty,
-    player_icon: PlayerIcon,
-    comp_icon: CompIcon,
-    player_as_toot_otto: PlayerAsTootOtto,
-}
-impl AppState {
-    fn new() -> Self {
-        Self {
-            difficulty: Difficulty::None,
-            player_icon: PlayerIcon::Option1,
-            comp_icon: CompIcon::Option3,
-            player_as_toot_otto: PlayerAsTootOtto::PlayerToot,
-        }
-    }
-}
+// Where `backend serve` listens
+const API_URL: &str = "http://localhost:3000/api";
 
-use lazy_static::lazy_static;
-use std::sync::{Arc, Mutex};
+// How long the computer "thinks" before it moves, so its move doesn't land in the same instant as the human's
+const COMPUTER_THINKING_MS: u32 = 600;
+// How long the computer may search for a move, once it has an answer at all
+const COMPUTER_BUDGET_MS: u32 = 1_000;
 
-lazy_static! {
-    static ref APP_STATE: Arc<Mutex<AppState>> = Arc::new(Mutex::new(AppState::new()));
-}
+// Board sizes the game-over dialog offers, as (rows, cols)
+const CONNECT4_SIZES: [(usize, usize); 3] = [(6, 7), (7, 9), (8, 10)];
+const TOOT_OTTO_SIZES: [(usize, usize); 2] = [(4, 6), (6, 9)];
 
-fn save_state(state: &AppState) {
-    let window = window().expect("no global `window` exists");
-    let storage = window.local_storage().unwrap().unwrap();
-    storage
-        .set_item("appState", &serde_json::to_string(state).unwrap())
-        .unwrap();
+// Whether the hard computer can search a Connect Four board this size. Bigger boards don't fit its
+// 64-bit positions, so it falls back to the easier heuristic there.
+fn searchable(rows: usize, cols: usize) -> bool {
+    yew_app::ai::Position::new(rows, cols).is_some()
 }
+// sessionStorage keys for the running score of each game
+const CONNECT4_SCORE_KEY: &str = "connect4Score";
+const TOOT_OTTO_SCORE_KEY: &str = "tootOttoScore";
+
+// Clock presets offered on the game pages, as minutes+increment seconds
+const TIME_CONTROLS: [(&str, Option<TimeControl>); 4] = [
+    ("none", None),
+    ("1+0", Some(TimeControl { initial_ms: 60_000, increment_ms: 0 })),
+    ("3+2", Some(TimeControl { initial_ms: 180_000, increment_ms: 2_000 })),
+    ("5+3", Some(TimeControl { initial_ms: 300_000, increment_ms: 3_000 })),
+];
 
-fn load_state() -> Option<AppState> {
-    let window = window().expect("no global `window` exists");
-    let storage = window.local_storage().unwrap().unwrap();
-    let state_json = storage.get_item("appState").unwrap()?;
-    Some(serde_json::from_str(&state_json).unwrap())
-}
-//alternative version to return state, incase serialized data returned is not compatible with AppState
-// fn load_state() -> Result<AppState, serde_json::Error> {
-//     let window = window().expect("no global `window` exists");
-//     let storage = window.local_storage().unwrap().unwrap();
-//     let state_json = storage.get_item("appState").unwrap()?;
-//     serde_json::from_str(&state_json)
-// }
 
 #[function_component(Home)]
 fn home() -> Html {
-    let mut app_state = APP_STATE.lock().unwrap();
-
-    // Load state when the application starts
-    let loaded_state = load_state();
-    if let Some(state) = loaded_state {
-        *app_state = state;
-    }
-    // // Load state when the application starts
-    // match load_state() {
-    //     Ok(state) => *app_state = state,
-    //     Err(e) => {
-    //         // Handle the error here, e.g. log it or show an error message
-    //         console::log_1(&format!("Failed to load state: {}", e).into());
-    //     }
-    // }
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let app_state = (*settings).clone();
+    let (player_icon, comp_icon) = app_state.icons();
+    // Unfinished games can be continued; starting a new one throws the old one away.
+    // Loading can clear a bad save, so it happens once when the page opens rather than on every render.
+    let connect4_saved = *use_state(|| saved_game::load(CONNECT4_GAME_KEY, Connect4Saved::is_playable).is_some());
+    let toot_otto_saved = *use_state(|| saved_game::load(TOOT_OTTO_GAME_KEY, TootOttoSaved::is_playable).is_some());
+    let online = pwa::use_online();
 
     let on_player_icon_change = {
-        let app_state = Arc::clone(&APP_STATE);
-        Callback::from(move |_| {
-            let mut app_state = app_state.lock().unwrap();
-            let value = if app_state.player_icon == PlayerIcon::Option1 {
-                PlayerIcon::Option2
-            } else {
-                PlayerIcon::Option1
-            };
-            app_state.player_icon = value;
-
-            // Save state when the player icon changes
-            save_state(&app_state);
-        })
+        let settings = settings.clone();
+        Callback::from(move |icon: &'static str| settings.dispatch(SettingsAction::PlayerIcon(icon.to_owned())))
     };
 
     let on_comp_icon_change = {
-        let app_state = Arc::clone(&APP_STATE);
-        Callback::from(move |_| {
-            let mut app_state = app_state.lock().unwrap();
-            let value = if app_state.comp_icon == CompIcon::Option3 {
-                CompIcon::Option4
-            } else {
-                CompIcon::Option3
-            };
-            app_state.comp_icon = value;
+        let settings = settings.clone();
+        Callback::from(move |icon: &'static str| settings.dispatch(SettingsAction::CompIcon(icon.to_owned())))
+    };
 
-            // Save state when the computer icon changes
-            save_state(&app_state);
-        })
+    let on_player_select_toototto = {
+        let settings = settings.clone();
+        Callback::from(move |word: PlayerAsTootOtto| settings.dispatch(SettingsAction::PlayerAsTootOtto(word)))
     };
 
-    // let on_player_select_toototto = {
-    //     let app_state = Arc::clone(&APP_STATE);
-    //     Callback::from(move |_| {
-    //         let mut app_state = app_state.lock().unwrap();
-    //         let value = if app_state.player_as_toot_otto == PlayerAsTootOtto::PlayerToot {
-    //             PlayerAsTootOtto::PlayerOtto
-    //         } else {
-    //             PlayerAsTootOtto::PlayerToot
-    //         };
-    //         app_state.player_as_toot_otto = value;
+    let on_first_mover_change = {
+        let settings = settings.clone();
+        Callback::from(move |first_mover: FirstMover| settings.dispatch(SettingsAction::FirstMover(first_mover)))
+    };
 
-    //         // Save state when the computer icon changes
-    //         save_state(&app_state);
-    //     })
-    // };
+    let on_theme_change = {
+        let settings = settings.clone();
+        Callback::from(move |theme: Theme| settings.dispatch(SettingsAction::Theme(theme)))
+    };
+
+    let on_language_change = {
+        let settings = settings.clone();
+        Callback::from(move |language: Language| settings.dispatch(SettingsAction::Language(language)))
+    };
+    let lang = app_state.language;
 
     // let on_difficulty_change = {
     //     let app_state = Arc::clone(&APP_STATE);
@@ -191,42 +177,93 @@ fn home() -> Html {
 
     html! {
         <div>
-            <h1 class="text_titles">{ "Welcome to our game center!" }</h1>
-            <p>{ "We have simple implementations of Connect Four, and, Toot and Otto, using Yew, WASM, and Rust." }</p>
+            <h1 class="text_titles">{ lang.t("home.title") }</h1>
+            <p>{ lang.t("home.intro") }</p>
+            <nav>
+                <text>{ lang.t("home.instructions_for") }</text>
+                <Link<Route> to={Route::Instructions}>{ lang.t("home.instructions") }</Link<Route>>
+            </nav>
             <nav>
-                <text>{ "Instructions for each game:  " }</text>
-                <Link<Route> to={Route::Instructions}>{ "Instructions" }</Link<Route>>
+                <text>{ lang.t("home.past_games") }</text>
+                <Link<Route> to={Route::Scores}>{ lang.t("home.scores") }</Link<Route>>
+                <text>{ " | " }</text>
+                <Link<Route> to={Route::History}>{ lang.t("home.history") }</Link<Route>>
+                <text>{ " | " }</text>
+                <Link<Route> to={Route::Replay}>{ lang.t("home.replay") }</Link<Route>>
+                <text>{ " | " }</text>
+                <Link<Route> to={Route::Sandbox}>{ lang.t("home.sandbox") }</Link<Route>>
+                <text>{ " | " }</text>
+                <Link<Route> to={Route::Puzzles}>{ lang.t("home.daily_puzzle") }</Link<Route>>
+            </nav>
+            <nav>
+                <text>{ lang.t("home.play_someone") }</text>
+                <Link<Route> to={Route::Online}>{ lang.t("home.online") }</Link<Route>>
+                { if online { "" } else { lang.t("home.offline") } }
             </nav>
 
-            <h1 class="text_titles">{ "I want to play Connect Four..." }</h1>
-            <p>{ "Select an icon for Player1:" }</p>
+            <p>{ lang.t("home.language") }</p>
+            <div class="radio-buttons">
+                {
+                    for Language::ALL.into_iter().map(|language| html! {
+                        <label class="l-radio" for={format!("language-{}", language.code())}>
+                            <input type="radio" id={format!("language-{}", language.code())} name="language" onclick={on_language_change.reform(move |_| language)} checked={lang == language} />
+                            <span lang={language.code()}>{ language.name() }</span>
+                        </label>
+                    })
+                }
+            </div>
+
+            <p>{ lang.t("home.first_mover") }</p>
             <div class="radio-buttons" style="display: flex; align-items: center;">
-                <img src={ARMOR_IMG_URL} width="80" height="80" />
-                <label class="l-radio" for="option1">
-                    <input type="radio" id="option1" name="player_icon" value="option1" onclick={on_player_icon_change.clone()} checked={app_state.player_icon == PlayerIcon::Option1} />
-                    <span>{"Armor"}</span>
+                <label class="l-radio" for="first_player1">
+                    <input type="radio" id="first_player1" name="first_mover" value="player1" onclick={on_first_mover_change.reform(|_| FirstMover::Player1)} checked={app_state.first_mover == FirstMover::Player1} />
+                    <span>{ lang.t("player.1") }</span>
                 </label>
-                
-                <label class="l-radio" for="option2">
-                    <input type="radio" id="option2" name="player_icon" value="option2" onclick={on_player_icon_change} checked={app_state.player_icon == PlayerIcon::Option2} />
-                    <span>{"Spear"}</span>
+                <label class="l-radio" for="first_player2">
+                    <input type="radio" id="first_player2" name="first_mover" value="player2" onclick={on_first_mover_change.reform(|_| FirstMover::Player2)} checked={app_state.first_mover == FirstMover::Player2} />
+                    <span>{ lang.t("home.player2_or_computer") }</span>
                 </label>
-                <img src={SWORD_IMG_URL} width="70" height="70" />
             </div>
 
-            <p>{ "Select an icon for Player2/Computer:" }</p>
+            <p>{ lang.t("home.colours") }</p>
+            <div class="radio-buttons">
+                {
+                    for Theme::ALL.into_iter().map(|theme| html! {
+                        <label class="l-radio" for={format!("theme-{:?}", theme)}>
+                            <input type="radio" id={format!("theme-{:?}", theme)} name="theme" onclick={on_theme_change.reform(move |_| theme)} checked={app_state.theme == theme} />
+                            <span>{ theme.name(lang) }</span>
+                        </label>
+                    })
+                }
+            </div>
+            <p><MuteToggle /></p>
+
+            <h1 class="text_titles">{ lang.t("home.play_connect4") }</h1>
+            // one radio per icon in icons.rs; an icon the other player has taken can't be picked
+            <p>{ lang.t("home.player1_icon") }</p>
             <div class="radio-buttons" style="display: flex; align-items: center;">
-                <img src={GEM_IMG_URL} width="90" height="90" />
-                <label class="l-radio" for="option3">
-                    <input type="radio" id="option3" name="comp_icon" value="option3" onclick={on_comp_icon_change.clone()} checked={app_state.comp_icon == CompIcon::Option3} />
-                    <span>{"Gem"}</span>
-                </label>
-                
-                <label class="l-radio" for="option4">
-                    <input type="radio" id="option4" name="comp_icon" value="option4" onclick={on_comp_icon_change} checked={app_state.comp_icon == CompIcon::Option4} />
-                    <span>{"Heart"}</span>
-                </label>
-                <img src={HEART_IMG_URL} width="80" height="80" />
+                {
+                    for ICONS.iter().map(|icon| html! {
+                        <label class="l-radio" for={format!("player_icon-{}", icon.id)}>
+                            <input type="radio" id={format!("player_icon-{}", icon.id)} name="player_icon" onclick={on_player_icon_change.reform(move |_| icon.id)} checked={player_icon.id == icon.id} disabled={comp_icon.id == icon.id} />
+                            <img src={icon.url} alt="" width="60" height="60" />
+                            <span>{ lang.t(icon.name) }</span>
+                        </label>
+                    })
+                }
+            </div>
+
+            <p>{ lang.t("home.player2_icon") }</p>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                {
+                    for ICONS.iter().map(|icon| html! {
+                        <label class="l-radio" for={format!("comp_icon-{}", icon.id)}>
+                            <input type="radio" id={format!("comp_icon-{}", icon.id)} name="comp_icon" onclick={on_comp_icon_change.reform(move |_| icon.id)} checked={comp_icon.id == icon.id} disabled={player_icon.id == icon.id} />
+                            <img src={icon.url} alt="" width="60" height="60" />
+                            <span>{ lang.t(icon.name) }</span>
+                        </label>
+                    })
+                }
             </div>
 
             // <p>{ "Select the play mode ('none' for 2-human players, 'easy' for easy computer opponent, 'hard' for hard computer opponent):" }</p>
@@ -242,28 +279,43 @@ fn home() -> Html {
 
             <nav>
                 <Link<Route> to={Route::Game}>
-                    <button class="btn-forward">
+                    <button class="btn-forward" onclick={Callback::from(|_| saved_game::clear(CONNECT4_GAME_KEY))}>
                         <span class="circle" aria-hidden="true">
                             <span class="icon arrow"></span>
                         </span>
-                        <span class="button-text">{"Play Connect Four"}</span>
+                        <span class="button-text">{ lang.t(if connect4_saved { "home.new_connect4" } else { "home.start_connect4" }) }</span>
                     </button>
                 </Link<Route>>
+                {
+                    if connect4_saved {
+                        html! {
+                            <Link<Route> to={Route::Game}>
+                                <button class="btn-forward">
+                                    <span class="circle" aria-hidden="true">
+                                        <span class="icon arrow"></span>
+                                    </span>
+                                    <span class="button-text">{ lang.t("home.continue") }</span>
+                                </button>
+                            </Link<Route>>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
             </nav>
 
-            <h1 class="text_titles">{ "I want to play TooT and Otto..." }</h1>
-            <p>{ "Player 1 is TOOT and always starts first, the Player2/Computer goes after." }</p>
-            // <p>{ "Select player1's word (and player2/computer will be other word):" }</p>
-            // <div class="radio-buttons" style="display: flex; align-items: center;">
-            //     <label class="l-radio" for="TOOT">
-            //         <input type="radio" id="TOOT" name="player1" value="TOOT" onclick={on_player_select_toototto.clone()} checked={app_state.player_as_toot_otto == PlayerAsTootOtto::PlayerToot} />
-            //         <span>{"TOOT"}</span>
-            //     </label>
-            //     <label class="l-radio" for="OTTO">
-            //         <input type="radio" id="OTTO" name="player1" value="OTTO" onclick={on_player_select_toototto} checked={app_state.player_as_toot_otto == PlayerAsTootOtto::PlayerOtto} />
-            //         <span>{"OTTO"}</span>
-            //     </label>
-            // </div>
+            <h1 class="text_titles">{ lang.t("home.play_toot_otto") }</h1>
+            <p>{ lang.t("home.player1_word") }</p>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                <label class="l-radio" for="TOOT">
+                    <input type="radio" id="TOOT" name="player1" value="TOOT" onclick={on_player_select_toototto.reform(|_| PlayerAsTootOtto::PlayerToot)} checked={app_state.player_as_toot_otto == PlayerAsTootOtto::PlayerToot} />
+                    <span>{"TOOT"}</span>
+                </label>
+                <label class="l-radio" for="OTTO">
+                    <input type="radio" id="OTTO" name="player1" value="OTTO" onclick={on_player_select_toototto.reform(|_| PlayerAsTootOtto::PlayerOtto)} checked={app_state.player_as_toot_otto == PlayerAsTootOtto::PlayerOtto} />
+                    <span>{"OTTO"}</span>
+                </label>
+            </div>
 
             // <p>{ "Select the play mode ('none' for 2-human players, 'easy' for easy computer opponent, 'hard' for hard computer opponent):" }</p>
             // <div style="display: flex; align-items: center;">
@@ -276,13 +328,27 @@ fn home() -> Html {
             //     <label for="hard">{"Hard"}</label>
             // </div>
             <nav>
-            <Link<Route> to={Route::TootOttoGame}><button class="btn-forward">
+            <Link<Route> to={Route::TootOttoGame}><button class="btn-forward" onclick={Callback::from(|_| saved_game::clear(TOOT_OTTO_GAME_KEY))}>
                 <span class="circle" aria-hidden="true">
                         <span class="icon arrow"></span>
                     </span>
-                    <span class="button-text">{"Play Toot and Otto"}</span>
+                    <span class="button-text">{ lang.t(if toot_otto_saved { "home.new_toot_otto" } else { "home.start_toot_otto" }) }</span>
                 </button>
             </Link<Route>>
+            {
+                if toot_otto_saved {
+                    html! {
+                        <Link<Route> to={Route::TootOttoGame}><button class="btn-forward">
+                            <span class="circle" aria-hidden="true">
+                                <span class="icon arrow"></span>
+                            </span>
+                            <span class="button-text">{ lang.t("home.continue") }</span>
+                        </button></Link<Route>>
+                    }
+                } else {
+                    html! {}
+                }
+            }
         </nav>
         </div>
     }
@@ -290,6 +356,8 @@ fn home() -> Html {
 
 #[function_component(Instructions)]
 fn instructions() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
     html! {
         <div class="content_padding">
             <Link<Route> to={Route::Home}>
@@ -297,50 +365,52 @@ fn instructions() -> Html {
                     <span class="circle" aria-hidden="true">
                         <span class="icon arrow"></span>
                     </span>
-                    <span class="button-text">{"Back to Home"}</span>
+                    <span class="button-text">{ lang.t("nav.back_home") }</span>
                 </button>
             </Link<Route>>
-            <h1 class="text_titles">{ "Instructions" }</h1>
-            <h2 class="text_titles">{ "How to play our Connect Four:" }</h2>
-            <p>{ "Connect Four is a two-player connection game in which the players take turns
-            dropping their disc/icons from the top into a seven-column, six-row vertically suspended 
-            grid. The objective of the game is to be the first to form a horizontal, vertical, 
-            or diagonal line of four of one's own discs/icons. In our implementation, 
-            a player can play against a person or a computer (with easy or hard modes); 
-            there are different two icons, per player, to choose between as their representative icon of the game; 
-            the Player1 goes first, so Player2/Computer is always second to play;
-            a tie is made when the board fills without a winner." }</p>
-
-            { "For more information on the official Connect Four game, click: " }
-            <a href="https://en.wikipedia.org/wiki/Connect_Four">{ "here" }</a>
+            <h1 class="text_titles">{ lang.t("instructions.title") }</h1>
+            <Tutorial />
+
+            <h2 class="text_titles">{ lang.t("instructions.connect4_title") }</h2>
+            <p>{ lang.t("instructions.connect4") }</p>
+
+            { lang.t("instructions.connect4_more") }
+            <a href={lang.t("instructions.connect4_link")}>{ lang.t("instructions.here") }</a>
             <br/>
 
-            <h2 class="text_titles">{ "How to play our Toot and Otto:" }</h2>
-            <p>{ "Toot and Otto is a two-player connection game in which the players take turns
-            dropping their selections of O or T (as representations of disc/icons) 
-            from the top into a six-column, four-row vertically suspended 
-            grid. Players choose if they will represent Toot or Otto before the game begins. 
-            The objective of the game is to be the first to form a horizontal, vertical, 
-            or diagonal spelling of one's own name (i.e. if they are Toot, they want to make TOOT somewhere in the grid). 
-            In our implementation, 
-            a player can play against a person or a computer (with easy or hard modes); 
-            the Player1 goes first and is always TOOT (in the official game, TOOT always goes first); 
-            so Player2/Computer is always OTTO;
-            a tie is made when the board fills without a winner." }</p>
-            { "For more information on the official Toot and Otto game, click: " }
-            <a href="https://boardgamegeek.com/boardgame/19530/toot-and-otto">{ "here" }</a>
+            <h2 class="text_titles">{ lang.t("instructions.toot_otto_title") }</h2>
+            <p>{ lang.t("instructions.toot_otto") }</p>
+            { lang.t("instructions.toot_otto_more") }
+            <a href="https://boardgamegeek.com/boardgame/19530/toot-and-otto">{ lang.t("instructions.here") }</a>
             <br/>
+
+            <h2 class="text_titles">{ lang.t("instructions.keyboard_title") }</h2>
+            <p>{ lang.t("instructions.keyboard") }</p>
+            <ul style="display: inline-block; text-align: left;">
+                <li>{ lang.t("instructions.keyboard_columns") }</li>
+                <li>{ lang.t("instructions.keyboard_piece") }</li>
+                <li>{ lang.t("instructions.keyboard_drop") }</li>
+            </ul>
+            <p>{ lang.t("instructions.screen_readers") }</p>
         </div>
     }
 }
 
+#[function_component(NotFound)]
+fn not_found() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    html! { <h1>{ settings.language.t("not_found") }</h1> }
+}
+
 #[function_component(App)]
 fn app() -> Html {
     html! {
-        <BrowserRouter>
-            // <Switch<Route> render={Switch::render(switch)} />
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+        <SettingsProvider>
+            <BrowserRouter>
+                // <Switch<Route> render={Switch::render(switch)} />
+                <Switch<Route> render={switch} />
+            </BrowserRouter>
+        </SettingsProvider>
     }
 }
 
@@ -350,7 +420,15 @@ fn switch(routes: Route) -> Html {
         Route::Game => html! { <ConnectFourGame /> },
         Route::TootOttoGame => html! { <TootOttoGame /> },
         Route::Instructions => html! { <Instructions /> },
-        Route::NotFound => html! { <h1>{ "404 Not Found" }</h1> },
+        Route::Scores => html! { <Scores /> },
+        Route::History => html! { <History /> },
+        Route::Replay => html! { <ReplayViewer /> },
+        Route::ReplayGame { date_ms } => html! { <ReplayViewer date_ms={Some(date_ms)} /> },
+        Route::Sandbox => html! { <Sandbox /> },
+        Route::SandboxPosition { position } => html! { <Sandbox position={Some(AttrValue::from(position))} /> },
+        Route::Puzzles => html! { <Puzzles /> },
+        Route::Online => html! { <OnlinePlay /> },
+        Route::NotFound => html! { <NotFound /> },
     }
 }
 
@@ -362,6 +440,7 @@ fn switch(routes: Route) -> Html {
 // }
 
 fn main() {
+    pwa::register_service_worker();
     yew::Renderer::<App>::new().render();
 }
 
@@ -375,111 +454,566 @@ fn main() {
 //     }
 // }
 
-#[function_component(ConnectFourGame)]
-fn connect_four_game() -> Html {
-    let mut app_state_borrowed = APP_STATE.lock().unwrap();
+// Class for the piece in a cell: the piece that just landed falls into place
+fn drop_class(dropping: Option<(usize, usize)>, row: usize, col: usize) -> Classes {
+    classes!((dropping == Some((row, col))).then_some("dropping"))
+}
 
-    // Load state when the application starts
-    let loaded_state = load_state();
-    if let Some(state) = loaded_state {
-        *app_state_borrowed = state;
+// What a key pressed on a game board asks for
+enum BoardKey {
+    Column(ColumnStep),
+    Drop,
+    Piece(Piece),
+}
+
+enum ColumnStep {
+    Left,
+    Right,
+    First,
+    Last,
+}
+
+fn board_key(key: &str) -> Option<BoardKey> {
+    match key {
+        "ArrowLeft" => Some(BoardKey::Column(ColumnStep::Left)),
+        "ArrowRight" => Some(BoardKey::Column(ColumnStep::Right)),
+        "Home" => Some(BoardKey::Column(ColumnStep::First)),
+        "End" => Some(BoardKey::Column(ColumnStep::Last)),
+        "Enter" | " " => Some(BoardKey::Drop),
+        "t" | "T" => Some(BoardKey::Piece(Piece::T)),
+        "o" | "O" => Some(BoardKey::Piece(Piece::O)),
+        _ => None,
     }
+}
+
+// The column the keyboard moves to; the first arrow press starts from the middle
+fn step_column(current: Option<usize>, step: ColumnStep, cols: usize) -> usize {
+    match (step, current) {
+        (ColumnStep::Left, Some(col)) => col.saturating_sub(1),
+        (ColumnStep::Right, Some(col)) => (col + 1).min(cols - 1),
+        (ColumnStep::Left | ColumnStep::Right, None) => cols / 2,
+        (ColumnStep::First, _) => 0,
+        (ColumnStep::Last, _) => cols - 1,
+    }
+}
 
-    let players_icon = match app_state_borrowed.player_icon {
-        PlayerIcon::Option1 => ARMOR_IMG_URL,
-        PlayerIcon::Option2 => SWORD_IMG_URL,
+// What screen readers hear after each Connect Four move: the move, then the result or who is next
+fn connect4_announcement(lang: Language, board: &Board) -> String {
+    let players = board.turn_order.len();
+    let last_move = board.last_move.and_then(|(row, col)| match board.grid[row][col] {
+        Cell::Occupied(player) => Some(lang.f("announce.disc_dropped", &[("player", &seat_name(lang, player, players)), ("col", &(col + 1))])),
+        Cell::Empty => None,
+    });
+    let next = match board.state {
+        State::Won(player) => lang.f("announce.wins", &[("player", &seat_name(lang, player, players))]),
+        State::Draw => lang.t("announce.draw").to_owned(),
+        State::Running => lang.f("announce.to_move", &[("player", &seat_name(lang, board.current_turn, players))]),
     };
-    let comp_icon = match app_state_borrowed.comp_icon {
-        CompIcon::Option3 => GEM_IMG_URL,
-        CompIcon::Option4 => HEART_IMG_URL,
+    last_move.unwrap_or_default() + &next
+}
+
+// The same for Toot and Otto; `name` says who plays each word
+fn toot_otto_announcement(lang: Language, board: &TootBoard, name: impl Fn(TootPlayer) -> String) -> String {
+    let last_move = board.last_move.and_then(|(row, col)| match board.grid[row][col] {
+        TootCell::Occupied(piece) => Some(lang.f("announce.piece_dropped", &[("piece", &format!("{:?}", piece)), ("col", &(col + 1))])),
+        TootCell::Empty => None,
+    });
+    let next = match board.state {
+        TootState::Won(player) => lang.f("announce.wins", &[("player", &name(player))]),
+        TootState::Draw => lang.t("announce.draw").to_owned(),
+        TootState::Running => lang.f("announce.to_move", &[("player", &name(board.current_turn))]),
+    };
+    last_move.unwrap_or_default() + &next
+}
+
+// Everything a Connect Four game is started from, so a rematch can start the same game again
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Connect4Setup {
+    players: usize,
+    // Every colour in the order picked on the game page; the game's players take turns in this order
+    #[serde(default = "seating_order")]
+    seating: [Player; 4],
+    rows: usize,
+    cols: usize,
+    first_mover: FirstMover,
+    control: Option<TimeControl>,
+}
+
+impl Connect4Setup {
+    fn board(&self) -> Board {
+        Board::with_players(self.rows, self.cols, connect4_turn_order(self.seating, self.players, self.first_mover))
+            .expect("every seated player once is always a valid turn order")
+    }
+}
+
+// Everything a TOOT-OTTO game is started from; Player1's word comes from the settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct TootOttoSetup {
+    rows: usize,
+    cols: usize,
+    first_mover: FirstMover,
+    control: Option<TimeControl>,
+}
+
+impl TootOttoSetup {
+    fn board(&self, player1: PlayerAsTootOtto) -> TootBoard {
+        TootBoard::starting_with(self.rows, self.cols, toot_otto_first_player(player1, self.first_mover))
+    }
+}
+
+// An unfinished Connect Four game as it is saved, so a reload or a trip to another page can pick it up again
+#[derive(Serialize, Deserialize)]
+struct Connect4Saved {
+    setup: Connect4Setup,
+    board: Board,
+    clock: Option<GameClock<Player>>,
+}
+
+impl Connect4Saved {
+    // What serde can't check, so a save from an older or newer app can't crash the page
+    fn is_playable(&self) -> bool {
+        let b = &self.board;
+        b.state == State::Running
+            && (b.rows, b.cols) == (self.setup.rows, self.setup.cols)
+            && b.grid.len() == b.rows
+            && b.grid.iter().all(|line| line.len() == b.cols)
+            // players who ran out of time have left the turn order, but nobody else can be in it
+            && b.turn_order.len() >= 2
+            && b.turn_order.iter().all(|p| Player::ALL.iter().take(self.setup.players).any(|q| q == p))
+            && b.turn_order.iter().enumerate().all(|(i, p)| !b.turn_order[..i].contains(p))
+            && b.turn_order.contains(&b.current_turn)
+    }
+}
+
+// The same for Toot and Otto, with the word Player1 was playing
+#[derive(Serialize, Deserialize)]
+struct TootOttoSaved {
+    setup: TootOttoSetup,
+    player1: PlayerAsTootOtto,
+    board: TootBoard,
+    clock: Option<GameClock<TootPlayer>>,
+}
+
+impl TootOttoSaved {
+    fn is_playable(&self) -> bool {
+        let b = &self.board;
+        b.state == TootState::Running
+            && (b.rows, b.cols) == (self.setup.rows, self.setup.cols)
+            && b.grid.len() == b.rows
+            && b.grid.iter().all(|line| line.len() == b.cols)
+    }
+}
+
+// A saved clock carries on where it was, except that the turn in progress starts over
+fn resume_clock<P: Copy + PartialEq>(clock: &Option<GameClock<P>>, to_move: P) -> Option<GameClock<P>> {
+    clock.clone().map(|mut c| {
+        if c.is_running() {
+            c.start(to_move, &BrowserTimeSource);
+        }
+        c
+    })
+}
+
+fn other_mover(first_mover: FirstMover) -> FirstMover {
+    match first_mover {
+        FirstMover::Player1 => FirstMover::Player2,
+        FirstMover::Player2 => FirstMover::Player1,
+    }
+}
+
+fn seating_order() -> [Player; 4] {
+    Player::ALL
+}
+
+// A seating with these players first, in this order, and everyone else after them
+fn seating_from(turn_order: &[Player]) -> [Player; 4] {
+    let mut seating = Player::ALL;
+    let rest = Player::ALL.into_iter().filter(|p| !turn_order.contains(p));
+    for (seat, player) in seating.iter_mut().zip(turn_order.iter().copied().chain(rest)) {
+        *seat = player;
+    }
+    seating
+}
+
+// Turn order for a Connect Four game: the seated players in the picked order, starting with the
+// next one if Player2 (or the computer) moves first
+fn connect4_turn_order(seating: [Player; 4], players: usize, first_mover: FirstMover) -> Vec<Player> {
+    let mut order: Vec<Player> = seating.into_iter().filter(|p| Player::ALL[..players].contains(p)).collect();
+    if first_mover == FirstMover::Player2 {
+        order.rotate_left(1);
+    }
+    order
+}
+
+// Which word moves first in TOOT-OTTO, given the word Player1 plays
+fn toot_otto_first_player(player1: PlayerAsTootOtto, first_mover: FirstMover) -> TootPlayer {
+    match (player1, first_mover) {
+        (PlayerAsTootOtto::PlayerToot, FirstMover::Player1) | (PlayerAsTootOtto::PlayerOtto, FirstMover::Player2) => TootPlayer::Toot,
+        (PlayerAsTootOtto::PlayerOtto, FirstMover::Player1) | (PlayerAsTootOtto::PlayerToot, FirstMover::Player2) => TootPlayer::Otto,
+    }
+}
+
+// A fresh clock for a new game, already running for the first player
+fn new_clock<P: Copy + PartialEq>(control: Option<TimeControl>, players: &[P], first: P) -> Option<GameClock<P>> {
+    control.map(|control| {
+        let mut clock = GameClock::new(control, players);
+        clock.start(first, &BrowserTimeSource);
+        clock
+    })
+}
+
+// Hand the clock over to the next player after a move, or stop it once the game is over
+fn advance_clock<P: Copy + PartialEq>(clock: &UseStateHandle<Option<GameClock<P>>>, next: P, running: bool) {
+    if let Some(mut c) = (**clock).clone() {
+        if running {
+            c.press(next, &BrowserTimeSource);
+        } else {
+            c.stop(&BrowserTimeSource);
+        }
+        clock.set(Some(c));
+    }
+}
+
+fn player_label(lang: Language, player: Player) -> &'static str {
+    lang.t(match player {
+        Player::Red => "player.1",
+        Player::Yellow => "player.2",
+        Player::Green => "player.3",
+        Player::Blue => "player.4",
+    })
+}
+
+// How a Connect Four player is shown; in two-player games Yellow may be the computer
+fn seat_name(lang: Language, player: Player, players: usize) -> &'static str {
+    if players == 2 && player == Player::Yellow {
+        lang.t("home.player2_or_computer")
+    } else {
+        player_label(lang, player)
+    }
+}
+
+// How the computer is named in the game history, so games against each difficulty are told apart
+fn computer_name(difficulty: Difficulty) -> String {
+    format!("Computer ({:?})", difficulty)
+}
+
+// The name a Connect Four player is saved under in the game history. Names are saved in English
+// whatever language the page is in, so the scores add up; the catalogs translate them for display.
+fn history_name(player: Player, players: usize, difficulty: Difficulty) -> String {
+    if players == 2 && player == Player::Yellow && difficulty != Difficulty::None {
+        computer_name(difficulty)
+    } else {
+        player_label(Language::English, player).to_owned()
+    }
+}
+
+// A CSS colour for the player; each theme in styles.scss has its own
+fn player_color(player: Player) -> &'static str {
+    match player {
+        Player::Red => "var(--player-red)",
+        Player::Yellow => "var(--player-yellow)",
+        Player::Green => "var(--player-green)",
+        Player::Blue => "var(--player-blue)",
+    }
+}
+
+// The style of a board's container. styles.scss sizes the cells from the column count,
+// up to 80px and smaller when that's what it takes to fit the screen.
+fn board_style(cols: usize) -> String {
+    format!("--cols: {};", cols)
+}
+
+// Sends requests to a page's AI worker, numbering them so only the answer to the latest one is used
+#[derive(Clone)]
+struct AiSender {
+    bridge: Rc<WorkerBridge<AiWorker>>,
+    last_id: Rc<std::cell::Cell<u32>>,
+}
+
+impl AiSender {
+    // `request` builds the request from its id
+    fn send(&self, request: impl FnOnce(u32) -> AiRequest) {
+        let id = self.last_id.get().wrapping_add(1);
+        self.last_id.set(id);
+        self.bridge.send(request(id));
+    }
+
+    // Whether `answer` replies to the last request sent, rather than one that has been superseded
+    fn is_latest(&self, answer: &AiResponse) -> bool {
+        answer.id() == self.last_id.get()
+    }
+}
+
+// A Web Worker for the page's computer player, and the last answer it sent back.
+// The worker shuts down when the page goes away, once any search it is running has finished.
+#[hook]
+fn use_ai_worker() -> (AiSender, UseStateHandle<Option<AiResponse>>) {
+    let answer = use_state(|| None);
+    let bridge = {
+        let answer = answer.setter();
+        use_memo((), move |_| {
+            AiWorker::spawner()
+                .callback(move |response| answer.set(Some(response)))
+                .spawn_with_loader(AI_WORKER_LOADER)
+        })
     };
+    let last_id = use_memo((), |_| std::cell::Cell::new(0));
+    (AiSender { bridge, last_id }, answer)
+}
+
+#[function_component(ConnectFourGame)]
+fn connect_four_game() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let app_state_borrowed = (*settings).clone();
+
+    let icons = app_state_borrowed.connect4_icons();
+    let icon_for = move |player: Player| icons[Player::ALL.iter().position(|&p| p == player).unwrap_or(0)];
 
     let on_difficulty_change = {
-        let app_state = Arc::clone(&APP_STATE);
+        let settings = settings.clone();
         Callback::from(move |d: &str| {
-            let mut app_state = app_state.lock().unwrap();
-            app_state.difficulty = match d {
+            let difficulty = match d {
                 "none" => Difficulty::None,
                 "easy" => Difficulty::Easy,
                 "hard" => Difficulty::Hard,
                 _ => Difficulty::None,
             };
-
-            // Save state when the difficulty changes
-            save_state(&app_state);
+            settings.dispatch(SettingsAction::Difficulty(difficulty));
         })
     };
 
-    let board = use_state(|| Board::new(6, 7)); // Initialize the board
+    let difficulty = app_state_borrowed.difficulty;
+    let muted = app_state_borrowed.muted;
+    let lang = app_state_borrowed.language;
+    // An unfinished game from an earlier visit carries on where it was left
+    let resumed = use_state(|| saved_game::load(CONNECT4_GAME_KEY, Connect4Saved::is_playable));
+    // How the game on screen was started; new games and rematches start from it
+    let setup = use_state(|| match &*resumed {
+        Some(saved) => saved.setup,
+        None => Connect4Setup {
+            players: 2,
+            seating: seating_order(),
+            rows: 6,
+            cols: 7,
+            first_mover: app_state_borrowed.first_mover,
+            control: None,
+        },
+    });
+    let board = use_state(|| match &*resumed {
+        Some(saved) => saved.board.clone(),
+        None => setup.board(),
+    });
     let hovered_col: UseStateHandle<Option<usize>> = use_state(|| None);
 
-    let current_player = match board.current_turn {
-        Player::Red => "Player1",
-        Player::Yellow => "Player2",
-    };
+    let current_player = player_label(lang, board.current_turn);
+    let current_player_icon = icon_for(board.current_turn);
 
-    let current_player_icon = match board.current_turn {
-        Player::Red => players_icon,
-        Player::Yellow => comp_icon,
-    };
+    let clock: UseStateHandle<Option<GameClock<Player>>> =
+        use_state(|| resumed.as_ref().and_then(|saved| resume_clock(&saved.clock, saved.board.current_turn)));
 
-    let player1_done = use_state(|| false);
-    let on_column_click_comp = {
+    // Keep the game saved while it can be continued: after the first move and until it is over
+    use_effect_with((*setup, (*board).clone(), (*clock).clone()), |(setup, board, clock)| {
+        if board.state == State::Running && !board.moves.is_empty() {
+            let saved = Connect4Saved { setup: *setup, board: board.clone(), clock: clock.clone() };
+            saved_game::save(CONNECT4_GAME_KEY, &saved);
+        } else {
+            saved_game::clear(CONNECT4_GAME_KEY);
+        }
+    });
+
+    // Tick the clocks while a timed game runs, and end the game for whoever runs out of time
+    {
         let board = board.clone();
-        let app_state_borrowed = app_state_borrowed.clone();
-        let player1_clicked = player1_done.clone();
-        Callback::from(move |_col: usize| {
-            let mut b = (*board).clone();
-            // if the current status is player vs. computer
-            match app_state_borrowed.difficulty {
-                Difficulty::None => (),
-                Difficulty::Easy => {
-                    if let Err(e) = b.computer_move() {
-                        println!("Error: {}", e);
-                    } else {
-                        board.set(b);
-                        player1_clicked.set(false);
-                    }
-                }
-                Difficulty::Hard => {
-                    if let Some((_row, col)) = b.last_move {
-                        if let Err(e) = b.computer_move_hard(col) {
-                            println!("Error: {}", e);
+        let clock = clock.clone();
+        let force_update = use_force_update();
+        use_effect_with(((*board).clone(), (*clock).clone()), move |(b, c)| {
+            let b = b.clone();
+            let interval = c.clone().filter(|c| c.is_running()).map(|c| {
+                Interval::new(100, move || {
+                    if let Some(player) = c.flagged(&BrowserTimeSource) {
+                        let mut b = b.clone();
+                        b.flag(player);
+                        let mut c = c.clone();
+                        if b.state == State::Running {
+                            c.start(b.current_turn, &BrowserTimeSource);
                         } else {
-                            println!("Hard moved");
-                            board.set(b);
-                            player1_clicked.set(false);
+                            c.stop(&BrowserTimeSource);
                         }
+                        board.set(b);
+                        clock.set(Some(c));
                     } else {
-                        if let Err(e) = b.computer_move_hard(_col) {
-                            println!("Error: {}", e);
-                        } else {
-                            println!("Hard moved");
-                            board.set(b);
-                            player1_clicked.set(false);
-                        }
+                        force_update.force_update();
+                    }
+                })
+            });
+            move || drop(interval)
+        });
+    }
+
+    // The computer plays Player2 (Yellow) in two-player games, thinking in a Web Worker.
+    // Whenever the board changes and it is the computer's turn, an effect sends the board to the
+    // worker after a short pause; a new board (a human move, a new game, a flag) cancels a pause
+    // that hasn't finished. The worker sends the board back with its move, and the move is only
+    // played if that is still the board on screen, so the computer moves exactly once per turn,
+    // never once the game is over, and answers for a restarted game are dropped.
+    let computer_to_move = difficulty != Difficulty::None
+        && board.turn_order.len() == 2
+        && board.state == State::Running
+        && board.current_turn == Player::Yellow;
+    // Where the last disc landed, found with predict_disc before it is played, so it can fall into place
+    let dropping: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+    let (ai_worker, ai_answer) = use_ai_worker();
+    {
+        let ai_worker = ai_worker.clone();
+        use_effect_with(((*board).clone(), computer_to_move, difficulty), move |(b, computer_to_move, difficulty)| {
+            let (board, difficulty) = (b.clone(), *difficulty);
+            let request = move |id| AiRequest::ConnectFour { id, board, difficulty, budget_ms: COMPUTER_BUDGET_MS };
+            let timeout = computer_to_move.then(|| Timeout::new(COMPUTER_THINKING_MS, move || ai_worker.send(request)));
+            move || drop(timeout)
+        });
+    }
+    {
+        let board = board.clone();
+        let clock = clock.clone();
+        let dropping = dropping.clone();
+        let answer = (*ai_answer).clone();
+        // keyed on the request id, so the same move for the same board in a new game is still played
+        use_effect_with(ai_answer.as_ref().map(AiResponse::id), move |_| {
+            if let Some(reply @ AiResponse::ConnectFour { board: asked, col: Some(col), .. }) = &answer {
+                if ai_worker.is_latest(reply) && *asked == *board && computer_to_move {
+                    let mut b = (*board).clone();
+                    let landing = b.predict_disc(*col);
+                    if b.insert_disc(*col).is_ok() {
+                        dropping.set(landing);
+                        sound::play(Sound::Drop, muted);
+                        advance_clock(&clock, b.current_turn, b.state == State::Running);
+                        board.set(b);
                     }
                 }
             }
+        });
+    }
+
+    let predicted_pos: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+
+    // Counts the games played on this page, so each finished game is scored exactly once
+    let game_number = use_state(|| 0u32);
+    let dialog_closed = use_state(|| false);
+    let score = use_state(|| SessionScore::load(CONNECT4_SCORE_KEY));
+    {
+        let score = score.clone();
+        let finished = (*board).clone();
+        // flags can drop players from the board's turn order, so the players and the replay come from the setup
+        let turn_order = setup.board().turn_order;
+        let players = setup.players;
+        use_effect_with((*game_number, board.state), move |(_, state)| {
+            let result = match *state {
+                State::Won(player) => Some(Player::ALL.iter().position(|&p| p == player)),
+                State::Draw => Some(None),
+                State::Running => None,
+            };
+            if let Some(winner) = result {
+                let mut s = (*score).clone();
+                s.record(winner);
+                s.save(CONNECT4_SCORE_KEY);
+                score.set(s);
+
+                // a loss is the computer beating its human opponent
+                let computer_won = players == 2 && difficulty != Difficulty::None && winner == Some(1);
+                sound::play(
+                    match winner {
+                        None => Sound::Draw,
+                        Some(_) if computer_won => Sound::Loss,
+                        Some(_) => Sound::Win,
+                    },
+                    muted,
+                );
+                let discs = finished.grid.iter().flatten().filter(|&&cell| cell != Cell::Empty).count();
+                record_game(GameRecord {
+                    game: GameKind::ConnectFour,
+                    players: Player::ALL[..players].iter().map(|&p| history_name(p, players, difficulty)).collect(),
+                    winner: winner.map(|seat| history_name(Player::ALL[seat], players, difficulty)),
+                    date_ms: js_sys::Date::now() as u64,
+                    moves: discs,
+                    // games saved from the sandbox by older versions have discs that no move put there
+                    replay: (finished.moves.len() == discs).then(|| Replay::ConnectFour {
+                        rows: finished.rows,
+                        cols: finished.cols,
+                        turn_order,
+                        moves: finished.moves.clone(),
+                    }),
+                });
+            }
+        });
+    }
+
+    // Start a fresh game, dropping whatever the computer was thinking about
+    let start_game = {
+        let setup = setup.clone();
+        let board = board.clone();
+        let predicted_pos = predicted_pos.clone();
+        let dropping = dropping.clone();
+        let clock = clock.clone();
+        let game_number = game_number.clone();
+        let dialog_closed = dialog_closed.clone();
+        Callback::from(move |next: Connect4Setup| {
+            let b = next.board();
+            predicted_pos.set(None);
+            dropping.set(None);
+            clock.set(new_clock(next.control, &b.turn_order, b.current_turn));
+            board.set(b);
+            setup.set(next);
+            game_number.set(*game_number + 1);
+            dialog_closed.set(false);
+        })
+    };
+    let current_setup = *setup;
+
+    // Changing the number of players starts a fresh game on a board sized for that many players
+    let on_players_change = start_game.reform(move |players: usize| {
+        let (rows, cols) = Board::dimensions_for(players);
+        Connect4Setup { players, rows, cols, ..current_setup }
+    });
+
+    // Moving a player earlier in the turn order starts a fresh game in the new order
+    let setup_order = current_setup.board().turn_order;
+    let on_move_earlier = {
+        let setup_order = setup_order.clone();
+        start_game.reform(move |i: usize| {
+            let mut order = setup_order.clone();
+            order.swap(i - 1, i);
+            Connect4Setup { seating: seating_from(&order), first_mover: FirstMover::Player1, ..current_setup }
         })
     };
 
-    let predicted_pos: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+    // Picking a time control starts a fresh timed (or untimed) game with the same players
+    let on_time_control_change = start_game.reform(move |control: Option<TimeControl>| Connect4Setup { control, ..current_setup });
 
     let on_column_click = {
         let board = board.clone();
         let hovered_col = hovered_col.clone();
-        let player1_done = player1_done.clone();
         let predicted_pos = predicted_pos.clone();
+        let clock = clock.clone();
+        let dropping = dropping.clone();
         Callback::from(move |col: usize| {
+            // the human has to wait while the computer is thinking
+            if computer_to_move {
+                return;
+            }
             let mut b = (*board).clone(); // Clone the current board state
-            b.insert_disc(col).ok(); // Ignore errors for simplicity
+            let landing = b.predict_disc(col);
+            if b.insert_disc(col).is_ok() {
+                dropping.set(landing);
+                sound::play(Sound::Drop, muted);
+                advance_clock(&clock, b.current_turn, b.state == State::Running);
+            } else {
+                sound::play(Sound::Invalid, muted);
+            }
             let b_cpy = b.clone();
             if b.state != connect4::State::Running {
                 hovered_col.set(None);
                 predicted_pos.set(None);
             }
-            player1_done.set(true);
             board.set(b); // Update the board state
             predicted_pos.set(b_cpy.predict_disc(col));
         })
@@ -504,16 +1038,36 @@ fn connect_four_game() -> Html {
         })
     };
 
-    let pixel_size = "80px";
-    let grid_style = format!(
-        "display: grid; text-align: center; grid-template-columns: repeat({}, {}); grid-auto-rows: {};",
-        board.cols, pixel_size, pixel_size
-    );
+    // The board takes the keyboard: arrows (and Home/End) choose a column, Enter or Space drops into it
+    let keyboard_col: UseStateHandle<Option<usize>> = use_state(|| None);
+    let is_enabled = board.state == State::Running && !computer_to_move;
+    let on_board_key = {
+        let keyboard_col = keyboard_col.clone();
+        let handle_mouseover = handle_mouseover.clone();
+        let on_column_click = on_column_click.clone();
+        let cols = board.cols;
+        Callback::from(move |e: KeyboardEvent| {
+            match board_key(&e.key()) {
+                Some(BoardKey::Column(step)) => {
+                    let col = step_column(*keyboard_col, step, cols);
+                    keyboard_col.set(Some(col));
+                    if is_enabled {
+                        handle_mouseover.emit(col);
+                    }
+                }
+                Some(BoardKey::Drop) => {
+                    if let (Some(col), true) = (*keyboard_col, is_enabled) {
+                        on_column_click.emit(col);
+                    }
+                }
+                _ => return,
+            }
+            e.prevent_default();
+        })
+    };
 
-    let cell_style_hovered = "
-    background-color: lightgray;
-    box-shadow: 2px 2px 5px rgba(0, 0, 0, 0.3);
-    ";
+    let winning = board.winning_line().unwrap_or_default();
+    let num_players = board.turn_order.len();
 
     html! {
         <>
@@ -522,61 +1076,141 @@ fn connect_four_game() -> Html {
                     <span class="circle" aria-hidden="true">
                         <span class="icon arrow"></span>
                     </span>
-                    <span class="button-text">{"Back to Home"}</span>
+                    <span class="button-text">{ lang.t("nav.back_home") }</span>
                 </button>
             </Link<Route>>
-            <h2 class="text_titles">{ "Connect Four" }</h2>
+            <h2 class="text_titles">{ GameKind::ConnectFour.name(lang) }</h2>
 
             <h2 class="radio-buttons" style="display: flex; align-items: center;">
-                { format!("Status: ") }
-                { format!("Player1 - ") }
-                <img src={players_icon} width="75" height="75" />
-                { format!(", Player2/Computer - ") }
-                <img src={comp_icon} width="75" height="75" />
+                { lang.t("game.status") }
+                {
+                    for board.turn_order.iter().enumerate().map(|(i, &player)| html! {
+                        <>
+                            { if i > 0 { ", " } else { "" } }
+                            { format!("{} - ", seat_name(lang, player, num_players)) }
+                            <img src={icon_for(player)} alt="" width="75" height="75" style={format!("border-bottom: 6px solid {};", player_color(player))} />
+                        </>
+                    })
+                }
             </h2>
             <div class="radio-buttons" style="display: flex; align-items: center;">
-                <text>{ "Robot: "}</text>
+                <text>{ lang.t("game.players") }</text>
+                {
+                    for (2..=4).map(|players| html! {
+                        <label class="l-radio" for={format!("players{}", players)}>
+                            <input type="radio" id={format!("players{}", players)} name="players" onclick={on_players_change.reform(move |_| players)} checked={num_players == players} />
+                            <span>{ players }</span>
+                        </label>
+                    })
+                }
+            </div>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                <text>{ lang.t("game.turn_order") }</text>
+                {
+                    for setup_order.iter().enumerate().map(|(i, &player)| {
+                        let name = seat_name(lang, player, num_players);
+                        html! {
+                            <>
+                                {
+                                    if i > 0 {
+                                        html! {
+                                            <button onclick={on_move_earlier.reform(move |_| i)} aria-label={lang.f("game.move_earlier", &[("player", &name)])}>{ "<" }</button>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
+                                <span style={format!("margin: 0 8px; border-bottom: 4px solid {};", player_color(player))}>{ name }</span>
+                            </>
+                        }
+                    })
+                }
+            </div>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                <text>{ lang.t("game.robot") }</text>
                 <label class="l-radio" for="none">
                     <input type="radio" id="none" name="difficulty" value="none" onclick={on_difficulty_change.reform(move |_| "none")} checked={app_state_borrowed.difficulty == Difficulty::None} />
-                    <span>{"none"}</span>
+                    <span>{ lang.t("game.robot_none") }</span>
                 </label>
                 <label class="l-radio" for="easy">
                     <input type="radio" id="easy" name="difficulty" value="easy" onclick={on_difficulty_change.reform(move |_| "easy")} checked={app_state_borrowed.difficulty == Difficulty::Easy} />
-                    <span>{"easy"}</span>
+                    <span>{ lang.t("game.robot_easy") }</span>
                 </label>
                 <label class="l-radio" for="hard">
                     <input type="radio" id="hard" name="difficulty" value="hard" onclick={on_difficulty_change.reform(move |_| "hard")} checked={app_state_borrowed.difficulty == Difficulty::Hard}/>
-                    <span>{"hard"}</span>
+                    <span>{ lang.t("game.robot_hard") }</span>
                 </label>
+                {
+                    if num_players > 2 {
+                        html! { <text>{ lang.t("game.robot_two_players") }</text> }
+                    } else if difficulty == Difficulty::Hard && !searchable(board.rows, board.cols) {
+                        html! { <text>{ lang.t("game.robot_big_board") }</text> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                <text>{ lang.t("game.clock") }</text>
+                {
+                    for TIME_CONTROLS.iter().map(|&(name, control)| html! {
+                        <label class="l-radio" for={format!("clock-{}", name)}>
+                            <input type="radio" id={format!("clock-{}", name)} name="clock" onclick={on_time_control_change.reform(move |_| control)} checked={current_setup.control == control} />
+                            <span>{ if control.is_some() { name } else { lang.t("game.clock_none") } }</span>
+                        </label>
+                    })
+                }
+                // starting over also drops whatever the computer was thinking about
+                <button style="margin-left: 16px;" onclick={start_game.reform(move |_| current_setup)}>{ lang.t("game.new_game") }</button>
+                <MuteToggle />
             </div>
             <p class="radio-buttons" style="display: flex; align-items: center;">
-                { format!("Current turn: ") }
-                <img src={current_player_icon} width="30" height="30" />
+                { lang.t("game.current_turn") }
+                <img src={current_player_icon} alt="" width="30" height="30" />
                 { format!(" ({})", current_player) }
+                { if computer_to_move { html! { <span class="thinking">{ lang.t("game.thinking") }</span> } } else { html! {} } }
+                {
+                    match &*clock {
+                        Some(c) if board.state == State::Running => lang.f("game.time_left", &[("time", &format_remaining(c.remaining(board.current_turn, &BrowserTimeSource)))]),
+                        _ => String::new(),
+                    }
+                }
             </p>
+            {
+                match &*clock {
+                    Some(c) => html! {
+                        <p>
+                            {
+                                board.turn_order.iter()
+                                    .map(|&p| format!("{} {}", player_label(lang, p), format_remaining(c.remaining(p, &BrowserTimeSource))))
+                                    .collect::<Vec<_>>()
+                                    .join(" | ")
+                            }
+                        </p>
+                    },
+                    None => html! {},
+                }
+            }
 
-            <div class="container-connect4">
-                <div class="grid" style={grid_style.clone()}>
+            <p class="visually-hidden" aria-live="polite">{ connect4_announcement(lang, &board) }</p>
+            <div class="container-connect4" style={board_style(board.cols)}>
+                <div class="grid" role="group" tabindex="0" onkeydown={on_board_key}
+                    aria-label={lang.f("game.connect4_board", &[("rows", &board.rows), ("cols", &board.cols)])}>
                     {
                         for board.grid.iter().enumerate().map(|(row, line)| {
                             html! {
                                 {
                                     for line.iter().enumerate().map(|(col, &cell)| {
-                                        let mut cell_style: &str = "";
-                                        if let Some(hovered_col) = *hovered_col {
-                                            if hovered_col == col {
-                                                cell_style = cell_style_hovered;
-                                            }
+                                        let piece_class = drop_class(*dropping, row, col);
+                                        let label = match cell {
+                                            connect4::Cell::Empty => lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &lang.t("board.empty"))]),
+                                            connect4::Cell::Occupied(player) => lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &seat_name(lang, player, num_players))]),
                                         };
-                                        let is_enabled = matches!(board.state, connect4::State::Running);
-                                        // robot move
-                                        if *player1_done == true {
-                                            on_column_click_comp.emit(col);
-                                        }
                                         html! {
                                             <button
-                                            class="cell"
-                                            style={cell_style}
+                                            class={classes!("cell", winning.contains(&(row, col)).then_some("winning"), (*keyboard_col == Some(col)).then_some("keyboard-column"), (*hovered_col == Some(col)).then_some("hovered"))}
+                                            aria-label={label}
+                                            tabindex="-1"
                                             onmouseenter={
                                                 if is_enabled {
                                                     handle_mouseover.reform(move |_| col)
@@ -594,7 +1228,7 @@ fn connect_four_game() -> Html {
                                                             // UI prediction of the future position of next move
                                                             if let Some(pos) = *predicted_pos {
 
-                                                                if row == pos.0 && col == pos.1 && is_enabled { html! { <img src={current_player_icon} style="opacity:0.6; transform: translate(-13px, -8px);" width="80" height="80"/> } }
+                                                                if row == pos.0 && col == pos.1 && is_enabled { html! { <img src={current_player_icon} alt="" style="opacity:0.6;" /> } }
 
                                                                 else {html! {}}
                                                             } else {
@@ -602,8 +1236,9 @@ fn connect_four_game() -> Html {
                                                             }
                                                         },
 
-                                                        connect4::Cell::Occupied(Player::Red) => html! { <img src={players_icon} style="transform: translate(-13px, -8px);" width="80" height="80" /> },
-                                                        connect4::Cell::Occupied(Player::Yellow) => html! { <img src={comp_icon} style="transform: translate(-13px, -8px);" width="80" height="80" /> },
+                                                        connect4::Cell::Occupied(player) if num_players == 2 => html! { <img class={piece_class} src={icon_for(player)} alt="" style={format!("--drop-rows: {};", row + 1)} /> },
+                                                        // with more than two players, a coloured ring tells the icons apart at a glance
+                                                        connect4::Cell::Occupied(player) => html! { <img class={piece_class} src={icon_for(player)} alt="" style={format!("border-radius: 50%; box-shadow: inset 0 0 0 5px {}; --drop-rows: {};", player_color(player), row + 1)} /> },
 
                                                     }
                                                 }
@@ -622,15 +1257,45 @@ fn connect_four_game() -> Html {
                         State::Won(player) => html! {
                             <p>
                                 {
-                                    format!("{} wins! Refresh to reset game.", if player == Player::Red {"Player1"} else {"Player2"})
+                                    lang.f("game.won", &[("player", &player_label(lang, player))])
                                 }
                             </p>
                         },
-                        State::Draw => html! { <p>{ "The game is a draw!" }</p> },
-                        State::Running => html! { <p>{ "Game is in progress..." }</p> },
+                        State::Draw => html! { <p>{ lang.t("game.draw") }</p> },
+                        State::Running => html! { <p>{ lang.t("game.in_progress") }</p> },
                     }
                 }
             </div>
+            {
+                if board.state != State::Running && !*dialog_closed {
+                    let (headline, icon) = match board.state {
+                        State::Won(player) => (lang.f("announce.wins", &[("player", &seat_name(lang, player, num_players))]), Some(AttrValue::from(icon_for(player)))),
+                        _ => (lang.t("game_over.draw").to_owned(), None),
+                    };
+                    let swapped = Connect4Setup { first_mover: other_mover(current_setup.first_mover), ..current_setup };
+                    let swap_label = seat_name(lang, swapped.board().current_turn, num_players);
+                    html! {
+                        <GameOverDialog
+                            headline={headline}
+                            icon={icon}
+                            score={board.turn_order.iter()
+                                .map(|&p| (AttrValue::from(seat_name(lang, p, num_players)), score.wins(Player::ALL.iter().position(|&q| q == p).unwrap_or(0))))
+                                .collect::<Vec<_>>()}
+                            draws={score.draws()}
+                            // the hard computer is only offered the boards it plays at full strength
+                            sizes={CONNECT4_SIZES.into_iter().filter(|&(rows, cols)| difficulty != Difficulty::Hard || searchable(rows, cols)).collect::<Vec<_>>()}
+                            size={(current_setup.rows, current_setup.cols)}
+                            swap_label={swap_label}
+                            on_rematch={start_game.reform(move |_| current_setup)}
+                            on_swap={start_game.reform(move |_| swapped)}
+                            on_size={start_game.reform(move |(rows, cols)| Connect4Setup { rows, cols, ..current_setup })}
+                            on_close={{ let dialog_closed = dialog_closed.clone(); Callback::from(move |_| dialog_closed.set(true)) }}
+                        />
+                    }
+                } else {
+                    html! {}
+                }
+            }
         </>
     }
 }
@@ -638,15 +1303,36 @@ fn connect_four_game() -> Html {
 ////////////////// toot otto
 #[function_component(TootOttoGame)]
 fn toot_otto_game() -> Html {
-    let mut app_state_borrowed = APP_STATE.lock().unwrap();
-
-    // Load state when the application starts
-    let loaded_state = load_state();
-    if let Some(state) = loaded_state {
-        *app_state_borrowed = state;
-    }
-
-    let board = use_state(|| TootBoard::new(4, 6)); // Standard TOOT-OTTO board size
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let app_state_borrowed = (*settings).clone();
+
+    let difficulty = app_state_borrowed.difficulty;
+    let muted = app_state_borrowed.muted;
+    let lang = app_state_borrowed.language;
+    // An unfinished game from an earlier visit carries on where it was left, with the words it was started with
+    let resumed = use_state(|| saved_game::load(TOOT_OTTO_GAME_KEY, TootOttoSaved::is_playable));
+    // The word Player1 plays on this visit: a resumed game (and its rematches) keeps its own, and
+    // the Home page's choice is left alone for the next fresh game
+    let player1_word = *use_state(|| resumed.as_ref().map_or(app_state_borrowed.player_as_toot_otto, |saved| saved.player1));
+    // the computer plays whichever word Player1 didn't pick
+    let computer_player = match player1_word {
+        PlayerAsTootOtto::PlayerToot => TootPlayer::Otto,
+        PlayerAsTootOtto::PlayerOtto => TootPlayer::Toot,
+    };
+    // How the game on screen was started; new games and rematches start from it
+    let setup = use_state(|| match &*resumed {
+        Some(saved) => saved.setup,
+        None => TootOttoSetup {
+            rows: 4, // Standard TOOT-OTTO board size
+            cols: 6,
+            first_mover: app_state_borrowed.first_mover,
+            control: None,
+        },
+    });
+    let board = use_state(|| match &*resumed {
+        Some(saved) => saved.board.clone(),
+        None => setup.board(player1_word),
+    });
 
     // State to keep track of the currently selected piece
     let selected_piece = use_state(|| None);
@@ -658,91 +1344,192 @@ fn toot_otto_game() -> Html {
         })
     };
 
-    // let app_state_borrowed = APP_STATE.lock().unwrap();
     let on_difficulty_change = {
-        let app_state = Arc::clone(&APP_STATE);
+        let settings = settings.clone();
         Callback::from(move |d: &str| {
-            let mut app_state = app_state.lock().unwrap();
-            app_state.difficulty = match d {
+            let difficulty = match d {
                 "none" => Difficulty::None,
                 "easy" => Difficulty::Easy,
                 "hard" => Difficulty::Hard,
                 _ => Difficulty::None,
             };
-
-            // Save state when the difficulty changes
-            save_state(&app_state);
+            settings.dispatch(SettingsAction::Difficulty(difficulty));
         })
     };
 
     let hovered_col: UseStateHandle<Option<usize>> = use_state(|| None);
 
-    let player1_done = use_state(|| false);
-    let on_column_click_comp = {
+    let clock: UseStateHandle<Option<GameClock<TootPlayer>>> =
+        use_state(|| resumed.as_ref().and_then(|saved| resume_clock(&saved.clock, saved.board.current_turn)));
+
+    // Keep the game saved while it can be continued: after the first move and until it is over
+    use_effect_with((*setup, player1_word, (*board).clone(), (*clock).clone()), |(setup, player1, board, clock)| {
+        if board.state == TootState::Running && !board.moves.is_empty() {
+            let saved = TootOttoSaved { setup: *setup, player1: *player1, board: board.clone(), clock: clock.clone() };
+            saved_game::save(TOOT_OTTO_GAME_KEY, &saved);
+        } else {
+            saved_game::clear(TOOT_OTTO_GAME_KEY);
+        }
+    });
+
+    // Tick the clocks while a timed game runs, and end the game for whoever runs out of time
+    {
         let board = board.clone();
-        let app_state_borrowed = app_state_borrowed.clone();
-        let player1_done = player1_done.clone();
-        Callback::from(move |_col: usize| {
-            let mut b = (*board).clone();
-            let current_difficulty = app_state_borrowed.difficulty; // Directly accessing because we cloned the state.
-
-            match current_difficulty {
-                Difficulty::None => (), // Do nothing if no difficulty is set.
-                Difficulty::Easy => {
-                    if let Err(e) = b.computer_move() {
-                        println!("Error: {}", e);
-                    } else {
+        let clock = clock.clone();
+        let force_update = use_force_update();
+        use_effect_with(((*board).clone(), (*clock).clone()), move |(b, c)| {
+            let b = b.clone();
+            let interval = c.clone().filter(|c| c.is_running()).map(|c| {
+                Interval::new(100, move || {
+                    if let Some(player) = c.flagged(&BrowserTimeSource) {
+                        let mut b = b.clone();
+                        b.flag(player);
+                        let mut c = c.clone();
+                        c.stop(&BrowserTimeSource);
                         board.set(b);
-                        player1_done.set(false); // Reset the player1_done flag.
-                    }
-                }
-                // Difficulty::Hard => {
-                //     if let Err(e) = b.computer_move_hard(_col) {
-                //         println!("Error: {}", e);
-                //     } else {
-                //         println!("Hard moved");
-                //         board.set(b);
-                //         player1_done.set(false); // Reset the player1_done flag.
-                //     }
-                // }
-                Difficulty::Hard => {
-                    if let Some((_row, col)) = b.last_move {
-                        if let Err(e) = b.computer_move_hard(col) {
-                            println!("Error: {}", e);
-                        } else {
-                            println!("Hard moved");
-                            board.set(b);
-                            player1_done.set(false);
-                        }
+                        clock.set(Some(c));
                     } else {
-                        if let Err(e) = b.computer_move_hard(_col) {
-                            println!("Error: {}", e);
-                        } else {
-                            println!("Hard moved");
-                            board.set(b);
-                            player1_done.set(false);
-                        }
+                        force_update.force_update();
+                    }
+                })
+            });
+            move || drop(interval)
+        });
+    }
+
+    // The computer thinks in a Web Worker, as on the Connect Four page: the board goes to the
+    // worker after a short pause, and its move is only played if that board is still on screen
+    let computer_to_move =
+        difficulty != Difficulty::None && board.state == TootState::Running && board.current_turn == computer_player;
+    // Where the last piece landed, found with predict_piece before it is played, so it can fall into place
+    let dropping: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+    let (ai_worker, ai_answer) = use_ai_worker();
+    {
+        let ai_worker = ai_worker.clone();
+        use_effect_with(((*board).clone(), computer_to_move, difficulty), move |(b, computer_to_move, difficulty)| {
+            let (board, difficulty) = (b.clone(), *difficulty);
+            let request = move |id| AiRequest::TootOtto { id, board, difficulty, budget_ms: COMPUTER_BUDGET_MS };
+            let timeout = computer_to_move.then(|| Timeout::new(COMPUTER_THINKING_MS, move || ai_worker.send(request)));
+            move || drop(timeout)
+        });
+    }
+    {
+        let board = board.clone();
+        let clock = clock.clone();
+        let dropping = dropping.clone();
+        let answer = (*ai_answer).clone();
+        use_effect_with(ai_answer.as_ref().map(AiResponse::id), move |_| {
+            if let Some(reply @ AiResponse::TootOtto { board: asked, mv: Some((col, piece)), .. }) = &answer {
+                if ai_worker.is_latest(reply) && *asked == *board && computer_to_move {
+                    let mut b = (*board).clone();
+                    let landing = b.predict_piece(*col);
+                    if b.insert_piece(*col, *piece).is_ok() {
+                        dropping.set(landing);
+                        sound::play(Sound::Drop, muted);
+                        advance_clock(&clock, b.current_turn, b.state == TootState::Running);
+                        board.set(b);
                     }
                 }
+            }
+        });
+    }
 
+    let predicted_pos: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+
+    // Counts the games played on this page, so each finished game is scored exactly once
+    let game_number = use_state(|| 0u32);
+    let dialog_closed = use_state(|| false);
+    let score = use_state(|| SessionScore::load(TOOT_OTTO_SCORE_KEY));
+    {
+        let score = score.clone();
+        let finished = (*board).clone();
+        use_effect_with((*game_number, board.state), move |(_, state)| {
+            // seat 0 is Player1, who plays the word chosen on the Home page
+            let result = match *state {
+                TootState::Won(word) => Some(Some(if word == computer_player { 1 } else { 0 })),
+                TootState::Draw => Some(None),
+                TootState::Running => None,
+            };
+            if let Some(winner) = result {
+                let mut s = (*score).clone();
+                s.record(winner);
+                s.save(TOOT_OTTO_SCORE_KEY);
+                score.set(s);
+                sound::play(
+                    match winner {
+                        None => Sound::Draw,
+                        Some(1) if difficulty != Difficulty::None => Sound::Loss,
+                        Some(_) => Sound::Win,
+                    },
+                    muted,
+                );
+
+                let players = vec![
+                    "Player1".to_owned(),
+                    if difficulty == Difficulty::None { "Player2".to_owned() } else { computer_name(difficulty) },
+                ];
+                let pieces = finished.grid.iter().flatten().filter(|&&cell| cell != TootCell::Empty).count();
+                record_game(GameRecord {
+                    game: GameKind::TootOtto,
+                    winner: winner.map(|seat| players[seat].clone()),
+                    players,
+                    date_ms: js_sys::Date::now() as u64,
+                    moves: pieces,
+                    // games saved from the sandbox by older versions have pieces that no move put there
+                    replay: (finished.moves.len() == pieces).then(|| Replay::TootOtto { rows: finished.rows, cols: finished.cols, moves: finished.moves.clone() }),
+                });
             }
+        });
+    }
+
+    // Start a fresh game, dropping whatever the computer was thinking about
+    let start_game = {
+        let setup = setup.clone();
+        let board = board.clone();
+        let selected_piece = selected_piece.clone();
+        let dropping = dropping.clone();
+        let clock = clock.clone();
+        let game_number = game_number.clone();
+        let dialog_closed = dialog_closed.clone();
+        Callback::from(move |next: TootOttoSetup| {
+            let b = next.board(player1_word);
+            selected_piece.set(None);
+            dropping.set(None);
+            clock.set(new_clock(next.control, &[TootPlayer::Toot, TootPlayer::Otto], b.current_turn));
+            board.set(b);
+            setup.set(next);
+            game_number.set(*game_number + 1);
+            dialog_closed.set(false);
         })
     };
+    let current_setup = *setup;
 
-    let predicted_pos: UseStateHandle<Option<(usize, usize)>> = use_state(|| None);
+    // Picking a time control starts a fresh timed (or untimed) game
+    let on_time_control_change = start_game.reform(move |control: Option<TimeControl>| TootOttoSetup { control, ..current_setup });
 
     let on_column_click = {
         let board = board.clone();
         let selected_piece = selected_piece.clone();
         let hovered_col = hovered_col.clone();
-        let player1_done = player1_done.clone();
+        let clock = clock.clone();
+        let dropping = dropping.clone();
         Callback::from(move |col: usize| {
+            // the human has to wait while the computer is thinking
+            if computer_to_move {
+                return;
+            }
             if let Some(piece) = *selected_piece {
                 let mut b = (*board).clone();
                 hovered_col.set(None);
-                b.insert_piece(col, piece).ok(); // Handling the insertion and ignoring errors
+                let landing = b.predict_piece(col);
+                if b.insert_piece(col, piece).is_ok() {
+                    dropping.set(landing);
+                    sound::play(Sound::Drop, muted);
+                    advance_clock(&clock, b.current_turn, b.state == TootState::Running);
+                } else {
+                    sound::play(Sound::Invalid, muted);
+                }
                 board.set(b); // Update the board state
-                player1_done.set(true);
                 selected_piece.set(None); // Reset the selected piece after placing it
             }
         })
@@ -773,24 +1560,38 @@ fn toot_otto_game() -> Html {
         })
     };
 
-    let pixel_size = "80px"; // Smaller pieces for a more complex board
-    let grid_style = format!(
-        "display: grid; text-align: center; grid-template-columns: repeat({}, {}); grid-auto-rows: {};",
-        board.cols, pixel_size, pixel_size
-    );
+    // The board takes the keyboard: arrows (and Home/End) choose a column, T and O choose a letter,
+    // Enter or Space drops it
+    let keyboard_col: UseStateHandle<Option<usize>> = use_state(|| None);
+    let is_enabled = board.state == TootState::Running && !computer_to_move;
+    let on_board_key = {
+        let keyboard_col = keyboard_col.clone();
+        let on_piece_select = on_piece_select.clone();
+        let on_column_click = on_column_click.clone();
+        let cols = board.cols;
+        Callback::from(move |e: KeyboardEvent| {
+            match board_key(&e.key()) {
+                Some(BoardKey::Column(step)) => keyboard_col.set(Some(step_column(*keyboard_col, step, cols))),
+                Some(BoardKey::Piece(piece)) => on_piece_select.emit(piece),
+                Some(BoardKey::Drop) => {
+                    if let (Some(col), true) = (*keyboard_col, is_enabled) {
+                        on_column_click.emit(col);
+                    }
+                }
+                None => return,
+            }
+            e.prevent_default();
+        })
+    };
+
+    let winning = board.winning_line().unwrap_or_default();
     let current_player = match board.current_turn {
         TootPlayer::Toot => "TOOT",
         TootPlayer::Otto => "OTTO",
     };
 
-    let cell_style_hovered = "
-    background-color: lightgray;
-    box-shadow: 2px 2px 5px rgba(0, 0, 0, 0.3);
-    ";
-    let btn_style_regular: &str = "color: dimgray; margin: 4px;";
-    let btn_style_selected = "background-color: lightgray; color: black; margin: 4px;";
 
-    let (player1_word, opponent_word) = match app_state_borrowed.player_as_toot_otto {
+    let (player1_word, opponent_word) = match player1_word {
         PlayerAsTootOtto::PlayerToot => ("TOOT", "OTTO"),
         PlayerAsTootOtto::PlayerOtto => ("OTTO", "TOOT"),
     };
@@ -801,77 +1602,114 @@ fn toot_otto_game() -> Html {
                     <span class="circle" aria-hidden="true">
                         <span class="icon arrow"></span>
                     </span>
-                    <span class="button-text">{"Back to Home"}</span>
+                    <span class="button-text">{ lang.t("nav.back_home") }</span>
                 </button>
             </Link<Route>>
-            <h1 class="text_titles">{ "TOOT and OTTO" }</h1>
+            <h1 class="text_titles">{ lang.t("game.toot_otto_title") }</h1>
             <h2 class="radio-buttons" style="display: flex; align-items: center;">
-                { format!("Status: ") }
-                { format!("Player1 - ") }
+                { lang.t("game.status") }
+                { format!("{} - ", lang.t("player.1")) }
                 { player1_word }
-                { format!(", Player2/Computer - ") }
+                { format!(", {} - ", lang.t("home.player2_or_computer")) }
                 { opponent_word }
             </h2>
             <div class="radio-buttons" style="display: flex; align-items: center;">
-                <text>{ "Robot: "}</text>
+                <text>{ lang.t("game.robot") }</text>
                 <label class="l-radio" for="none">
                     <input type="radio" id="none" name="difficulty" value="none" onclick={on_difficulty_change.reform(move |_| "none")} checked={app_state_borrowed.difficulty == Difficulty::None} />
-                    <span>{"none"}</span>
+                    <span>{ lang.t("game.robot_none") }</span>
                 </label>
                 <label class="l-radio" for="easy">
                     <input type="radio" id="easy" name="difficulty" value="easy" onclick={on_difficulty_change.reform(move |_| "easy")} checked={app_state_borrowed.difficulty == Difficulty::Easy} />
-                    <span>{"easy"}</span>
+                    <span>{ lang.t("game.robot_easy") }</span>
                 </label>
                 <label class="l-radio" for="hard">
                     <input type="radio" id="hard" name="difficulty" value="hard" onclick={on_difficulty_change.reform(move |_| "hard")} checked={app_state_borrowed.difficulty == Difficulty::Hard}/>
-                    <span>{"hard"}</span>
+                    <span>{ lang.t("game.robot_hard") }</span>
                 </label>
             </div>
-            <h2>{ format!("Current turn: {}", current_player) }</h2>
+            <div class="radio-buttons" style="display: flex; align-items: center;">
+                <text>{ lang.t("game.clock") }</text>
+                {
+                    for TIME_CONTROLS.iter().map(|&(name, control)| html! {
+                        <label class="l-radio" for={format!("clock-{}", name)}>
+                            <input type="radio" id={format!("clock-{}", name)} name="clock" onclick={on_time_control_change.reform(move |_| control)} checked={current_setup.control == control} />
+                            <span>{ if control.is_some() { name } else { lang.t("game.clock_none") } }</span>
+                        </label>
+                    })
+                }
+                // starting over also drops whatever the computer was thinking about
+                <button style="margin-left: 16px;" onclick={start_game.reform(move |_| current_setup)}>{ lang.t("game.new_game") }</button>
+                <MuteToggle />
+            </div>
+            <h2>
+                { format!("{}{}", lang.t("game.current_turn"), current_player) }
+                { if computer_to_move { html! { <span class="thinking">{ lang.t("game.thinking") }</span> } } else { html! {} } }
+                {
+                    match &*clock {
+                        Some(c) if board.state == TootState::Running => lang.f("game.time_left", &[("time", &format_remaining(c.remaining(board.current_turn, &BrowserTimeSource)))]),
+                        _ => String::new(),
+                    }
+                }
+            </h2>
+            {
+                match &*clock {
+                    Some(c) => html! {
+                        <p>
+                            { format!("TOOT {} | OTTO {}",
+                                format_remaining(c.remaining(TootPlayer::Toot, &BrowserTimeSource)),
+                                format_remaining(c.remaining(TootPlayer::Otto, &BrowserTimeSource))) }
+                        </p>
+                    },
+                    None => html! {},
+                }
+            }
             <div>
                 <button
-                    style={
-                        if *selected_piece == Some(Piece::T) {btn_style_selected}
-                        else {btn_style_regular}
-                    }
+                    class="piece-choice"
+                    aria-pressed={(*selected_piece == Some(Piece::T)).to_string()}
                     onclick={on_piece_select.reform(|_| Piece::T)}
-                    disabled={!matches!(board.state, toot_otto::State::Running)}
+                    disabled={!matches!(board.state, toot_otto::State::Running) || board.pieces_left(board.current_turn, Piece::T) == 0}
                 >
-                    { "Select T" }
+                    { lang.f("game.select_piece", &[("piece", &"T")]) }
                 </button>
                 <button
-                    style={
-                        if *selected_piece == Some(Piece::O) {btn_style_selected}
-                        else {btn_style_regular}
-                    }
+                    class="piece-choice"
+                    aria-pressed={(*selected_piece == Some(Piece::O)).to_string()}
                     onclick={on_piece_select.reform(|_| Piece::O)}
-                    disabled={!matches!(board.state, toot_otto::State::Running)}
+                    disabled={!matches!(board.state, toot_otto::State::Running) || board.pieces_left(board.current_turn, Piece::O) == 0}
                 >
-                    { "Select O" }
+                    { lang.f("game.select_piece", &[("piece", &"O")]) }
                 </button>
             </div>
-            <div class="container-toototto">
-                <div class="grid" style={grid_style.clone()}>
+            <p class="visually-hidden" aria-live="polite">
+                {
+                    toot_otto_announcement(lang, &board, |word| {
+                        let word_name = if word == TootPlayer::Toot { "TOOT" } else { "OTTO" };
+                        let seat = if word == computer_player { lang.t("home.player2_or_computer") } else { lang.t("player.1") };
+                        format!("{} ({})", seat, word_name)
+                    })
+                }
+            </p>
+            <div class="container-toototto" style={board_style(board.cols)}>
+                <div class="grid" role="group" tabindex="0" onkeydown={on_board_key}
+                    aria-label={lang.f("game.toot_otto_board", &[("rows", &board.rows), ("cols", &board.cols)])}>
                     {
                         for board.grid.iter().enumerate().map(|(row, line)| {
                             html! {
                                 {
                                     for line.iter().enumerate().map(|(col, &cell)| {
-                                        let mut cell_style: &str = "text-align: center;";
-                                        if let Some(hovered_col) = *hovered_col {
-                                            if hovered_col == col {
-                                                cell_style = cell_style_hovered;
-                                            }
+                                        let piece_class = drop_class(*dropping, row, col);
+                                        let piece_style = format!("--drop-rows: {};", row + 1);
+                                        let label = match cell {
+                                            TootCell::Empty => lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &lang.t("board.empty"))]),
+                                            TootCell::Occupied(piece) => lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &format!("{:?}", piece))]),
                                         };
-                                        // robot move
-                                        if *player1_done == true {
-                                            on_column_click_comp.emit(col);
-                                        }
-                                        let is_enabled = matches!(board.state, toot_otto::State::Running);
                                         html! {
                                             <button
-                                            class="cell"
-                                            style={cell_style}
+                                            class={classes!("cell", winning.contains(&(row, col)).then_some("winning"), (*keyboard_col == Some(col)).then_some("keyboard-column"), (*hovered_col == Some(col)).then_some("hovered"))}
+                                            aria-label={label}
+                                            tabindex="-1"
                                             onmouseenter={
                                                 handle_mouseover.reform(move |_| col)
                                             }
@@ -887,8 +1725,8 @@ fn toot_otto_game() -> Html {
                                                                 if row == pos.0 && col == pos.1 {
                                                                     if let Some(piece) = *selected_piece {
                                                                         match piece {
-                                                                            Piece::T => html! {<text style="font-size: 60px; text-align: center; display: block; line-height: 0.65; opacity: 0.6;">{"T"}</text>},
-                                                                            Piece::O => html! {<text style="font-size: 60px; text-align: center; display: block; line-height: 0.65; opacity: 0.6;">{"O"}</text>},
+                                                                            Piece::T => html! {<text style="opacity: 0.6;">{"T"}</text>},
+                                                                            Piece::O => html! {<text style="opacity: 0.6;">{"O"}</text>},
                                                                         }
                                                                     } else {html! {<text>{" "}</text>}}
                                                                 }
@@ -898,8 +1736,8 @@ fn toot_otto_game() -> Html {
                                                             }
                                                         },
                                                         TootCell::Occupied(piece) => match piece {
-                                                            Piece::T => html! {<text style="font-size: 60px; text-align: center; display: block; line-height: 0.65; ">{"T"}</text>},
-                                                            Piece::O => html! {<text style="font-size: 60px; text-align: center; display: block; line-height: 0.65; ">{"O"}</text>},
+                                                            Piece::T => html! {<text class={piece_class} style={piece_style}>{"T"}</text>},
+                                                            Piece::O => html! {<text class={piece_class} style={piece_style}>{"O"}</text>},
                                                         },
                                                     }
                                                 }
@@ -915,12 +1753,45 @@ fn toot_otto_game() -> Html {
             <div>
                 {
                     match board.state {
-                        TootState::Won(player) => html! { <p>{ format!("Player {:?} wins! Refresh to reset game.", player) }</p> },
-                        TootState::Draw => html! { <p>{ "The game is a draw!" }</p> },
-                        TootState::Running => html! { <p>{ "Game is in progress..." }</p> },
+                        TootState::Won(player) => html! { <p>{ lang.f("game.won", &[("player", &format!("{:?}", player).to_uppercase())]) }</p> },
+                        TootState::Draw => html! { <p>{ lang.t("game.draw") }</p> },
+                        TootState::Running => html! { <p>{ lang.t("game.in_progress") }</p> },
                     }
                 }
             </div>
+            {
+                if board.state != TootState::Running && !*dialog_closed {
+                    let player1 = format!("{} ({})", lang.t("player.1"), player1_word);
+                    let player2 = format!("{} ({})", lang.t("home.player2_or_computer"), opponent_word);
+                    let headline = match board.state {
+                        TootState::Won(word) if word == computer_player => lang.f("announce.wins", &[("player", &player2)]),
+                        TootState::Won(_) => lang.f("announce.wins", &[("player", &player1)]),
+                        _ => lang.t("game_over.draw").to_owned(),
+                    };
+                    let swapped = TootOttoSetup { first_mover: other_mover(current_setup.first_mover), ..current_setup };
+                    let swap_label = match swapped.first_mover {
+                        FirstMover::Player1 => player1.clone(),
+                        FirstMover::Player2 => player2.clone(),
+                    };
+                    html! {
+                        <GameOverDialog
+                            headline={headline}
+                            icon={None::<AttrValue>}
+                            score={vec![(AttrValue::from(player1), score.wins(0)), (AttrValue::from(player2), score.wins(1))]}
+                            draws={score.draws()}
+                            sizes={TOOT_OTTO_SIZES.to_vec()}
+                            size={(current_setup.rows, current_setup.cols)}
+                            swap_label={swap_label}
+                            on_rematch={start_game.reform(move |_| current_setup)}
+                            on_swap={start_game.reform(move |_| swapped)}
+                            on_size={start_game.reform(move |(rows, cols)| TootOttoSetup { rows, cols, ..current_setup })}
+                            on_close={{ let dialog_closed = dialog_closed.clone(); Callback::from(move |_| dialog_closed.set(true)) }}
+                        />
+                    }
+                } else {
+                    html! {}
+                }
+            }
         </>
     }
 }
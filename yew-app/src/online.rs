@@ -0,0 +1,817 @@
+use crate::history::GameKind;
+use crate::i18n::Language;
+use crate::pwa::use_online;
+use crate::settings::Settings;
+use crate::sound::{self, MuteToggle, Sound};
+use crate::{
+    board_key, board_style, connect4_announcement, player_color, step_column, toot_otto_announcement, BoardKey, Route, API_URL,
+    TIME_CONTROLS,
+};
+use gloo_net::http::{Request, Response};
+use gloo_timers::callback::Interval;
+use gloo_timers::future::TimeoutFuture;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{window, HtmlInputElement, Storage};
+use yew::prelude::*;
+use yew_app::clock::{format_remaining, TimeControl};
+use yew_app::connect4::{self, Board};
+use yew_app::toot_otto::{self, Board as TootBoard, Piece};
+use yew_router::prelude::*;
+
+// sessionStorage keys of the seat this tab is playing, or the game it is watching, so reloading the page goes back to it
+const SEAT_KEY: &str = "onlineSeat";
+const WATCHING_KEY: &str = "onlineWatching";
+// How long to wait before trying the server again after losing it
+const RETRY_MS: u32 = 2_000;
+// How often the lobby's list of games to watch is refreshed
+const GAMES_REFRESH_MS: u32 = 5_000;
+
+// These match the backend's online.rs
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum OnlineBoard {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+impl OnlineBoard {
+    // Seat 0 is Player1 (Red, or TOOT) and moves first; seat 1 is Player2 (Yellow, or OTTO)
+    fn seat_to_move(&self) -> Option<usize> {
+        match self {
+            OnlineBoard::ConnectFour(b) if b.state == connect4::State::Running => {
+                Some(if b.current_turn == connect4::Player::Red { 0 } else { 1 })
+            }
+            OnlineBoard::TootOtto(b) if b.state == toot_otto::State::Running => {
+                Some(if b.current_turn == toot_otto::Player::Toot { 0 } else { 1 })
+            }
+            _ => None,
+        }
+    }
+
+    // The winner's seat once the game is over, or None for a draw
+    fn result(&self) -> Option<Option<usize>> {
+        match self {
+            OnlineBoard::ConnectFour(b) => match b.state {
+                connect4::State::Won(player) => Some(Some(if player == connect4::Player::Red { 0 } else { 1 })),
+                connect4::State::Draw => Some(None),
+                connect4::State::Running => None,
+            },
+            OnlineBoard::TootOtto(b) => match b.state {
+                toot_otto::State::Won(player) => Some(Some(if player == toot_otto::Player::Toot { 0 } else { 1 })),
+                toot_otto::State::Draw => Some(None),
+                toot_otto::State::Running => None,
+            },
+        }
+    }
+
+    fn play(&mut self, col: usize, piece: Option<Piece>) -> Result<(), &'static str> {
+        match self {
+            OnlineBoard::ConnectFour(b) => b.insert_disc(col),
+            OnlineBoard::TootOtto(b) => b.insert_piece(col, piece.ok_or("Choose a T or an O first")?),
+        }
+    }
+
+    fn moves(&self) -> usize {
+        match self {
+            OnlineBoard::ConnectFour(b) => b.moves.len(),
+            OnlineBoard::TootOtto(b) => b.moves.len(),
+        }
+    }
+
+    fn seat_label(&self, lang: Language, seat: usize) -> String {
+        let player = lang.t(if seat == 0 { "player.1" } else { "player.2" });
+        match (self, seat) {
+            (OnlineBoard::ConnectFour(_), _) => player.to_owned(),
+            (OnlineBoard::TootOtto(_), 0) => format!("{} (TOOT)", player),
+            (OnlineBoard::TootOtto(_), _) => format!("{} (OTTO)", player),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Joined {
+    pub code: String,
+    pub seat: usize,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spectating {
+    pub code: String,
+    pub spectator: String,
+}
+
+// A game someone is playing or watching. Spectators see every move but can't play.
+#[derive(Debug, Clone, PartialEq)]
+enum Following {
+    Player(Joined),
+    Spectator(Spectating),
+}
+
+impl Following {
+    fn code(&self) -> &str {
+        match self {
+            Following::Player(seat) => &seat.code,
+            Following::Spectator(watching) => &watching.code,
+        }
+    }
+
+    // How the server knows who is asking
+    fn query(&self) -> String {
+        match self {
+            Following::Player(seat) => format!("token={}", seat.token),
+            Following::Spectator(watching) => format!("spectator={}", watching.spectator),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SeatStatus {
+    pub joined: bool,
+    pub connected: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ClockView {
+    pub control: TimeControl,
+    pub remaining_ms: Vec<u64>,
+    pub running: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GameView {
+    pub code: String,
+    pub version: u64,
+    pub board_version: u64,
+    pub board: OnlineBoard,
+    pub seats: Vec<SeatStatus>,
+    pub spectators: usize,
+    #[serde(default)]
+    pub clock: Option<ClockView>,
+}
+
+// A game in the list of games to watch
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GameSummary {
+    pub code: String,
+    pub game: GameKind,
+    pub moves: usize,
+    pub spectators: usize,
+}
+
+#[derive(Serialize)]
+struct NewGame {
+    game: GameKind,
+    rows: usize,
+    cols: usize,
+    control: Option<TimeControl>,
+}
+
+#[derive(Serialize)]
+struct Move<'a> {
+    token: &'a str,
+    board_version: u64,
+    col: usize,
+    piece: Option<Piece>,
+}
+
+enum ApiError {
+    Offline,                // the server couldn't be reached
+    Rejected(u16, String), // the server said no: its status code and reason
+}
+
+impl ApiError {
+    fn reason(&self) -> String {
+        match self {
+            ApiError::Offline => "The game server can't be reached".to_owned(),
+            ApiError::Rejected(_, reason) => reason.clone(),
+        }
+    }
+}
+
+async fn read<T: for<'de> Deserialize<'de>>(response: Result<Response, gloo_net::Error>) -> Result<T, ApiError> {
+    let response = response.map_err(|_| ApiError::Offline)?;
+    if !response.ok() {
+        let reason = response.text().await.unwrap_or_default();
+        return Err(ApiError::Rejected(response.status(), reason));
+    }
+    response.json().await.map_err(|_| ApiError::Offline)
+}
+
+async fn create_game(new: NewGame) -> Result<Joined, ApiError> {
+    let request = Request::post(&format!("{}/games", API_URL)).json(&new).map_err(|_| ApiError::Offline)?;
+    read(request.send().await).await
+}
+
+async fn join_game(code: &str) -> Result<Joined, ApiError> {
+    read(Request::post(&format!("{}/games/{}/join", API_URL, code)).send().await).await
+}
+
+async fn spectate_game(code: &str) -> Result<Spectating, ApiError> {
+    read(Request::post(&format!("{}/games/{}/spectate", API_URL, code)).send().await).await
+}
+
+// Games being played that can be watched
+async fn list_games() -> Result<Vec<GameSummary>, ApiError> {
+    read(Request::get(&format!("{}/games", API_URL)).send().await).await
+}
+
+// Waits until the game is newer than `since`; without `since` answers right away
+async fn watch_game(following: &Following, since: Option<u64>) -> Result<GameView, ApiError> {
+    let mut url = format!("{}/games/{}?{}", API_URL, following.code(), following.query());
+    if let Some(since) = since {
+        url.push_str(&format!("&since={}", since));
+    }
+    read(Request::get(&url).send().await).await
+}
+
+async fn send_move(seat: &Joined, board_version: u64, col: usize, piece: Option<Piece>) -> Result<GameView, ApiError> {
+    let mv = Move { token: &seat.token, board_version, col, piece };
+    let request = Request::post(&format!("{}/games/{}/move", API_URL, seat.code)).json(&mv).map_err(|_| ApiError::Offline)?;
+    read(request.send().await).await
+}
+
+fn storage() -> Option<Storage> {
+    window()?.session_storage().ok()?
+}
+
+fn load<T: for<'de> Deserialize<'de>>(key: &str) -> Option<T> {
+    let json = storage()?.get_item(key).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+// A stored seat from a bad or hand-edited save is ignored; games only have seats 0 and 1
+fn load_following() -> Option<Following> {
+    let seat = load::<Joined>(SEAT_KEY).filter(|seat| seat.seat < 2);
+    seat.map(Following::Player).or_else(|| load(WATCHING_KEY).map(Following::Spectator))
+}
+
+fn save_following(following: Option<&Following>) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    let _ = storage.remove_item(SEAT_KEY);
+    let _ = storage.remove_item(WATCHING_KEY);
+    let saved = match following {
+        Some(Following::Player(seat)) => serde_json::to_string(seat).ok().map(|json| (SEAT_KEY, json)),
+        Some(Following::Spectator(watching)) => serde_json::to_string(watching).ok().map(|json| (WATCHING_KEY, json)),
+        None => None,
+    };
+    if let Some((key, json)) = saved {
+        let _ = storage.set_item(key, &json);
+    }
+}
+
+// What the page knows about the game it is playing
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Online {
+    view: Option<GameView>,      // The server's latest board
+    pending: Option<OnlineBoard>, // Our move, shown while the server hasn't answered it yet
+    message: Option<String>,     // Why the last move or request didn't work
+    offline: bool,               // The last attempt to reach the server failed
+    received_ms: f64,            // When the latest board arrived, which its clock times are counted from
+}
+
+enum OnlineAction {
+    Update(GameView),
+    Sent(OnlineBoard),
+    Rejected(String),
+    Offline,
+    Leave,
+}
+
+impl Reducible for Online {
+    type Action = OnlineAction;
+
+    fn reduce(self: Rc<Self>, action: OnlineAction) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            OnlineAction::Update(view) => {
+                state.offline = false;
+                // the long poll and a move's answer can arrive in either order; keep the newest
+                if state.view.as_ref().is_none_or(|current| view.version > current.version) {
+                    state.view = Some(view);
+                    state.pending = None;
+                    state.received_ms = js_sys::Date::now();
+                }
+            }
+            OnlineAction::Sent(board) => {
+                state.pending = Some(board);
+                state.message = None;
+            }
+            OnlineAction::Rejected(reason) => {
+                state.pending = None; // back to the server's board
+                state.message = Some(reason);
+            }
+            OnlineAction::Offline => state.offline = true,
+            OnlineAction::Leave => state = Online::default(),
+        }
+        Rc::new(state)
+    }
+}
+
+// Play Connect Four or Toot and Otto against someone on another computer.
+// One player creates a game and shares its code; the other joins with it. Anyone else can
+// watch a game that has both its players. The backend (`backend serve`) holds the real board:
+// each page long-polls it for changes, and moves are shown straight away but only stick once
+// the server accepts them.
+#[function_component(OnlinePlay)]
+pub fn online_play() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let following: UseStateHandle<Option<Following>> = use_state(load_following);
+    let online = use_reducer(Online::default);
+    let game_choice = use_state(|| GameKind::ConnectFour);
+    let control_choice: UseStateHandle<Option<TimeControl>> = use_state(|| None);
+    let code_input = use_node_ref();
+    let selected_piece: UseStateHandle<Option<Piece>> = use_state(|| None);
+    let keyboard_col: UseStateHandle<Option<usize>> = use_state(|| None);
+    let lobby_message: UseStateHandle<Option<String>> = use_state(|| None);
+    let games: UseStateHandle<Option<Vec<GameSummary>>> = use_state(|| None);
+    let network = use_online();
+
+    // Follow the game for as long as we are playing or watching it
+    {
+        let online = online.clone();
+        let following_handle = following.clone();
+        let lobby_message = lobby_message.clone();
+        use_effect_with((*following).clone(), move |following| {
+            let alive = Rc::new(Cell::new(true));
+            if let Some(following) = following.clone() {
+                let alive = alive.clone();
+                spawn_local(async move {
+                    let mut since = None;
+                    while alive.get() {
+                        match watch_game(&following, since).await {
+                            Ok(view) => {
+                                since = Some(view.version);
+                                online.dispatch(OnlineAction::Update(view));
+                            }
+                            Err(ApiError::Rejected(404, _)) => {
+                                // e.g. the server restarted and forgot the game
+                                save_following(None);
+                                online.dispatch(OnlineAction::Leave);
+                                lobby_message.set(Some("That game no longer exists".to_owned()));
+                                following_handle.set(None);
+                                break;
+                            }
+                            Err(_) => {
+                                online.dispatch(OnlineAction::Offline);
+                                TimeoutFuture::new(RETRY_MS).await;
+                            }
+                        }
+                    }
+                });
+            }
+            move || alive.set(false)
+        });
+    }
+
+    // A sound for each move the server reports, and for the end of the game. What the page
+    // finds when it (re)connects is already old news.
+    {
+        let heard = use_mut_ref(|| None);
+        let muted = settings.muted;
+        let my_seat = match &*following {
+            Some(Following::Player(seat)) => Some(seat.seat),
+            _ => None,
+        };
+        let latest = online.view.as_ref().map(|view| (view.code.clone(), view.board.moves(), view.board.result()));
+        use_effect_with(latest, move |latest| {
+            let before = heard.replace(latest.clone());
+            if let (Some((code, moves, result)), Some((code_before, moves_before, _))) = (latest, before) {
+                if *code == code_before && *moves > moves_before {
+                    let sound = match result {
+                        None => Sound::Drop,
+                        Some(None) => Sound::Draw,
+                        Some(Some(winner)) if my_seat.is_some_and(|seat| seat != *winner) => Sound::Loss,
+                        Some(Some(_)) => Sound::Win,
+                    };
+                    sound::play(sound, muted);
+                }
+            }
+        });
+    }
+
+    // Count the running clock down between the server's updates; the server decides when time is up
+    {
+        let force_update = use_force_update();
+        let ticking = online.view.as_ref().and_then(|view| view.clock.as_ref()).is_some_and(|clock| clock.running.is_some());
+        use_effect_with(ticking, move |ticking| {
+            let interval = ticking.then(|| Interval::new(100, move || force_update.force_update()));
+            move || drop(interval)
+        });
+    }
+
+    // Keep the list of games to watch fresh while in the lobby
+    {
+        let games = games.clone();
+        use_effect_with(following.is_none(), move |in_lobby| {
+            let alive = Rc::new(Cell::new(true));
+            if *in_lobby {
+                let alive = alive.clone();
+                spawn_local(async move {
+                    while alive.get() {
+                        if let Ok(list) = list_games().await {
+                            games.set(Some(list));
+                        }
+                        TimeoutFuture::new(GAMES_REFRESH_MS).await;
+                    }
+                });
+            }
+            move || alive.set(false)
+        });
+    }
+
+    let follow = {
+        let following = following.clone();
+        let lobby_message = lobby_message.clone();
+        Callback::from(move |result: Result<Following, ApiError>| match result {
+            Ok(followed) => {
+                save_following(Some(&followed));
+                lobby_message.set(None);
+                following.set(Some(followed));
+            }
+            Err(e) => lobby_message.set(Some(e.reason())),
+        })
+    };
+    let take_seat = follow.reform(|result: Result<Joined, ApiError>| result.map(Following::Player));
+    let on_watch = {
+        let follow = follow.clone();
+        Callback::from(move |code: String| {
+            let follow = follow.clone();
+            spawn_local(async move { follow.emit(spectate_game(&code).await.map(Following::Spectator)) });
+        })
+    };
+
+    let on_create = {
+        let take_seat = take_seat.clone();
+        let game = *game_choice;
+        let control = *control_choice;
+        Callback::from(move |_| {
+            let take_seat = take_seat.clone();
+            let (rows, cols) = match game {
+                GameKind::ConnectFour => (6, 7),
+                GameKind::TootOtto => (4, 6),
+            };
+            spawn_local(async move { take_seat.emit(create_game(NewGame { game, rows, cols, control }).await) });
+        })
+    };
+
+    let entered_code = {
+        let code_input = code_input.clone();
+        move || {
+            code_input
+                .cast::<HtmlInputElement>()
+                .map(|input| input.value().trim().to_uppercase())
+                .unwrap_or_default()
+        }
+    };
+
+    let on_join = {
+        let take_seat = take_seat.clone();
+        let entered_code = entered_code.clone();
+        Callback::from(move |_| {
+            let take_seat = take_seat.clone();
+            let code = entered_code();
+            spawn_local(async move { take_seat.emit(join_game(&code).await) });
+        })
+    };
+
+    let on_leave = {
+        let following = following.clone();
+        let online = online.clone();
+        Callback::from(move |_| {
+            save_following(None);
+            online.dispatch(OnlineAction::Leave);
+            following.set(None);
+        })
+    };
+
+    let back = html! {
+        <Link<Route> to={Route::Home}>
+            <button class="btn-back">
+                <span class="circle" aria-hidden="true">
+                    <span class="icon arrow"></span>
+                </span>
+                <span class="button-text">{ lang.t("nav.back_home") }</span>
+            </button>
+        </Link<Route>>
+    };
+
+    let Some(followed) = (*following).clone() else {
+        // the lobby
+        let choose = |game: GameKind| {
+            let game_choice = game_choice.clone();
+            Callback::from(move |_| game_choice.set(game))
+        };
+        return html! {
+            <div class="content_padding">
+                { back }
+                <h1 class="text_titles">{ lang.t("online.title") }</h1>
+                {
+                    if network {
+                        html! {}
+                    } else {
+                        html! { <p class="online-message">{ lang.t("online.offline") }</p> }
+                    }
+                }
+                <h2 class="text_titles">{ lang.t("online.start") }</h2>
+                <div class="radio-buttons">
+                    {
+                        for [GameKind::ConnectFour, GameKind::TootOtto].into_iter().map(|game| html! {
+                            <label class="l-radio" for={format!("online-{:?}", game)}>
+                                <input type="radio" id={format!("online-{:?}", game)} name="online_game" onclick={choose(game)} checked={*game_choice == game} />
+                                <span>{ game.name(lang) }</span>
+                            </label>
+                        })
+                    }
+                </div>
+                <div class="radio-buttons">
+                    <text>{ lang.t("game.clock") }</text>
+                    {
+                        for TIME_CONTROLS.iter().map(|&(name, control)| {
+                            let control_choice = control_choice.clone();
+                            html! {
+                                <label class="l-radio" for={format!("online-clock-{}", name)}>
+                                    <input type="radio" id={format!("online-clock-{}", name)} name="online_clock" onclick={Callback::from(move |_| control_choice.set(control))} checked={*control_choice == control} />
+                                    <span>{ if control.is_some() { name } else { lang.t("game.clock_none") } }</span>
+                                </label>
+                            }
+                        })
+                    }
+                    <button onclick={on_create} disabled={!network}>{ lang.t("online.create") }</button>
+                </div>
+                <h2 class="text_titles">{ lang.t("online.join_title") }</h2>
+                <div>
+                    <input ref={code_input} type="text" placeholder={lang.t("online.code_placeholder")} maxlength="5" size="8" />
+                    <button onclick={on_join} disabled={!network}>{ lang.t("online.join") }</button>
+                    <button onclick={on_watch.reform(move |_| entered_code())} disabled={!network}>{ lang.t("online.watch") }</button>
+                </div>
+                { for lobby_message.as_ref().map(|message| html! { <p class="online-message">{ lang.translate(message) }</p> }) }
+                <h2 class="text_titles">{ lang.t("online.watch_title") }</h2>
+                {
+                    match games.as_ref() {
+                        _ if !network => html! {},
+                        None => html! { <p>{ lang.t("online.looking") }</p> },
+                        Some(list) if list.is_empty() => html! { <p>{ lang.t("online.nobody") }</p> },
+                        Some(list) => html! {
+                            <table class="history">
+                                <tr>
+                                    <th>{ lang.t("scores.game") }</th>
+                                    <th>{ lang.t("online.code") }</th>
+                                    <th>{ lang.t("scores.moves") }</th>
+                                    <th>{ lang.t("online.watching") }</th>
+                                    <th></th>
+                                </tr>
+                                {
+                                    for list.iter().map(|summary| {
+                                        let code = summary.code.clone();
+                                        html! {
+                                            <tr>
+                                                <td>{ summary.game.name(lang) }</td>
+                                                <td>{ &summary.code }</td>
+                                                <td>{ summary.moves }</td>
+                                                <td>{ summary.spectators }</td>
+                                                <td><button onclick={on_watch.reform(move |_| code.clone())}>{ lang.t("online.watch") }</button></td>
+                                            </tr>
+                                        }
+                                    })
+                                }
+                            </table>
+                        },
+                    }
+                }
+            </div>
+        };
+    };
+
+    let Some(view) = online.view.clone() else {
+        return html! {
+            <div class="content_padding">
+                { back }
+                <p>{ lang.t(if online.offline { "online.retrying" } else { "online.connecting" }) }</p>
+                <button onclick={on_leave}>{ lang.t(if matches!(followed, Following::Player(_)) { "online.leave" } else { "online.stop_watching" }) }</button>
+            </div>
+        };
+    };
+
+    let board = online.pending.clone().unwrap_or_else(|| view.board.clone());
+    let all_joined = view.seats.iter().all(|seat| seat.joined);
+    let my_seat = match &followed {
+        Following::Player(seat) => Some(seat.clone()),
+        Following::Spectator(_) => None,
+    };
+    let my_turn = my_seat.as_ref().is_some_and(|my_seat| {
+        online.pending.is_none() && !online.offline && all_joined && view.board.seat_to_move() == Some(my_seat.seat)
+    });
+
+    let on_column_click = {
+        let online = online.clone();
+        let view = view.clone();
+        let my_seat = my_seat.clone();
+        let piece = *selected_piece;
+        let muted = settings.muted;
+        Callback::from(move |col: usize| {
+            let Some(my_seat) = my_seat.clone().filter(|_| my_turn) else {
+                return;
+            };
+            let mut predicted = view.board.clone();
+            if let Err(reason) = predicted.play(col, piece) {
+                sound::play(Sound::Invalid, muted);
+                online.dispatch(OnlineAction::Rejected(reason.to_owned()));
+                return;
+            }
+            online.dispatch(OnlineAction::Sent(predicted));
+            let online = online.clone();
+            let board_version = view.board_version;
+            spawn_local(async move {
+                match send_move(&my_seat, board_version, col, piece).await {
+                    Ok(view) => online.dispatch(OnlineAction::Update(view)),
+                    Err(ApiError::Offline) => {
+                        online.dispatch(OnlineAction::Offline);
+                        online.dispatch(OnlineAction::Rejected("Your move couldn't be sent; try again once you're back online".to_owned()));
+                    }
+                    Err(e) => online.dispatch(OnlineAction::Rejected(e.reason())),
+                }
+            });
+        })
+    };
+
+    // Arrows (and Home/End) choose a column, T and O choose a letter, Enter or Space plays
+    let on_board_key = {
+        let keyboard_col = keyboard_col.clone();
+        let selected_piece = selected_piece.clone();
+        let on_column_click = on_column_click.clone();
+        let cols = match &board {
+            OnlineBoard::ConnectFour(b) => b.cols,
+            OnlineBoard::TootOtto(b) => b.cols,
+        };
+        Callback::from(move |e: KeyboardEvent| {
+            match board_key(&e.key()) {
+                Some(BoardKey::Column(step)) => keyboard_col.set(Some(step_column(*keyboard_col, step, cols))),
+                Some(BoardKey::Piece(piece)) => selected_piece.set(Some(piece)),
+                Some(BoardKey::Drop) => {
+                    if let Some(col) = *keyboard_col {
+                        on_column_click.emit(col);
+                    }
+                }
+                None => return,
+            }
+            e.prevent_default();
+        })
+    };
+
+    let status = match &my_seat {
+        Some(_) if !all_joined => lang.f("online.share_code", &[("code", &view.code)]),
+        Some(my_seat) => match view.board.result() {
+            Some(Some(winner)) if winner == my_seat.seat => lang.t("online.you_win").to_owned(),
+            Some(Some(_)) => lang.t("online.opponent_wins").to_owned(),
+            Some(None) => lang.t("game.draw").to_owned(),
+            None if online.pending.is_some() => lang.t("online.sending").to_owned(),
+            None if my_turn => lang.t("online.your_turn").to_owned(),
+            None => lang.t("online.waiting").to_owned(),
+        },
+        None if !all_joined => lang.t("online.waiting_second").to_owned(),
+        None => match (view.board.result(), view.board.seat_to_move()) {
+            (Some(Some(winner)), _) => lang.f("announce.wins", &[("player", &view.board.seat_label(lang, winner))]),
+            (Some(None), _) => lang.t("game.draw").to_owned(),
+            (None, Some(seat)) => lang.f("online.to_move", &[("player", &view.board.seat_label(lang, seat))]),
+            (None, None) => String::new(),
+        },
+    };
+    let disconnected = match &my_seat {
+        Some(my_seat) => {
+            let opponent = view.seats.get(1 - my_seat.seat.min(1));
+            opponent.filter(|seat| seat.joined && !seat.connected).map(|_| lang.t("online.opponent_disconnected"))
+        }
+        None => view.seats.iter().any(|seat| seat.joined && !seat.connected).then_some(lang.t("online.player_disconnected")),
+    };
+    // each seat's time, less what the running clock has used since the server sent it
+    let clocks = view.clock.as_ref().map(|clock| {
+        let since = (js_sys::Date::now() - online.received_ms).max(0.0) as u64;
+        (0..2)
+            .map(|seat| {
+                let left = clock.remaining_ms.get(seat).copied().unwrap_or(0);
+                let left = if clock.running == Some(seat) { left.saturating_sub(since) } else { left };
+                format!("{} {}", view.board.seat_label(lang, seat), format_remaining(Duration::from_millis(left)))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    });
+    let watching = match view.spectators {
+        0 => String::new(),
+        n => lang.plural("online.spectators", n, &[]),
+    };
+
+    let (players_icon, comp_icon) = {
+        let (player1, player2) = settings.icons();
+        (player1.url, player2.url)
+    };
+
+    // each cell's piece, and what a screen reader says about it
+    let (title, container, cols, cells, announcement): (&str, &str, usize, Vec<(Html, String)>, String) = match &board {
+        OnlineBoard::ConnectFour(b) => (
+            GameKind::ConnectFour.name(lang),
+            "container-connect4",
+            b.cols,
+            b.grid.iter().flatten().map(|cell| match *cell {
+                connect4::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                connect4::Cell::Occupied(player) => {
+                    let (icon, name) = if player == connect4::Player::Red { (players_icon, lang.t("player.1")) } else { (comp_icon, lang.t("player.2")) };
+                    (html! { <img src={icon} alt="" /> }, name.to_owned())
+                }
+            }).collect(),
+            connect4_announcement(lang, b),
+        ),
+        OnlineBoard::TootOtto(b) => (
+            GameKind::TootOtto.name(lang),
+            "container-toototto",
+            b.cols,
+            b.grid.iter().flatten().map(|cell| match *cell {
+                toot_otto::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                toot_otto::Cell::Occupied(piece) => (
+                    html! {
+                        <text>
+                            { if piece == Piece::T { "T" } else { "O" } }
+                        </text>
+                    },
+                    format!("{:?}", piece),
+                ),
+            }).collect(),
+            toot_otto_announcement(lang, b, |word| {
+                board.seat_label(lang, if word == toot_otto::Player::Toot { 0 } else { 1 })
+            }),
+        ),
+    };
+    let you_are = match (&board, my_seat.as_ref().map(|my_seat| my_seat.seat)) {
+        (_, None) => html! { { lang.t("online.you_watch") } },
+        (OnlineBoard::ConnectFour(_), Some(0)) => html! { <>{ lang.f("online.you_are", &[("player", &board.seat_label(lang, 0))]) }{ " " }<img src={players_icon} alt="" width="40" height="40" style={format!("border-bottom: 4px solid {};", player_color(connect4::Player::Red))} /></> },
+        (OnlineBoard::ConnectFour(_), Some(_)) => html! { <>{ lang.f("online.you_are", &[("player", &board.seat_label(lang, 1))]) }{ " " }<img src={comp_icon} alt="" width="40" height="40" style={format!("border-bottom: 4px solid {};", player_color(connect4::Player::Yellow))} /></> },
+        (OnlineBoard::TootOtto(_), Some(seat)) => html! { { lang.f("online.you_are", &[("player", &board.seat_label(lang, seat))]) } },
+    };
+    let on_piece_select = |piece: Piece| {
+        let selected_piece = selected_piece.clone();
+        Callback::from(move |_| selected_piece.set(Some(piece)))
+    };
+
+    html! {
+        <>
+            { back }
+            <h2 class="text_titles">{ lang.f("online.game_title", &[("game", &title), ("code", &view.code)]) }</h2>
+            <p>{ you_are }</p>
+            <p class="online-status">{ status }</p>
+            { for clocks.map(|clocks| html! { <p>{ clocks }</p> }) }
+            <p>{ watching }</p>
+            {
+                if online.offline {
+                    html! { <p class="online-message">{ lang.t("online.reconnecting") }</p> }
+                } else if let Some(disconnected) = disconnected {
+                    html! { <p class="online-message">{ disconnected }</p> }
+                } else {
+                    html! {}
+                }
+            }
+            { for online.message.as_ref().map(|message| html! { <p class="online-message">{ lang.translate(message) }</p> }) }
+            {
+                if matches!(board, OnlineBoard::TootOtto(_)) && my_seat.is_some() {
+                    html! {
+                        <div>
+                            <button onclick={on_piece_select(Piece::T)} disabled={*selected_piece == Some(Piece::T)} aria-pressed={(*selected_piece == Some(Piece::T)).to_string()}>{ lang.f("game.select_piece", &[("piece", &"T")]) }</button>
+                            <button onclick={on_piece_select(Piece::O)} disabled={*selected_piece == Some(Piece::O)} aria-pressed={(*selected_piece == Some(Piece::O)).to_string()}>{ lang.f("game.select_piece", &[("piece", &"O")]) }</button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <p class="visually-hidden" aria-live="polite">{ if all_joined { announcement } else { String::new() } }</p>
+            <div class={container} style={board_style(cols)}>
+                <div class="grid" role="group" tabindex="0" onkeydown={on_board_key}
+                    aria-label={lang.f("online.board", &[("game", &title), ("cols", &cols)])}>
+                    {
+                        for cells.into_iter().enumerate().map(|(i, (piece, name))| {
+                            let (row, col) = (i / cols, i % cols);
+                            html! {
+                                <button
+                                    class={classes!("cell", (*keyboard_col == Some(col)).then_some("keyboard-column"))}
+                                    aria-label={lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &name)])}
+                                    tabindex="-1"
+                                    onclick={on_column_click.reform(move |_| col)}
+                                    disabled={!my_turn}
+                                >
+                                    { piece }
+                                </button>
+                            }
+                        })
+                    }
+                </div>
+            </div>
+            <button onclick={on_leave}>{ lang.t(if my_seat.is_some() { "online.leave" } else { "online.stop_watching" }) }</button>
+            <MuteToggle />
+        </>
+    }
+}
@@ -0,0 +1,314 @@
+use crate::history::GameKind;
+use crate::i18n::Language;
+use crate::settings::Settings;
+use crate::{board_style, player_color, player_label, Route};
+use serde::{Deserialize, Serialize};
+use web_sys::{window, Storage};
+use yew::prelude::*;
+use yew_app::connect4::{self, Board, Player};
+use yew_app::toot_otto::{self, Board as TootBoard, Piece, Player as TootPlayer};
+use yew_router::prelude::*;
+
+// Puzzles found in self-play games by `backend gen-puzzles`, copied from backend/puzzles/
+const BUNDLED: &str = include_str!("../puzzles/puzzles.json");
+// localStorage key of the solved streak
+const STREAK_KEY: &str = "puzzleStreak";
+
+// These match the backend's puzzle.rs
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+struct Move {
+    col: usize,
+    #[serde(default)]
+    piece: Option<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Line {
+    play: Move,
+    #[serde(default)]
+    reply: Option<Move>,
+    #[serde(default)]
+    then: Vec<Line>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct Puzzle {
+    game: GameKind,
+    rows: usize,
+    cols: usize,
+    opening: Vec<Move>,
+    moves: usize,
+    solution: Vec<Line>,
+}
+
+// Puzzles solved in a row without a wrong move, and the best run so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Streak {
+    current: u32,
+    best: u32,
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+impl Streak {
+    fn load() -> Streak {
+        storage()
+            .and_then(|storage| storage.get_item(STREAK_KEY).ok().flatten())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let (Some(storage), Ok(json)) = (storage(), serde_json::to_string(self)) {
+            let _ = storage.set_item(STREAK_KEY, &json);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PuzzleBoard {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+impl PuzzleBoard {
+    fn play(&mut self, mv: Move) -> Result<(), &'static str> {
+        match (self, mv.piece) {
+            (PuzzleBoard::ConnectFour(b), _) => b.insert_disc(mv.col),
+            (PuzzleBoard::TootOtto(b), Some(piece)) => b.insert_piece(mv.col, piece),
+            (PuzzleBoard::TootOtto(_), None) => Err("Toot and Otto moves need a piece"),
+        }
+    }
+}
+
+fn describe(lang: Language, mv: Move) -> String {
+    match mv.piece {
+        Some(piece) => lang.f("puzzles.piece_move", &[("piece", &format!("{:?}", piece)), ("col", &(mv.col + 1))]),
+        None => lang.f("puzzles.disc_move", &[("col", &(mv.col + 1))]),
+    }
+}
+
+fn toot_otto_name(player: TootPlayer) -> &'static str {
+    match player {
+        TootPlayer::Toot => "TOOT",
+        TootPlayer::Otto => "OTTO",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Solving,
+    Solved,
+    GaveUp,
+}
+
+// One go at a puzzle: the board so far, and the winning lines still open from it
+#[derive(Debug, Clone, PartialEq)]
+struct Attempt {
+    index: usize,
+    board: PuzzleBoard,
+    lines: Vec<Line>,
+    status: Status,
+    missed: bool, // a wrong move was tried, so solving it no longer adds to the streak
+    message: Option<String>,
+}
+
+impl Attempt {
+    fn start(puzzles: &[Puzzle], index: usize) -> Option<Attempt> {
+        let puzzle = puzzles.get(index)?;
+        let mut board = match puzzle.game {
+            GameKind::ConnectFour => PuzzleBoard::ConnectFour(Board::new(puzzle.rows, puzzle.cols)),
+            GameKind::TootOtto => PuzzleBoard::TootOtto(TootBoard::new(puzzle.rows, puzzle.cols)),
+        };
+        for &mv in &puzzle.opening {
+            board.play(mv).ok()?;
+        }
+        Some(Attempt { index, board, lines: puzzle.solution.clone(), status: Status::Solving, missed: false, message: None })
+    }
+}
+
+// Today's puzzle is the same for everyone, working through the set one day at a time
+fn daily_index(count: usize) -> usize {
+    (js_sys::Date::now() / 86_400_000.0) as usize % count.max(1)
+}
+
+#[function_component(Puzzles)]
+pub fn puzzles() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let puzzles = use_memo((), |_| serde_json::from_str::<Vec<Puzzle>>(BUNDLED).unwrap_or_default());
+    let attempt = use_state(|| Attempt::start(&puzzles, daily_index(puzzles.len())));
+    let streak = use_state(Streak::load);
+    let piece = use_state(|| Piece::T);
+
+    let open = {
+        let attempt = attempt.clone();
+        let puzzles = puzzles.clone();
+        Callback::from(move |index: usize| attempt.set(Attempt::start(&puzzles, index % puzzles.len().max(1))))
+    };
+
+    // A move on the board: kept and answered if it is on a winning line, otherwise taken back
+    let on_move = {
+        let attempt = attempt.clone();
+        let streak = streak.clone();
+        Callback::from(move |mv: Move| {
+            let Some(mut a) = (*attempt).clone().filter(|a| a.status == Status::Solving) else {
+                return;
+            };
+            match a.lines.iter().find(|line| line.play == mv).cloned() {
+                Some(line) => {
+                    let _ = a.board.play(line.play);
+                    a.message = line.reply.map(|reply| {
+                        let _ = a.board.play(reply);
+                        lang.f("puzzles.defence", &[("move", &describe(lang, reply))])
+                    });
+                    a.lines = line.then;
+                    if a.lines.is_empty() {
+                        a.status = Status::Solved;
+                        a.message = Some(lang.t("puzzles.solved").to_owned());
+                        if !a.missed {
+                            let mut s = *streak;
+                            s.current += 1;
+                            s.best = s.best.max(s.current);
+                            s.save();
+                            streak.set(s);
+                        }
+                    }
+                }
+                None => {
+                    a.message = Some(lang.f("puzzles.wrong", &[("move", &describe(lang, mv))]));
+                    if !a.missed {
+                        a.missed = true;
+                        let s = Streak { current: 0, ..*streak };
+                        s.save();
+                        streak.set(s);
+                    }
+                }
+            }
+            attempt.set(Some(a));
+        })
+    };
+
+    let give_up = {
+        let attempt = attempt.clone();
+        let streak = streak.clone();
+        Callback::from(move |_| {
+            if let Some(mut a) = (*attempt).clone() {
+                let moves: Vec<String> = a.lines.iter().map(|line| describe(lang, line.play)).collect();
+                a.message = Some(lang.f("puzzles.solution", &[("moves", &moves.join(", "))]));
+                a.status = Status::GaveUp;
+                a.missed = true;
+                let s = Streak { current: 0, ..*streak };
+                s.save();
+                streak.set(s);
+                attempt.set(Some(a));
+            }
+        })
+    };
+
+    let Some(a) = (*attempt).clone() else {
+        return html! {
+            <div class="content_padding">
+                <h1 class="text_titles">{ lang.t("puzzles.title") }</h1>
+                <p>{ lang.t("puzzles.none") }</p>
+            </div>
+        };
+    };
+    let puzzle = &puzzles[a.index];
+    let daily = daily_index(puzzles.len());
+
+    let icons = settings.connect4_icons();
+    let (rows, cols, container, to_move) = match &a.board {
+        PuzzleBoard::ConnectFour(b) => (b.rows, b.cols, "container-connect4", player_label(lang, b.current_turn).to_owned()),
+        PuzzleBoard::TootOtto(b) => (b.rows, b.cols, "container-toototto", toot_otto_name(b.current_turn).to_owned()),
+    };
+    let mv_for = {
+        let piece = *piece;
+        let toot_otto = matches!(a.board, PuzzleBoard::TootOtto(_));
+        move |col: usize| Move { col, piece: toot_otto.then_some(piece) }
+    };
+    let cells = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).map(|(row, col)| {
+        let (content, label) = match &a.board {
+            PuzzleBoard::ConnectFour(b) => match b.grid[row][col] {
+                connect4::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                connect4::Cell::Occupied(player) => (
+                    html! { <img src={icons[Player::ALL.iter().position(|&p| p == player).unwrap_or(0)]} alt="" /> },
+                    player_label(lang, player).to_owned(),
+                ),
+            },
+            PuzzleBoard::TootOtto(b) => match b.grid[row][col] {
+                toot_otto::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                toot_otto::Cell::Occupied(piece) => (
+                    html! { <text>{ if piece == Piece::T { "T" } else { "O" } }</text> },
+                    format!("{:?}", piece),
+                ),
+            },
+        };
+        let last = match &a.board {
+            PuzzleBoard::ConnectFour(b) => b.last_move,
+            PuzzleBoard::TootOtto(b) => b.last_move,
+        };
+        html! {
+            <button
+                class={classes!("cell", (last == Some((row, col))).then_some("last-move"))}
+                aria-label={lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &label)])}
+                onclick={on_move.reform(move |_| mv_for(col))}
+                disabled={a.status != Status::Solving}
+            >
+                { content }
+            </button>
+        }
+    });
+
+    html! {
+        <div class="content_padding">
+            <Link<Route> to={Route::Home}>
+                <button class="btn-back">
+                    <span class="circle" aria-hidden="true">
+                        <span class="icon arrow"></span>
+                    </span>
+                    <span class="button-text">{ lang.t("nav.back_home") }</span>
+                </button>
+            </Link<Route>>
+            <h1 class="text_titles">{ lang.t(if a.index == daily { "home.daily_puzzle" } else { "puzzles.title" }) }</h1>
+            <p>{ lang.f("puzzles.progress", &[("index", &(a.index + 1)), ("count", &puzzles.len()), ("streak", &streak.current), ("best", &streak.best)]) }</p>
+            <h2>
+                {
+                    match &a.board {
+                        PuzzleBoard::ConnectFour(b) => html! {
+                            <span style={format!("border-bottom: 4px solid {};", player_color(b.current_turn))}>{ lang.plural("puzzles.goal", puzzle.moves, &[("game", &puzzle.game.name(lang)), ("player", &to_move)]) }</span>
+                        },
+                        PuzzleBoard::TootOtto(_) => html! { <span>{ lang.plural("puzzles.goal", puzzle.moves, &[("game", &puzzle.game.name(lang)), ("player", &to_move)]) }</span> },
+                    }
+                }
+            </h2>
+            {
+                if matches!(a.board, PuzzleBoard::TootOtto(_)) {
+                    let select = |p: Piece| { let piece = piece.clone(); Callback::from(move |_| piece.set(p)) };
+                    html! {
+                        <p>
+                            <button onclick={select(Piece::T)} aria-pressed={(*piece == Piece::T).to_string()}>{ "T" }</button>
+                            <button onclick={select(Piece::O)} aria-pressed={(*piece == Piece::O).to_string()}>{ "O" }</button>
+                        </p>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <div class={container} style={board_style(cols)}>
+                <div class="grid" role="group" aria-label={lang.f("puzzles.board", &[("rows", &rows), ("cols", &cols)])}>
+                    { for cells }
+                </div>
+            </div>
+            <p class="replay-review" aria-live="polite">{ a.message.clone().unwrap_or_default() }</p>
+            <div class="sandbox-actions">
+                <button onclick={open.reform(move |_| a.index)}>{ lang.t("puzzles.start_over") }</button>
+                <button onclick={give_up} disabled={a.status != Status::Solving}>{ lang.t("puzzles.show_solution") }</button>
+                <button onclick={open.reform(move |_| a.index + 1)}>{ lang.t("puzzles.next") }</button>
+                <button onclick={open.reform(move |_| daily)} disabled={a.index == daily}>{ lang.t("puzzles.today") }</button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,36 @@
+use gloo_events::EventListener;
+use web_sys::window;
+use yew::prelude::*;
+
+// Let sw.js keep a copy of the app, so it can be installed and played against the computer
+// offline. Browsers only have service workers on https and localhost; anywhere else the app
+// works as before, from the network.
+pub fn register_service_worker() {
+    let Some(navigator) = window().map(|window| window.navigator()) else {
+        return;
+    };
+    if js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        let _ = navigator.service_worker().register("/sw.js");
+    }
+}
+
+// Whether the browser has a network connection, kept up to date as it comes and goes.
+// It can still be wrong about the game server, so requests to it still have to cope with failing.
+#[hook]
+pub fn use_online() -> bool {
+    let online = use_state(|| window().is_none_or(|window| window.navigator().on_line()));
+    {
+        let online = online.clone();
+        use_effect_with((), move |_| {
+            let listeners = window().map(|window| {
+                let back = online.clone();
+                [
+                    EventListener::new(&window, "online", move |_| back.set(true)),
+                    EventListener::new(&window, "offline", move |_| online.set(false)),
+                ]
+            });
+            move || drop(listeners)
+        });
+    }
+    *online
+}
@@ -0,0 +1,440 @@
+use crate::history::{load_history, GameRecord, Replay};
+use crate::i18n::Language;
+use crate::scores::format_date;
+use crate::settings::Settings;
+use crate::{board_key, board_style, player_color, seat_name, BoardKey, ColumnStep, Route, API_URL};
+use gloo_net::http::Request;
+use gloo_timers::callback::Timeout;
+use serde::Deserialize;
+use std::cell::Cell as Flag;
+use std::rc::Rc;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_app::connect4::{self, Board, Player};
+use yew_app::toot_otto::{self, Board as TootBoard, Piece, Player as TootPlayer};
+use yew_router::prelude::*;
+
+// How long autoplay shows each move
+const AUTOPLAY_MS: u32 = 800;
+// Bigger boards than any page offers are refused, so a bad file can't allocate a huge grid
+const MAX_CELLS: usize = 144;
+
+// These match the backend's analysis.rs
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+enum Eval {
+    Wins { moves: usize },  // the winning disc is this many discs away, counting the move itself
+    Loses { moves: usize }, // the same for the opponent's winning disc
+    Score(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+struct MoveReview {
+    best: usize,
+    best_eval: Eval,
+    played_eval: Eval,
+}
+
+impl Eval {
+    // From the point of view of the player making the move
+    fn describe(self, lang: Language) -> String {
+        match self {
+            Eval::Wins { moves: 1 } => lang.t("replay.wins_now").to_owned(),
+            Eval::Wins { moves } => lang.plural("replay.wins_in", moves.div_ceil(2), &[]),
+            Eval::Loses { moves } => lang.plural("replay.loses_in", moves / 2, &[]),
+            Eval::Score(score) => format!("{:+}", score),
+        }
+    }
+}
+
+// "??" throws away a win or walks into a loss, "?" gives away a lot of the engine's score
+fn mistake(review: &MoveReview) -> Option<&'static str> {
+    match (review.best_eval, review.played_eval) {
+        (Eval::Wins { .. }, Eval::Wins { .. }) | (Eval::Loses { .. }, Eval::Loses { .. }) => None,
+        (Eval::Wins { .. }, _) | (_, Eval::Loses { .. }) => Some("??"),
+        (Eval::Score(best), Eval::Score(played)) if best - played >= 10 => Some("?"),
+        _ => None,
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Analysis {
+    Pending,
+    Done(Rc<Vec<MoveReview>>),
+    Unavailable(&'static str),
+}
+
+enum ReplayBoard {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+// The board after the first `ply` moves of the replay
+fn board_at(replay: &Replay, ply: usize) -> Result<ReplayBoard, &'static str> {
+    let (rows, cols) = match replay {
+        Replay::ConnectFour { rows, cols, .. } | Replay::TootOtto { rows, cols, .. } => (*rows, *cols),
+    };
+    if rows == 0 || cols == 0 || rows * cols > MAX_CELLS {
+        return Err("The board size isn't one this app plays");
+    }
+    // the recorded player makes each move, even when a flag changed the turn order since
+    match replay {
+        Replay::ConnectFour { turn_order, moves, .. } => {
+            let mut board = Board::with_players(rows, cols, turn_order.clone())?;
+            for &(player, col) in moves.iter().take(ply) {
+                board.current_turn = player;
+                board.insert_disc(col)?;
+            }
+            Ok(ReplayBoard::ConnectFour(board))
+        }
+        Replay::TootOtto { moves, .. } => {
+            let mut board = TootBoard::new(rows, cols);
+            for &(player, col, piece) in moves.iter().take(ply) {
+                board.current_turn = player;
+                board.insert_piece(col, piece)?;
+            }
+            Ok(ReplayBoard::TootOtto(board))
+        }
+    }
+}
+
+fn toot_otto_name(player: TootPlayer) -> &'static str {
+    match player {
+        TootPlayer::Toot => "TOOT",
+        TootPlayer::Otto => "OTTO",
+    }
+}
+
+// "Player1: column 4", for the move list and the screen reader
+fn describe_move(lang: Language, replay: &Replay, index: usize) -> String {
+    match replay {
+        Replay::ConnectFour { turn_order, moves, .. } => {
+            let (player, col) = moves[index];
+            lang.f("replay.disc_move", &[("player", &seat_name(lang, player, turn_order.len())), ("col", &(col + 1))])
+        }
+        Replay::TootOtto { moves, .. } => {
+            let (player, col, piece) = moves[index];
+            lang.f("replay.piece_move", &[("player", &toot_otto_name(player)), ("piece", &format!("{:?}", piece)), ("col", &(col + 1))])
+        }
+    }
+}
+
+// Ask the backend's engine about every move; only two-player Connect Four games can be reviewed
+async fn analyse(replay: &Replay) -> Analysis {
+    if !matches!(replay, Replay::ConnectFour { turn_order, .. } if turn_order.len() == 2) {
+        return Analysis::Unavailable("The engine only reviews two-player Connect Four games.");
+    }
+    let Ok(request) = Request::post(&format!("{}/analysis", API_URL)).json(replay) else {
+        return Analysis::Unavailable("The game could not be sent for analysis.");
+    };
+    match request.send().await {
+        Ok(response) if response.ok() => match response.json::<Vec<MoveReview>>().await {
+            Ok(reviews) => Analysis::Done(Rc::new(reviews)),
+            Err(_) => Analysis::Unavailable("The game server sent an analysis this app can't read."),
+        },
+        Ok(_) => Analysis::Unavailable("The game server couldn't analyse this game."),
+        Err(_) => Analysis::Unavailable("Move analysis needs the game server, which can't be reached."),
+    }
+}
+
+fn render_board(lang: Language, board: &ReplayBoard, icons: [&'static str; 4], final_position: bool) -> Html {
+    let (rows, cols, container) = match board {
+        ReplayBoard::ConnectFour(b) => (b.rows, b.cols, "container-connect4"),
+        ReplayBoard::TootOtto(b) => (b.rows, b.cols, "container-toototto"),
+    };
+    let (last_move, winning) = match board {
+        ReplayBoard::ConnectFour(b) => (b.last_move, b.winning_line()),
+        ReplayBoard::TootOtto(b) => (b.last_move, b.winning_line()),
+    };
+    // the winning line only glows once the game has been played out
+    let winning = winning.filter(|_| final_position).unwrap_or_default();
+    let cells = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).map(|(row, col)| {
+        let (piece, label) = match board {
+            ReplayBoard::ConnectFour(b) => match b.grid[row][col] {
+                connect4::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                connect4::Cell::Occupied(player) => {
+                    let icon = icons[Player::ALL.iter().position(|&p| p == player).unwrap_or(0)];
+                    // with more than two players, a coloured ring tells the icons apart at a glance
+                    let ring = if b.turn_order.len() > 2 { format!("border-radius: 50%; box-shadow: inset 0 0 0 5px {};", player_color(player)) } else { String::new() };
+                    (
+                        html! { <img src={icon} alt="" style={ring} /> },
+                        seat_name(lang, player, b.turn_order.len()).to_owned(),
+                    )
+                }
+            },
+            ReplayBoard::TootOtto(b) => match b.grid[row][col] {
+                toot_otto::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                toot_otto::Cell::Occupied(piece) => (
+                    html! { <text>{ if piece == Piece::T { "T" } else { "O" } }</text> },
+                    format!("{:?}", piece),
+                ),
+            },
+        };
+        html! {
+            <div
+                class={classes!("cell", winning.contains(&(row, col)).then_some("winning"), (last_move == Some((row, col))).then_some("last-move"))}
+                role="img"
+                aria-label={lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &label)])}
+            >
+                { piece }
+            </div>
+        }
+    });
+    html! {
+        <div class={container} style={board_style(cols)}>
+            <div class="grid">{ for cells }</div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ReplayViewerProps {
+    #[prop_or_default]
+    pub date_ms: Option<u64>, // The game from the history to open, if any
+}
+
+// Step through a recorded game, from the history or from a saved file
+#[function_component(ReplayViewer)]
+pub fn replay_viewer(props: &ReplayViewerProps) -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let record: UseStateHandle<Option<Rc<GameRecord>>> = use_state(|| None);
+    let ply = use_state(|| 0usize);
+    let playing = use_state(|| false);
+    let analysis = use_state(|| Analysis::Pending);
+    let error: UseStateHandle<Option<&'static str>> = use_state(|| None);
+
+    // Show a game from its first move, if its moves can all be played
+    let open = {
+        let record = record.clone();
+        let ply = ply.clone();
+        let playing = playing.clone();
+        let error = error.clone();
+        Callback::from(move |game: GameRecord| {
+            let checked = match &game.replay {
+                Some(replay) => board_at(replay, replay.plies()).map(drop),
+                None => Err("This game was recorded before replays were saved"),
+            };
+            match checked {
+                Ok(()) => {
+                    record.set(Some(Rc::new(game)));
+                    ply.set(0);
+                    playing.set(false);
+                    error.set(None);
+                }
+                Err(reason) => error.set(Some(reason)),
+            }
+        })
+    };
+
+    // A game picked on the History page
+    {
+        let open = open.clone();
+        let error = error.clone();
+        use_effect_with(props.date_ms, move |&date_ms| {
+            if let Some(date_ms) = date_ms {
+                spawn_local(async move {
+                    let (games, _) = load_history().await;
+                    match games.into_iter().rev().find(|game| game.date_ms == date_ms) {
+                        Some(game) => open.emit(game),
+                        None => error.set(Some("That game isn't in the history any more")),
+                    }
+                });
+            }
+        });
+    }
+
+    // The engine's review, asked for once per game; a review of a game that is no longer open is dropped
+    {
+        let analysis = analysis.clone();
+        use_effect_with((*record).clone(), move |record| {
+            let alive = Rc::new(Flag::new(true));
+            analysis.set(Analysis::Pending);
+            if let Some(replay) = record.as_ref().and_then(|game| game.replay.clone()) {
+                let alive = alive.clone();
+                spawn_local(async move {
+                    let result = analyse(&replay).await;
+                    if alive.get() {
+                        analysis.set(result);
+                    }
+                });
+            }
+            move || alive.set(false)
+        });
+    }
+
+    let plies = record.as_ref().and_then(|game| game.replay.as_ref()).map_or(0, Replay::plies);
+
+    // Autoplay steps forward until the last move
+    {
+        let ply = ply.clone();
+        let playing = playing.clone();
+        use_effect_with((*playing, *ply, plies), move |&(is_playing, at, plies)| {
+            if is_playing && at >= plies {
+                playing.set(false);
+            }
+            let timeout = (is_playing && at < plies).then(|| Timeout::new(AUTOPLAY_MS, move || ply.set(at + 1)));
+            move || drop(timeout)
+        });
+    }
+
+    let go_to = {
+        let ply = ply.clone();
+        let playing = playing.clone();
+        Callback::from(move |to: usize| {
+            playing.set(false);
+            ply.set(to.min(plies));
+        })
+    };
+    let toggle_autoplay = {
+        let ply = ply.clone();
+        let playing = playing.clone();
+        Callback::from(move |_| {
+            // playing from the end starts over
+            if !*playing && *ply >= plies {
+                ply.set(0);
+            }
+            playing.set(!*playing);
+        })
+    };
+    // the arrow keys that choose a column in a game step through the moves here
+    let on_key = {
+        let go_to = go_to.clone();
+        let at = *ply;
+        Callback::from(move |e: KeyboardEvent| {
+            let to = match board_key(&e.key()) {
+                Some(BoardKey::Column(ColumnStep::Left)) => at.saturating_sub(1),
+                Some(BoardKey::Column(ColumnStep::Right)) => at + 1,
+                Some(BoardKey::Column(ColumnStep::First)) => 0,
+                Some(BoardKey::Column(ColumnStep::Last)) => plies,
+                _ => return,
+            };
+            go_to.emit(to);
+            e.prevent_default();
+        })
+    };
+
+    let on_file = {
+        let open = open.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let open = open.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let text = JsFuture::from(file.text()).await.ok().and_then(|text| text.as_string());
+                match text.and_then(|text| serde_json::from_str::<GameRecord>(&text).ok()) {
+                    Some(game) => open.emit(game),
+                    None => error.set(Some("That file isn't a saved game")),
+                }
+            });
+        })
+    };
+
+    let viewer = match record.as_ref().and_then(|game| game.replay.as_ref().map(|replay| (game, replay))) {
+        None => html! {},
+        Some((game, replay)) => {
+            let at = *ply;
+            let board = board_at(replay, at).expect("replays are checked when they are opened");
+            let json = serde_json::to_string(&**game).unwrap_or_default();
+            let download = format!("data:application/json,{}", js_sys::encode_uri_component(&json));
+            let reviews = match &*analysis {
+                Analysis::Done(reviews) => Some(reviews.clone()),
+                _ => None,
+            };
+            let review = at.checked_sub(1).and_then(|index| reviews.as_ref()?.get(index).copied());
+            let result = match &game.winner {
+                Some(winner) => lang.f("replay.won", &[("player", &lang.translate(winner))]),
+                None => lang.t("replay.draw").to_owned(),
+            };
+            let position = match at {
+                0 => lang.t("replay.start").to_owned(),
+                at => lang.f("replay.position", &[("at", &at), ("plies", &plies), ("move", &describe_move(lang, replay, at - 1))]),
+            };
+            let players: Vec<String> = game.players.iter().map(|name| lang.translate(name)).collect();
+            html! {
+                <>
+                    <h2 class="text_titles">{ format!("{} - {}", game.game.name(lang), players.join(lang.t("replay.versus"))) }</h2>
+                    <p>{ format_date(lang, game.date_ms) }{ " - " }{ result }</p>
+                    <div class="replay-controls" role="group" aria-label={lang.t("replay.controls")} tabindex="0" onkeydown={on_key}>
+                        <button onclick={go_to.reform(|_| 0)} disabled={at == 0}>{ lang.t("replay.first") }</button>
+                        <button onclick={go_to.reform(move |_| at.saturating_sub(1))} disabled={at == 0}>{ lang.t("replay.previous") }</button>
+                        <button onclick={toggle_autoplay} aria-pressed={if *playing { "true" } else { "false" }}>{ lang.t(if *playing { "replay.pause" } else { "replay.autoplay" }) }</button>
+                        <button onclick={go_to.reform(move |_| at + 1)} disabled={at >= plies}>{ lang.t("replay.next") }</button>
+                        <button onclick={go_to.reform(move |_| plies)} disabled={at >= plies}>{ lang.t("replay.last") }</button>
+                    </div>
+                    <p aria-live="polite">{ position }</p>
+                    {
+                        match review {
+                            Some(r) if r.played_eval == r.best_eval => html! { <p class="replay-review">{ lang.f("replay.best_move", &[("eval", &r.played_eval.describe(lang))]) }</p> },
+                            Some(r) => html! {
+                                <p class="replay-review">
+                                    {
+                                        lang.f("replay.better_move", &[
+                                            ("mistake", &mistake(&r).map_or(String::new(), |m| format!("{} ", m))),
+                                            ("col", &(r.best + 1)),
+                                            ("best", &r.best_eval.describe(lang)),
+                                            ("played", &r.played_eval.describe(lang)),
+                                        ])
+                                    }
+                                </p>
+                            },
+                            None => html! {},
+                        }
+                    }
+                    { render_board(lang, &board, settings.connect4_icons(), at == plies) }
+                    {
+                        match &*analysis {
+                            Analysis::Pending => html! { <p>{ lang.t("replay.analysing") }</p> },
+                            Analysis::Unavailable(reason) => html! { <p>{ lang.translate(reason) }</p> },
+                            Analysis::Done(_) => html! {},
+                        }
+                    }
+                    <ol class="replay-moves">
+                        {
+                            for (0..plies).map(|index| {
+                                let marker = reviews.as_ref().and_then(|reviews| reviews.get(index)).and_then(mistake);
+                                html! {
+                                    <li>
+                                        <button class={classes!((index + 1 == at).then_some("current"))} onclick={go_to.reform(move |_| index + 1)}>
+                                            { describe_move(lang, replay, index) }{ marker.map_or(String::new(), |m| format!(" {}", m)) }
+                                        </button>
+                                    </li>
+                                }
+                            })
+                        }
+                    </ol>
+                    <a href={download} download={format!("game-{}.json", game.date_ms)}>{ lang.t("replay.download") }</a>
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="content_padding">
+            <Link<Route> to={Route::History}>
+                <button class="btn-back">
+                    <span class="circle" aria-hidden="true">
+                        <span class="icon arrow"></span>
+                    </span>
+                    <span class="button-text">{ lang.t("replay.back_history") }</span>
+                </button>
+            </Link<Route>>
+            <h1 class="text_titles">{ lang.t("home.replay") }</h1>
+            <p>
+                { lang.t("replay.pick_before") }<Link<Route> to={Route::History}>{ lang.t("home.history") }</Link<Route>>{ lang.t("replay.pick_after") }
+                <input type="file" accept="application/json,.json" onchange={on_file} aria-label={lang.t("replay.open_file")} />
+            </p>
+            {
+                match *error {
+                    Some(reason) => html! { <p class="online-message" role="alert">{ lang.translate(reason) }</p> },
+                    None => html! {},
+                }
+            }
+            { viewer }
+        </div>
+    }
+}
@@ -0,0 +1,728 @@
+use crate::history::GameKind;
+use crate::saved_game::{self, CONNECT4_GAME_KEY, TOOT_OTTO_GAME_KEY};
+use crate::settings::{Difficulty, FirstMover, Settings};
+use crate::{
+    board_style, player_color, seat_name, seating_from, use_ai_worker, Connect4Saved, Connect4Setup, Route, TootOttoSaved,
+    TootOttoSetup, COMPUTER_BUDGET_MS, CONNECT4_SIZES, TOOT_OTTO_SIZES,
+};
+use std::collections::HashSet;
+use std::hash::Hash;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_app::connect4::{Board, Cell, Player};
+use yew_app::toot_otto::{Board as TootBoard, Cell as TootCell, Piece, Player as TootPlayer, Winner};
+use yew_app::worker::{AiRequest, AiResponse};
+use yew_router::prelude::*;
+
+// A position being set up. Only the grid and whose turn it is matter; check finds moves that lead there.
+#[derive(Debug, Clone, PartialEq)]
+enum Position {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+// What a click on a cell puts there
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Brush {
+    Empty,
+    Disc(Player),
+    Piece(Piece),
+}
+
+fn empty_connect4(turn_order: Vec<Player>, rows: usize, cols: usize) -> Position {
+    Position::ConnectFour(Board::with_players(rows, cols, turn_order).expect("2 to 4 players once each are a valid turn order"))
+}
+
+fn empty_toot_otto(rows: usize, cols: usize) -> Position {
+    Position::TootOtto(TootBoard::new(rows, cols))
+}
+
+fn disc_letter(player: Player) -> char {
+    match player {
+        Player::Red => 'r',
+        Player::Yellow => 'y',
+        Player::Green => 'g',
+        Player::Blue => 'b',
+    }
+}
+
+// Positions are shared as one line of text, rows from the top separated by commas:
+//   Connect Four:  c4:<turn order>:<r|y|g|b to move>:<cells, . r y g b>   e.g. c4:ry:y:.......,.......,.......,.......,.......,...r...
+// Links shared before the turn order could be picked have the number of players instead, who go in seating order.
+//   Toot and Otto: to:<toot|otto to move>:<cells, . T O>               e.g. to:otto:......,......,......,..TO..
+fn position_string(position: &Position) -> String {
+    match position {
+        Position::ConnectFour(b) => {
+            let rows: Vec<String> = b.grid.iter()
+                .map(|line| line.iter().map(|cell| match cell {
+                    Cell::Empty => '.',
+                    Cell::Occupied(player) => disc_letter(*player),
+                }).collect())
+                .collect();
+            let order: String = b.turn_order.iter().map(|&p| disc_letter(p)).collect();
+            format!("c4:{}:{}:{}", order, disc_letter(b.current_turn), rows.join(","))
+        }
+        Position::TootOtto(b) => {
+            let rows: Vec<String> = b.grid.iter()
+                .map(|line| line.iter().map(|cell| match cell {
+                    TootCell::Empty => '.',
+                    TootCell::Occupied(Piece::T) => 'T',
+                    TootCell::Occupied(Piece::O) => 'O',
+                }).collect())
+                .collect();
+            let to_move = if b.current_turn == TootPlayer::Toot { "toot" } else { "otto" };
+            format!("to:{}:{}", to_move, rows.join(","))
+        }
+    }
+}
+
+// The grid of a shared position, checked to be one of the sizes the game pages offer
+fn parse_grid<T>(rows: &str, sizes: &[(usize, usize)], cell: impl Fn(char) -> Option<T>) -> Result<Vec<Vec<T>>, &'static str> {
+    let grid = rows
+        .split(',')
+        .map(|line| line.chars().map(&cell).collect::<Option<Vec<T>>>())
+        .collect::<Option<Vec<_>>>()
+        .ok_or("The position has a cell this game doesn't have")?;
+    let cols = grid.first().map_or(0, Vec::len);
+    if grid.iter().any(|line| line.len() != cols) || !sizes.contains(&(grid.len(), cols)) {
+        return Err("The position's board isn't a size the game is played on");
+    }
+    Ok(grid)
+}
+
+fn parse_position(text: &str) -> Result<Position, &'static str> {
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    match parts.as_slice() {
+        ["c4", order, to_move, rows] => {
+            let turn_order: Vec<Player> = match order.parse::<usize>() {
+                Ok(players) if (2..=4).contains(&players) => Player::ALL[..players].to_vec(),
+                Ok(_) => return Err("Connect Four is played by 2 to 4 players"),
+                Err(_) => order
+                    .chars()
+                    .map(|letter| Player::ALL.into_iter().find(|&p| disc_letter(p) == letter))
+                    .collect::<Option<_>>()
+                    .ok_or("The turn order has a player this game doesn't have")?,
+            };
+            let players = turn_order.len();
+            if !(2..=4).contains(&players) {
+                return Err("Connect Four is played by 2 to 4 players");
+            }
+            let seats = &Player::ALL[..players];
+            if !seats.iter().all(|p| turn_order.contains(p)) {
+                return Err("The turn order must name each player once");
+            }
+            let disc = |letter: char| seats.iter().copied().find(|&p| disc_letter(p) == letter);
+            let grid = parse_grid(rows, &CONNECT4_SIZES, |c| if c == '.' { Some(Cell::Empty) } else { disc(c).map(Cell::Occupied) })?;
+            let mut letters = to_move.chars();
+            let current_turn = letters.next().and_then(disc).filter(|_| letters.next().is_none()).ok_or("Whose turn it is isn't one of the players")?;
+            let Position::ConnectFour(mut board) = empty_connect4(turn_order, grid.len(), grid[0].len()) else {
+                unreachable!("empty_connect4 makes Connect Four positions");
+            };
+            board.grid = grid;
+            board.current_turn = current_turn;
+            Ok(Position::ConnectFour(board))
+        }
+        ["to", to_move, rows] => {
+            let current_turn = match *to_move {
+                "toot" => TootPlayer::Toot,
+                "otto" => TootPlayer::Otto,
+                _ => return Err("Whose turn it is must be toot or otto"),
+            };
+            let grid = parse_grid(rows, &TOOT_OTTO_SIZES, |c| match c {
+                '.' => Some(TootCell::Empty),
+                'T' => Some(TootCell::Occupied(Piece::T)),
+                'O' => Some(TootCell::Occupied(Piece::O)),
+                _ => None,
+            })?;
+            let mut board = TootBoard::new(grid.len(), grid[0].len());
+            board.grid = grid;
+            board.current_turn = current_turn;
+            Ok(Position::TootOtto(board))
+        }
+        _ => Err("That isn't a position from the sandbox"),
+    }
+}
+
+// Pieces fall to the bottom, so no column may have a gap under a piece
+fn floating<T>(grid: &[Vec<T>], is_empty: impl Fn(&T) -> bool) -> bool {
+    let cols = grid.first().map_or(0, Vec::len);
+    (0..cols).any(|col| grid.windows(2).any(|rows| !is_empty(&rows[0][col]) && is_empty(&rows[1][col])))
+}
+
+// Give up on a position after this many dead ends, rather than freeze the page
+const SEARCH_LIMIT: usize = 200_000;
+
+// The pieces of each column, bottom first
+fn columns<T: Copy>(grid: &[Vec<T>], is_empty: impl Fn(&T) -> bool) -> Vec<Vec<T>> {
+    let cols = grid.first().map_or(0, Vec::len);
+    (0..cols).map(|col| grid.iter().rev().map(|line| line[col]).take_while(|cell| !is_empty(cell)).collect()).collect()
+}
+
+// An order to drop the pieces in, as (column, piece), that stacks every column up as in `columns`, if there is one.
+// `fits(dropped, piece)` says whether `piece` can go next; `key` sums up what matters about the
+// pieces dropped so far, so a position reached again that way is only searched once.
+fn drop_order<T: Copy, K: Hash + Eq>(
+    columns: &[Vec<T>],
+    fits: impl Fn(&[(usize, T)], T) -> bool,
+    key: impl Fn(&[(usize, T)]) -> K,
+) -> Result<Option<Vec<(usize, T)>>, &'static str> {
+    fn search<T: Copy, K: Hash + Eq>(
+        columns: &[Vec<T>],
+        dropped: &mut Vec<(usize, T)>,
+        heights: &mut Vec<usize>,
+        dead_ends: &mut HashSet<(Vec<usize>, K)>,
+        fits: &impl Fn(&[(usize, T)], T) -> bool,
+        key: &impl Fn(&[(usize, T)]) -> K,
+    ) -> Result<bool, &'static str> {
+        if heights.iter().zip(columns).all(|(&height, column)| height == column.len()) {
+            return Ok(true);
+        }
+        if dead_ends.len() >= SEARCH_LIMIT {
+            return Err("This position has too many ways it could have been played to check them all");
+        }
+        if dead_ends.contains(&(heights.clone(), key(dropped))) {
+            return Ok(false);
+        }
+        for col in 0..columns.len() {
+            let Some(&piece) = columns[col].get(heights[col]) else {
+                continue;
+            };
+            if fits(dropped, piece) {
+                dropped.push((col, piece));
+                heights[col] += 1;
+                if search(columns, dropped, heights, dead_ends, fits, key)? {
+                    return Ok(true);
+                }
+                dropped.pop();
+                heights[col] -= 1;
+            }
+        }
+        dead_ends.insert((heights.clone(), key(dropped)));
+        Ok(false)
+    }
+
+    let mut dropped = Vec::new();
+    let mut heights = vec![0; columns.len()];
+    let found = search(columns, &mut dropped, &mut heights, &mut HashSet::new(), &fits, &key)?;
+    Ok(found.then_some(dropped))
+}
+
+// Whether real play could have led here, and the game is still on. If so, the position comes back
+// played out move by move, so the game page can undo, replay and count pieces as in any other game.
+fn check(position: &Position) -> Result<Position, &'static str> {
+    match position {
+        Position::ConnectFour(b) => {
+            if floating(&b.grid, |&cell| cell == Cell::Empty) {
+                return Err("A disc is floating: discs rest on the bottom row or on other discs");
+            }
+            // The first player in the turn order starts, so going round the table from them everyone has
+            // played the same number of discs, except that the first few have played one more
+            let players = b.turn_order.len();
+            let discs = b.grid.iter().flatten().filter(|&&cell| cell != Cell::Empty).count();
+            let fits_turns = b.turn_order.iter().enumerate().all(|(i, &player)| {
+                let played = b.grid.iter().flatten().filter(|&&cell| cell == Cell::Occupied(player)).count();
+                played == discs / players + usize::from(i < discs % players)
+            });
+            if !fits_turns || b.current_turn != b.turn_order[discs % players] {
+                return Err("The disc counts don't fit the turn order: the first player in it starts, then everyone takes turns, one disc at a time");
+            }
+            let won = (0..b.rows).flat_map(|row| (0..b.cols).map(move |col| (row, col)))
+                .any(|(row, col)| b.grid[row][col] != Cell::Empty && b.check_win(row, col));
+            if won {
+                return Err("Someone has already connected four, so the game is over");
+            }
+            if b.grid.iter().flatten().all(|&cell| cell != Cell::Empty) {
+                return Err("The board is full, so the game is over");
+            }
+
+            let columns = columns(&b.grid, |&cell| cell == Cell::Empty);
+            let order = drop_order(&columns, |dropped, cell| cell == Cell::Occupied(b.turn_order[dropped.len() % players]), |_| ())?
+                .ok_or("No order of turns stacks the discs up like this")?;
+            let mut played = Board::with_players(b.rows, b.cols, b.turn_order.clone())?;
+            for (col, _) in order {
+                played.insert_disc(col)?;
+            }
+            Ok(Position::ConnectFour(played))
+        }
+        // pieces belong to no one, so it only takes the right first mover and sharing out of the letters
+        Position::TootOtto(b) => {
+            if floating(&b.grid, |&cell| cell == TootCell::Empty) {
+                return Err("A piece is floating: pieces rest on the bottom row or on other pieces");
+            }
+            let won = (0..b.rows).flat_map(|row| (0..b.cols).map(move |col| (row, col)))
+                .any(|(row, col)| b.grid[row][col] != TootCell::Empty && matches!(b.check_win(row, col), Some(Winner::Player(_) | Winner::Draw)));
+            if won {
+                return Err("TOOT or OTTO is already spelled, so the game is over");
+            }
+            if b.is_draw() {
+                return Err("The board is full, so the game is over");
+            }
+
+            let columns = columns(&b.grid, |&cell| cell == TootCell::Empty);
+            let pieces: usize = columns.iter().map(Vec::len).sum();
+            let first = if pieces.is_multiple_of(2) {
+                b.current_turn
+            } else {
+                match b.current_turn {
+                    TootPlayer::Toot => TootPlayer::Otto,
+                    TootPlayer::Otto => TootPlayer::Toot,
+                }
+            };
+            let mut played = TootBoard::starting_with(b.rows, b.cols, first);
+            // each player only has so many of each letter, counted from the pieces on their turns so far
+            let share = played.pieces_left(first, Piece::T);
+            let used = |dropped: &[(usize, TootCell)], step: usize, letter: TootCell| {
+                dropped.iter().skip(step % 2).step_by(2).filter(|&&(_, cell)| cell == letter).count()
+            };
+            let order = drop_order(
+                &columns,
+                |dropped, cell| used(dropped, dropped.len(), cell) < share,
+                |dropped| used(dropped, 0, TootCell::Occupied(Piece::T)),
+            )?
+            .ok_or("No order of turns places these pieces without a player using more T's or O's than they have")?;
+            for (col, cell) in order {
+                if let TootCell::Occupied(piece) = cell {
+                    played.insert_piece(col, piece)?;
+                }
+            }
+            Ok(Position::TootOtto(played))
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SandboxProps {
+    #[prop_or_default]
+    pub position: Option<AttrValue>, // A shared position to start from
+}
+
+// Set up any position, check it could happen in a real game, then play it out or ask the engine about it
+#[function_component(Sandbox)]
+pub fn sandbox(props: &SandboxProps) -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let navigator = use_navigator().expect("pages are rendered inside the router");
+    let shared = props.position.as_deref().map(parse_position);
+    let position = use_state(|| match &shared {
+        Some(Ok(position)) => position.clone(),
+        _ => empty_connect4(Player::ALL[..2].to_vec(), 6, 7),
+    });
+    let brush = use_state(|| Brush::Disc(Player::Red));
+    let import_text = use_state(String::new);
+    let import_error: UseStateHandle<Option<&'static str>> = use_state(|| shared.and_then(Result::err));
+    let (ai_worker, ai_answer) = use_ai_worker();
+
+    let valid = use_memo((*position).clone(), check);
+    let icons = settings.connect4_icons();
+    let icon_for = move |player: Player| icons[Player::ALL.iter().position(|&p| p == player).unwrap_or(0)];
+
+    let switch_to = {
+        let position = position.clone();
+        let brush = brush.clone();
+        Callback::from(move |next: Position| {
+            brush.set(match &next {
+                Position::ConnectFour(_) => Brush::Disc(Player::Red),
+                Position::TootOtto(_) => Brush::Piece(Piece::T),
+            });
+            position.set(next);
+        })
+    };
+
+    let paint = {
+        let position = position.clone();
+        let brush = *brush;
+        Callback::from(move |(row, col): (usize, usize)| {
+            let mut next = (*position).clone();
+            match (&mut next, brush) {
+                (Position::ConnectFour(b), Brush::Empty) => b.grid[row][col] = Cell::Empty,
+                (Position::ConnectFour(b), Brush::Disc(player)) => b.grid[row][col] = Cell::Occupied(player),
+                (Position::TootOtto(b), Brush::Empty) => b.grid[row][col] = TootCell::Empty,
+                (Position::TootOtto(b), Brush::Piece(piece)) => b.grid[row][col] = TootCell::Occupied(piece),
+                _ => return,
+            }
+            position.set(next);
+        })
+    };
+
+    let set_turn = {
+        let position = position.clone();
+        Callback::from(move |next: Position| position.set(next))
+    };
+
+    // Hand the position, played out move by move, to the game page through its saved game, which it resumes on load
+    let play = {
+        let valid = valid.clone();
+        let settings = settings.clone();
+        let navigator = navigator.clone();
+        Callback::from(move |_| match &*valid {
+            Ok(Position::ConnectFour(b)) => {
+                // the position's own turn order, which Player2 moving first would rotate
+                let setup = Connect4Setup {
+                    players: b.turn_order.len(),
+                    seating: seating_from(&b.turn_order),
+                    rows: b.rows,
+                    cols: b.cols,
+                    first_mover: FirstMover::Player1,
+                    control: None,
+                };
+                saved_game::save(CONNECT4_GAME_KEY, &Connect4Saved { setup, board: b.clone(), clock: None });
+                navigator.push(&Route::Game);
+            }
+            Ok(Position::TootOtto(b)) => {
+                let setup = TootOttoSetup { rows: b.rows, cols: b.cols, first_mover: settings.first_mover, control: None };
+                let saved = TootOttoSaved { setup, player1: settings.player_as_toot_otto, board: b.clone(), clock: None };
+                saved_game::save(TOOT_OTTO_GAME_KEY, &saved);
+                navigator.push(&Route::TootOttoGame);
+            }
+            Err(_) => {}
+        })
+    };
+
+    let ask_engine = {
+        let position = position.clone();
+        let ai_answer = ai_answer.clone();
+        let ai_worker = ai_worker.clone();
+        Callback::from(move |_| {
+            if let Position::ConnectFour(b) = &*position {
+                ai_answer.set(None);
+                let board = b.clone();
+                ai_worker.send(|id| AiRequest::ConnectFour { id, board, difficulty: Difficulty::Hard, budget_ms: COMPUTER_BUDGET_MS });
+            }
+        })
+    };
+    // only an answer to the last question, about the position on screen, counts
+    let engine_move = match (&*ai_answer, &*position) {
+        (Some(reply @ AiResponse::ConnectFour { board, col, .. }), Position::ConnectFour(b)) if ai_worker.is_latest(reply) && board == b => {
+            Some(*col)
+        }
+        _ => None,
+    };
+
+    let on_import_input = {
+        let import_text = import_text.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            import_text.set(input.value());
+        })
+    };
+    let import = {
+        let import_text = import_text.clone();
+        let import_error = import_error.clone();
+        let switch_to = switch_to.clone();
+        Callback::from(move |_| match parse_position(&import_text) {
+            Ok(next) => {
+                import_error.set(None);
+                switch_to.emit(next);
+            }
+            Err(reason) => import_error.set(Some(reason)),
+        })
+    };
+
+    let shared_text = position_string(&position);
+    let share_link = format!("/sandbox/{}", shared_text);
+
+    // the game, its size and its players; changing any of them starts from an empty board
+    let (game_options, brushes, turns) = match &*position {
+        Position::ConnectFour(b) => {
+            let players = b.turn_order.len();
+            (
+                html! {
+                    <>
+                        <div class="radio-buttons">
+                            <text>{ lang.t("game.players") }</text>
+                            {
+                                for (2..=4).map(|n| {
+                                    let (rows, cols) = (b.rows, b.cols);
+                                    html! {
+                                        <label class="l-radio" for={format!("sandbox-players{}", n)}>
+                                            <input type="radio" id={format!("sandbox-players{}", n)} name="sandbox-players" checked={players == n} onclick={switch_to.reform(move |_| empty_connect4(Player::ALL[..n].to_vec(), rows, cols))} />
+                                            <span>{ n }</span>
+                                        </label>
+                                    }
+                                })
+                            }
+                        </div>
+                        <div class="radio-buttons">
+                            <text>{ lang.t("sandbox.size") }</text>
+                            {
+                                for CONNECT4_SIZES.iter().map(|&(rows, cols)| {
+                                    let turn_order = b.turn_order.clone();
+                                    html! {
+                                        <label class="l-radio" for={format!("sandbox-size{}x{}", rows, cols)}>
+                                            <input type="radio" id={format!("sandbox-size{}x{}", rows, cols)} name="sandbox-size" checked={(b.rows, b.cols) == (rows, cols)} onclick={switch_to.reform(move |_| empty_connect4(turn_order.clone(), rows, cols))} />
+                                            <span>{ format!("{}x{}", rows, cols) }</span>
+                                        </label>
+                                    }
+                                })
+                            }
+                        </div>
+                        <div class="radio-buttons">
+                            <text>{ lang.t("game.turn_order") }</text>
+                            {
+                                for b.turn_order.iter().enumerate().map(|(i, &player)| {
+                                    let name = seat_name(lang, player, players);
+                                    let mut next = b.clone();
+                                    next.turn_order.swap(i.saturating_sub(1), i);
+                                    html! {
+                                        <>
+                                            {
+                                                if i > 0 {
+                                                    html! {
+                                                        <button onclick={set_turn.reform(move |_| Position::ConnectFour(next.clone()))} aria-label={lang.f("game.move_earlier", &[("player", &name)])}>{ "<" }</button>
+                                                    }
+                                                } else {
+                                                    html! {}
+                                                }
+                                            }
+                                            <span style={format!("margin: 0 8px; border-bottom: 4px solid {};", player_color(player))}>{ name }</span>
+                                        </>
+                                    }
+                                })
+                            }
+                        </div>
+                    </>
+                },
+                std::iter::once((Brush::Empty, lang.t("sandbox.empty").to_owned()))
+                    .chain(b.turn_order.iter().map(|&p| (Brush::Disc(p), seat_name(lang, p, players).to_owned())))
+                    .collect::<Vec<_>>(),
+                b.turn_order.iter().map(|&p| {
+                    let mut next = b.clone();
+                    next.current_turn = p;
+                    (b.current_turn == p, seat_name(lang, p, players).to_owned(), Position::ConnectFour(next))
+                }).collect::<Vec<_>>(),
+            )
+        }
+        Position::TootOtto(b) => (
+            html! {
+                <div class="radio-buttons">
+                    <text>{ lang.t("sandbox.size") }</text>
+                    {
+                        for TOOT_OTTO_SIZES.iter().map(|&(rows, cols)| html! {
+                            <label class="l-radio" for={format!("sandbox-size{}x{}", rows, cols)}>
+                                <input type="radio" id={format!("sandbox-size{}x{}", rows, cols)} name="sandbox-size" checked={(b.rows, b.cols) == (rows, cols)} onclick={switch_to.reform(move |_| empty_toot_otto(rows, cols))} />
+                                <span>{ format!("{}x{}", rows, cols) }</span>
+                            </label>
+                        })
+                    }
+                </div>
+            },
+            vec![(Brush::Empty, lang.t("sandbox.empty").to_owned()), (Brush::Piece(Piece::T), "T".to_owned()), (Brush::Piece(Piece::O), "O".to_owned())],
+            [TootPlayer::Toot, TootPlayer::Otto].into_iter().map(|p| {
+                let mut next = b.clone();
+                next.current_turn = p;
+                (b.current_turn == p, if p == TootPlayer::Toot { "TOOT" } else { "OTTO" }.to_owned(), Position::TootOtto(next))
+            }).collect::<Vec<_>>(),
+        ),
+    };
+
+    let (rows, cols, container) = match &*position {
+        Position::ConnectFour(b) => (b.rows, b.cols, "container-connect4"),
+        Position::TootOtto(b) => (b.rows, b.cols, "container-toototto"),
+    };
+    let cells = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).map(|(row, col)| {
+        let (piece, label) = match &*position {
+            Position::ConnectFour(b) => match b.grid[row][col] {
+                Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                Cell::Occupied(player) => {
+                    let ring = if b.turn_order.len() > 2 { format!("border-radius: 50%; box-shadow: inset 0 0 0 5px {};", player_color(player)) } else { String::new() };
+                    (
+                        html! { <img src={icon_for(player)} alt="" style={ring} /> },
+                        seat_name(lang, player, b.turn_order.len()).to_owned(),
+                    )
+                }
+            },
+            Position::TootOtto(b) => match b.grid[row][col] {
+                TootCell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                TootCell::Occupied(piece) => (
+                    html! { <text>{ if piece == Piece::T { "T" } else { "O" } }</text> },
+                    format!("{:?}", piece),
+                ),
+            },
+        };
+        html! {
+            <button class="cell" aria-label={lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &label)])} onclick={paint.reform(move |_| (row, col))}>
+                { piece }
+            </button>
+        }
+    });
+
+    html! {
+        <div class="content_padding">
+            <Link<Route> to={Route::Home}>
+                <button class="btn-back">
+                    <span class="circle" aria-hidden="true">
+                        <span class="icon arrow"></span>
+                    </span>
+                    <span class="button-text">{ lang.t("nav.back_home") }</span>
+                </button>
+            </Link<Route>>
+            <h1 class="text_titles">{ lang.t("home.sandbox") }</h1>
+            <p>{ lang.t("sandbox.intro") }</p>
+            <div class="radio-buttons">
+                <text>{ lang.t("sandbox.game") }</text>
+                <label class="l-radio" for="sandbox-connect4">
+                    <input type="radio" id="sandbox-connect4" name="sandbox-game" checked={matches!(*position, Position::ConnectFour(_))} onclick={switch_to.reform(|_| empty_connect4(Player::ALL[..2].to_vec(), 6, 7))} />
+                    <span>{ GameKind::ConnectFour.name(lang) }</span>
+                </label>
+                <label class="l-radio" for="sandbox-toot-otto">
+                    <input type="radio" id="sandbox-toot-otto" name="sandbox-game" checked={matches!(*position, Position::TootOtto(_))} onclick={switch_to.reform(|_| empty_toot_otto(4, 6))} />
+                    <span>{ GameKind::TootOtto.name(lang) }</span>
+                </label>
+            </div>
+            { game_options }
+            <div class="radio-buttons">
+                <text>{ lang.t("sandbox.place") }</text>
+                {
+                    for brushes.into_iter().enumerate().map(|(i, (b, name))| html! {
+                        <label class="l-radio" for={format!("sandbox-brush{}", i)}>
+                            <input type="radio" id={format!("sandbox-brush{}", i)} name="sandbox-brush" checked={*brush == b} onclick={{ let brush = brush.clone(); Callback::from(move |_| brush.set(b)) }} />
+                            <span>{ name }</span>
+                        </label>
+                    })
+                }
+            </div>
+            <div class="radio-buttons">
+                <text>{ lang.t("sandbox.to_move") }</text>
+                {
+                    for turns.into_iter().enumerate().map(|(i, (checked, name, next))| html! {
+                        <label class="l-radio" for={format!("sandbox-turn{}", i)}>
+                            <input type="radio" id={format!("sandbox-turn{}", i)} name="sandbox-turn" checked={checked} onclick={set_turn.reform(move |_| next.clone())} />
+                            <span>{ name }</span>
+                        </label>
+                    })
+                }
+            </div>
+            <div class={container} style={board_style(cols)}>
+                <div class="grid" role="group" aria-label={lang.f("sandbox.board", &[("rows", &rows), ("cols", &cols)])}>
+                    { for cells }
+                </div>
+            </div>
+            {
+                match &*valid {
+                    Ok(_) => html! { <p aria-live="polite">{ lang.t("sandbox.reachable") }</p> },
+                    Err(reason) => html! { <p class="online-message" aria-live="polite">{ lang.translate(reason) }</p> },
+                }
+            }
+            <div class="sandbox-actions">
+                <button onclick={play} disabled={valid.is_err()}>{ lang.t("sandbox.play") }</button>
+                {
+                    match &*position {
+                        Position::ConnectFour(b) if b.turn_order.len() == 2 => html! {
+                            <button onclick={ask_engine} disabled={valid.is_err()}>{ lang.t("sandbox.ask_engine") }</button>
+                        },
+                        _ => html! { <text>{ lang.t("sandbox.engine_only") }</text> },
+                    }
+                }
+            </div>
+            {
+                match engine_move {
+                    Some(Some(col)) => html! { <p class="replay-review">{ lang.f("sandbox.engine_move", &[("col", &(col + 1))]) }</p> },
+                    Some(None) => html! { <p>{ lang.t("sandbox.engine_no_move") }</p> },
+                    None => html! {},
+                }
+            }
+            <h2>{ lang.t("sandbox.share") }</h2>
+            <p>
+                <input type="text" readonly=true value={shared_text} size="60" aria-label={lang.t("sandbox.as_text")} />
+                { " " }<a href={share_link}>{ lang.t("sandbox.link") }</a>
+            </p>
+            <p>
+                <input type="text" value={(*import_text).clone()} oninput={on_import_input} size="60" placeholder={lang.t("sandbox.paste")} aria-label={lang.t("sandbox.shared_position")} />
+                <button onclick={import}>{ lang.t("sandbox.load") }</button>
+            </p>
+            {
+                match *import_error {
+                    Some(reason) => html! { <p class="online-message" role="alert">{ lang.translate(reason) }</p> },
+                    None => html! {},
+                }
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rows of a 6x7 Connect Four board, bottom row last
+    fn connect4(order: &str, to_move: char, bottom: [&str; 2]) -> String {
+        format!("c4:{}:{}:.......,.......,.......,.......,{},{}", order, to_move, bottom[0], bottom[1])
+    }
+
+    #[test]
+    fn positions_survive_being_shared() {
+        for text in [
+            connect4("ry", 'r', [".......", "...y..."]),
+            connect4("gbry", 'b', [".......", "..rgyb."]),
+            "to:otto:......,......,......,..TO..".to_owned(),
+        ] {
+            let position = parse_position(&text).unwrap();
+            assert_eq!(position_string(&position), text);
+            assert_eq!(parse_position(&position_string(&position)), Ok(position));
+        }
+        assert_eq!(parse_position("c4:ry:r:......."), Err("The position's board isn't a size the game is played on"));
+        assert_eq!(parse_position("c4:rr:r:.......").unwrap_err(), "The turn order must name each player once");
+        assert_eq!(parse_position("chess:e4"), Err("That isn't a position from the sandbox"));
+    }
+
+    #[test]
+    fn old_links_give_the_number_of_players() {
+        let text = connect4("3", 'g', [".......", "...ry.."]);
+        let Ok(Position::ConnectFour(board)) = parse_position(&text) else {
+            panic!("{} should parse", text);
+        };
+        assert_eq!(board.turn_order, vec![Player::Red, Player::Yellow, Player::Green]);
+        assert_eq!(position_string(&Position::ConnectFour(board)), connect4("ryg", 'g', [".......", "...ry.."]));
+        assert_eq!(parse_position(&connect4("5", 'r', [".......", "......."])).unwrap_err(), "Connect Four is played by 2 to 4 players");
+    }
+
+    #[test]
+    fn discs_cannot_float() {
+        let position = parse_position(&connect4("ry", 'y', ["...r...", "......."])).unwrap();
+        assert_eq!(check(&position), Err("A disc is floating: discs rest on the bottom row or on other discs"));
+        let position = parse_position("to:otto:......,......,..T...,......").unwrap();
+        assert_eq!(check(&position), Err("A piece is floating: pieces rest on the bottom row or on other pieces"));
+    }
+
+    #[test]
+    fn disc_counts_follow_the_turn_order() {
+        let wrong = "The disc counts don't fit the turn order: the first player in it starts, then everyone takes turns, one disc at a time";
+        // Yellow starts, so one Red disc and no Yellow one can't happen
+        let position = parse_position(&connect4("yr", 'y', [".......", "...r..."])).unwrap();
+        assert_eq!(check(&position), Err(wrong));
+        // Green and Yellow have had their turn, but it is Yellow's go again
+        let position = parse_position(&connect4("gyr", 'y', [".......", "..gy..."])).unwrap();
+        assert_eq!(check(&position), Err(wrong));
+        let position = parse_position(&connect4("gyr", 'r', [".......", "..gy..."])).unwrap();
+        assert!(check(&position).is_ok());
+    }
+
+    #[test]
+    fn nobody_has_more_letters_than_they_were_given() {
+        // 13 T's means whoever started has played 7 of their 6
+        let position = parse_position("to:toot:......,T.....,TTTTTT,TTTTTT").unwrap();
+        assert_eq!(
+            check(&position),
+            Err("No order of turns places these pieces without a player using more T's or O's than they have")
+        );
+        let position = parse_position("to:toot:......,......,TTTTTT,TTTTTT").unwrap();
+        assert!(check(&position).is_ok());
+    }
+
+    #[test]
+    fn reachable_positions_are_played_out_to_the_same_grid() {
+        let position = parse_position(&connect4("ygr", 'r', ["...y...", "..ygrg."])).unwrap();
+        let Ok(Position::ConnectFour(played)) = check(&position) else {
+            panic!("the position can be reached");
+        };
+        let Position::ConnectFour(board) = &position else { unreachable!() };
+        assert_eq!((&played.grid, played.current_turn, played.moves.len()), (&board.grid, Player::Red, 5));
+
+        let position = parse_position("to:otto:......,......,...O..,..TOT.").unwrap();
+        let Ok(Position::TootOtto(played)) = check(&position) else {
+            panic!("the position can be reached");
+        };
+        let Position::TootOtto(board) = &position else { unreachable!() };
+        assert_eq!((&played.grid, played.current_turn, played.moves.len()), (&board.grid, TootPlayer::Otto, 4));
+    }
+}
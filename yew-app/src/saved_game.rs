@@ -0,0 +1,57 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use web_sys::{console, window, Storage};
+
+// localStorage keys of the unfinished game on each page
+pub const CONNECT4_GAME_KEY: &str = "connect4Game";
+pub const TOOT_OTTO_GAME_KEY: &str = "tootOttoGame";
+// Bump this when a saved game from the previous version can't be read by the current code.
+// Games saved by another version are dropped rather than half-loaded.
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    version: u32,
+    game: serde_json::Value,
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+pub fn save<T: Serialize>(key: &str, game: &T) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    let saved = Saved {
+        version: SAVE_VERSION,
+        game: serde_json::to_value(game).unwrap_or_default(),
+    };
+    if let Ok(json) = serde_json::to_string(&saved) {
+        let _ = storage.set_item(key, &json);
+    }
+}
+
+pub fn clear(key: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(key);
+    }
+}
+
+// The game saved under `key`, if there is one this version can play. `playable` checks what serde
+// can't, e.g. that the grid matches the board size; a game that fails any check is thrown away.
+pub fn load<T: DeserializeOwned>(key: &str, playable: impl Fn(&T) -> bool) -> Option<T> {
+    let json = storage()?.get_item(key).ok()??;
+    let saved = serde_json::from_str::<Saved>(&json).ok();
+    if let Some(saved) = saved.as_ref().filter(|saved| saved.version != SAVE_VERSION) {
+        console::warn_1(&format!("Dropping a game saved by version {} of the app", saved.version).into());
+        clear(key);
+        return None;
+    }
+    let game = saved.and_then(|saved| serde_json::from_value(saved.game).ok()).filter(|game| playable(game));
+    if game.is_none() {
+        console::warn_1(&"Dropping an unreadable saved game".into());
+        clear(key);
+    }
+    game
+}
@@ -0,0 +1,193 @@
+use crate::history::{load_history, GameKind, GameRecord, Source};
+use crate::i18n::Language;
+use crate::settings::Settings;
+use crate::Route;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+// The game history, once it has loaded
+#[hook]
+fn use_history() -> UseStateHandle<Option<(Vec<GameRecord>, Source)>> {
+    let history = use_state(|| None);
+    {
+        let history = history.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move { history.set(Some(load_history().await)) });
+        });
+    }
+    history
+}
+
+fn source_note(lang: Language, source: Source) -> Html {
+    match source {
+        Source::Server => html! {},
+        Source::Browser => html! { <p>{ lang.t("scores.browser_only") }</p> },
+    }
+}
+
+pub fn format_date(lang: Language, date_ms: u64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(date_ms as f64))
+        .to_locale_string(lang.code(), &JsValue::UNDEFINED)
+        .into()
+}
+
+// Player names are saved in English; see history_name in main.rs
+fn player_names(lang: Language, names: &[String], separator: &str) -> String {
+    names.iter().map(|name| lang.translate(name)).collect::<Vec<_>>().join(separator)
+}
+
+fn back_to_home(lang: Language) -> Html {
+    html! {
+        <Link<Route> to={Route::Home}>
+            <button class="btn-back">
+                <span class="circle" aria-hidden="true">
+                    <span class="icon arrow"></span>
+                </span>
+                <span class="button-text">{ lang.t("nav.back_home") }</span>
+            </button>
+        </Link<Route>>
+    }
+}
+
+// How Player1 has done against one opponent (or group of opponents) in one game
+struct Record {
+    game: GameKind,
+    opponents: Vec<String>,
+    wins: u32,
+    losses: u32,
+    draws: u32,
+}
+
+// Player1's results grouped by game and by who they played, in the order the opponents were first met
+fn records_by_opponent(games: &[GameRecord]) -> Vec<Record> {
+    let mut records: Vec<Record> = Vec::new();
+    for game in games {
+        let Some((player1, others)) = game.players.split_first() else {
+            continue;
+        };
+        let opponents = others.to_vec();
+        let index = match records.iter().position(|r| r.game == game.game && r.opponents == opponents) {
+            Some(index) => index,
+            None => {
+                records.push(Record { game: game.game, opponents, wins: 0, losses: 0, draws: 0 });
+                records.len() - 1
+            }
+        };
+        let record = &mut records[index];
+        match &game.winner {
+            Some(winner) if winner == player1 => record.wins += 1,
+            Some(_) => record.losses += 1,
+            None => record.draws += 1,
+        }
+    }
+    records
+}
+
+#[function_component(Scores)]
+pub fn scores() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let history = use_history();
+
+    let content = match &*history {
+        None => html! { <p>{ lang.t("scores.loading") }</p> },
+        Some((games, _)) if games.is_empty() => html! { <p>{ lang.t("scores.no_games") }</p> },
+        Some((games, source)) => html! {
+            <>
+                { source_note(lang, *source) }
+                <table class="history">
+                    <tr>
+                        <th>{ lang.t("scores.game") }</th>
+                        <th>{ lang.t("scores.player1_against") }</th>
+                        <th>{ lang.t("scores.won") }</th>
+                        <th>{ lang.t("scores.lost") }</th>
+                        <th>{ lang.t("scores.drawn") }</th>
+                        <th>{ lang.t("scores.win_rate") }</th>
+                    </tr>
+                    {
+                        for records_by_opponent(games).into_iter().map(|r| {
+                            let played = r.wins + r.losses + r.draws;
+                            html! {
+                                <tr>
+                                    <td>{ r.game.name(lang) }</td>
+                                    <td>{ player_names(lang, &r.opponents, " & ") }</td>
+                                    <td>{ r.wins }</td>
+                                    <td>{ r.losses }</td>
+                                    <td>{ r.draws }</td>
+                                    <td>{ format!("{:.0}%", 100.0 * r.wins as f64 / played as f64) }</td>
+                                </tr>
+                            }
+                        })
+                    }
+                </table>
+            </>
+        },
+    };
+
+    html! {
+        <div class="content_padding">
+            { back_to_home(lang) }
+            <h1 class="text_titles">{ lang.t("home.scores") }</h1>
+            { content }
+        </div>
+    }
+}
+
+#[function_component(History)]
+pub fn history() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let history = use_history();
+
+    let content = match &*history {
+        None => html! { <p>{ lang.t("scores.loading") }</p> },
+        Some((games, _)) if games.is_empty() => html! { <p>{ lang.t("scores.no_games") }</p> },
+        Some((games, source)) => html! {
+            <>
+                { source_note(lang, *source) }
+                <table class="history">
+                    <tr>
+                        <th>{ lang.t("scores.game") }</th>
+                        <th>{ lang.t("scores.players") }</th>
+                        <th>{ lang.t("scores.winner") }</th>
+                        <th>{ lang.t("scores.date") }</th>
+                        <th>{ lang.t("scores.moves") }</th>
+                        <th>{ lang.t("home.replay") }</th>
+                    </tr>
+                    {
+                        // newest first
+                        for games.iter().rev().map(|game| html! {
+                            <tr>
+                                <td>{ game.game.name(lang) }</td>
+                                <td>{ player_names(lang, &game.players, ", ") }</td>
+                                <td>{ game.winner.as_ref().map_or_else(|| lang.t("scores.draw").to_owned(), |winner| lang.translate(winner)) }</td>
+                                <td>{ format_date(lang, game.date_ms) }</td>
+                                <td>{ game.moves }</td>
+                                <td>
+                                    {
+                                        // games recorded before replays were saved can't be watched again
+                                        if game.replay.is_some() {
+                                            html! { <Link<Route> to={Route::ReplayGame { date_ms: game.date_ms }}>{ lang.t("scores.watch") }</Link<Route>> }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                </td>
+                            </tr>
+                        })
+                    }
+                </table>
+            </>
+        },
+    };
+
+    html! {
+        <div class="content_padding">
+            { back_to_home(lang) }
+            <h1 class="text_titles">{ lang.t("scores.history_title") }</h1>
+            { content }
+        </div>
+    }
+}
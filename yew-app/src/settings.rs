@@ -0,0 +1,263 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::rc::Rc;
+use web_sys::{console, window, Storage};
+use yew::prelude::*;
+
+pub use yew_app::ai::Difficulty;
+
+use crate::i18n::Language;
+use crate::icons::{self, Icon, DEFAULT_COMP_ICON, DEFAULT_PLAYER_ICON};
+
+// localStorage key the settings are saved under
+const STORAGE_KEY: &str = "appState";
+// Bump this when the saved format changes in a way `#[serde(default)]` can't absorb,
+// and teach `load_state` how to read the older version
+const STORAGE_VERSION: u32 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum PlayerAsTootOtto {
+    #[default]
+    PlayerToot,
+    PlayerOtto,
+}
+
+// Who makes the opening move, in both games
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum FirstMover {
+    #[default]
+    Player1,
+    Player2, // Player2, or the computer when playing against it
+}
+
+// The page's colours; styles.scss has each theme's palette
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    HighContrast, // Black and white, with player colours told apart with any colour vision
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub fn name(self, lang: Language) -> &'static str {
+        lang.t(match self {
+            Theme::Light => "theme.light",
+            Theme::Dark => "theme.dark",
+            Theme::HighContrast => "theme.high_contrast",
+        })
+    }
+
+    // The page's data-theme attribute, which picks the palette in styles.scss
+    fn id(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+}
+
+// The player's settings, shared by every page through a context.
+// Each field falls back to its default on its own if the saved value is missing or not understood,
+// so one bad field doesn't throw away the rest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppState {
+    #[serde(default, deserialize_with = "lenient")]
+    pub difficulty: Difficulty,
+    #[serde(default, deserialize_with = "lenient")]
+    pub player_icon: String, // An icon id from icons.rs
+    #[serde(default, deserialize_with = "lenient")]
+    pub comp_icon: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub player_as_toot_otto: PlayerAsTootOtto,
+    #[serde(default, deserialize_with = "lenient")]
+    pub first_mover: FirstMover,
+    #[serde(default, deserialize_with = "lenient")]
+    pub theme: Theme,
+    #[serde(default, deserialize_with = "lenient")]
+    pub muted: bool, // No sounds or vibration
+    // Until one is picked, the browser's language if there is a catalog for it
+    #[serde(default = "Language::from_browser", deserialize_with = "lenient")]
+    pub language: Language,
+}
+
+impl Default for AppState {
+    fn default() -> AppState {
+        AppState {
+            difficulty: Difficulty::default(),
+            player_icon: DEFAULT_PLAYER_ICON.to_owned(),
+            comp_icon: DEFAULT_COMP_ICON.to_owned(),
+            player_as_toot_otto: PlayerAsTootOtto::default(),
+            first_mover: FirstMover::default(),
+            theme: Theme::default(),
+            muted: false,
+            language: Language::from_browser(),
+        }
+    }
+}
+
+impl AppState {
+    // The icons of Player1 and Player2/Computer; an icon that no longer exists falls back to the default
+    pub fn icons(&self) -> (&'static Icon, &'static Icon) {
+        (
+            icons::find_or(&self.player_icon, DEFAULT_PLAYER_ICON),
+            icons::find_or(&self.comp_icon, DEFAULT_COMP_ICON),
+        )
+    }
+
+    // Connect Four icon urls in seat order (see Player::ALL): Player3 and Player4 get the first
+    // icons Player1 and Player2 did not pick
+    pub fn connect4_icons(&self) -> [&'static str; 4] {
+        let (player1, player2) = self.icons();
+        let picked = [player1.id, player2.id];
+        let mut unpicked = icons::unpicked(&picked).map(|icon| icon.url);
+        let player3 = unpicked.next().unwrap_or(player1.url);
+        let player4 = unpicked.next().unwrap_or(player2.url);
+        [player1.url, player2.url, player3, player4]
+    }
+}
+
+pub enum SettingsAction {
+    Difficulty(Difficulty),
+    PlayerIcon(String),
+    CompIcon(String),
+    PlayerAsTootOtto(PlayerAsTootOtto),
+    FirstMover(FirstMover),
+    Theme(Theme),
+    Muted(bool),
+    Language(Language),
+}
+
+impl Reducible for AppState {
+    type Action = SettingsAction;
+
+    fn reduce(self: Rc<Self>, action: SettingsAction) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            SettingsAction::Difficulty(difficulty) => state.difficulty = difficulty,
+            SettingsAction::PlayerIcon(icon) => state.player_icon = icon,
+            SettingsAction::CompIcon(icon) => state.comp_icon = icon,
+            SettingsAction::PlayerAsTootOtto(word) => state.player_as_toot_otto = word,
+            SettingsAction::FirstMover(first_mover) => state.first_mover = first_mover,
+            SettingsAction::Theme(theme) => state.theme = theme,
+            SettingsAction::Muted(muted) => state.muted = muted,
+            SettingsAction::Language(language) => state.language = language,
+        }
+        Rc::new(state)
+    }
+}
+
+// What pages get from `use_context::<Settings>()`: read it like an AppState, change it with `dispatch`
+pub type Settings = UseReducerHandle<AppState>;
+
+// Saved settings, tagged with the format version
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    version: u32,
+    settings: serde_json::Value,
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+fn save_state(state: &AppState) {
+    let Some(storage) = storage() else {
+        return; // e.g. storage disabled by the browser; the settings just won't outlive the page
+    };
+    let saved = Saved {
+        version: STORAGE_VERSION,
+        settings: serde_json::to_value(state).unwrap_or_default(),
+    };
+    if let Ok(json) = serde_json::to_string(&saved) {
+        let _ = storage.set_item(STORAGE_KEY, &json);
+    }
+}
+
+// The saved settings, or the defaults if there are none or they can't be read
+fn load_state() -> AppState {
+    let Some(json) = storage().and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten()) else {
+        return AppState::default();
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&json) else {
+        console::warn_1(&"Ignoring unreadable saved settings".into());
+        return AppState::default();
+    };
+    let settings = match serde_json::from_value::<Saved>(value.clone()) {
+        Ok(saved) if saved.version == STORAGE_VERSION => saved.settings,
+        Ok(saved) if saved.version == 1 => icons_from_v1(saved.settings),
+        Ok(saved) => {
+            console::warn_1(&format!("Ignoring saved settings from unknown version {}", saved.version).into());
+            return AppState::default();
+        }
+        // before versioning the settings were saved as a bare AppState, with version 1's icons
+        Err(_) => icons_from_v1(value),
+    };
+    serde_json::from_value(settings).unwrap_or_default()
+}
+
+// Version 1 saved the icons as enum variants, Option1-Option4; version 2 saves icon ids
+fn icons_from_v1(mut settings: serde_json::Value) -> serde_json::Value {
+    for field in ["player_icon", "comp_icon"] {
+        let id = match settings.get(field).and_then(|v| v.as_str()) {
+            Some("Option1") => "armor",
+            Some("Option2") => "sword",
+            Some("Option3") => "gem",
+            Some("Option4") => "heart",
+            _ => continue,
+        };
+        settings[field] = id.into();
+    }
+    settings
+}
+
+// Colour the whole page, including what's outside the app's own elements
+fn apply_theme(theme: Theme) {
+    if let Some(root) = window().and_then(|window| window.document()).and_then(|document| document.document_element()) {
+        let _ = root.set_attribute("data-theme", theme.id());
+    }
+}
+
+// Tell the browser, and screen readers, which language the page is in
+fn apply_language(language: Language) {
+    if let Some(root) = window().and_then(|window| window.document()).and_then(|document| document.document_element()) {
+        let _ = root.set_attribute("lang", language.code());
+    }
+}
+
+// Deserialize a field, using its default when the saved value doesn't fit
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsProviderProps {
+    pub children: Html,
+}
+
+// Loads the settings once, shares them with everything inside it and saves every change
+#[function_component(SettingsProvider)]
+pub fn settings_provider(props: &SettingsProviderProps) -> Html {
+    let settings = use_reducer(load_state);
+
+    {
+        let state = (*settings).clone();
+        use_effect_with(state, save_state);
+    }
+    use_effect_with(settings.theme, |theme| apply_theme(*theme));
+    use_effect_with(settings.language, |language| apply_language(*language));
+
+    html! {
+        <ContextProvider<Settings> context={settings}>
+            { props.children.clone() }
+        </ContextProvider<Settings>>
+    }
+}
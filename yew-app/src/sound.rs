@@ -0,0 +1,121 @@
+use crate::settings::{Settings, SettingsAction};
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+use web_sys::{window, AudioContext, AudioContextState, OscillatorType};
+use yew::prelude::*;
+
+// What the game pages make a noise about
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sound {
+    Drop,    // a disc or letter lands
+    Invalid, // e.g. a full column
+    Win,
+    Loss, // the computer, or the online opponent, won
+    Draw,
+}
+
+// A tone: its frequency in Hz, when it starts after the sound does and how long it lasts, in seconds
+type Note = (f32, f64, f64);
+
+impl Sound {
+    fn notes(self) -> (OscillatorType, &'static [Note]) {
+        match self {
+            Sound::Drop => (OscillatorType::Triangle, &[(196.0, 0.0, 0.09)]),
+            Sound::Invalid => (OscillatorType::Square, &[(110.0, 0.0, 0.08), (110.0, 0.12, 0.08)]),
+            // C E G C up
+            Sound::Win => (OscillatorType::Sine, &[(523.3, 0.0, 0.12), (659.3, 0.12, 0.12), (784.0, 0.24, 0.12), (1046.5, 0.36, 0.3)]),
+            // G E C down
+            Sound::Loss => (OscillatorType::Triangle, &[(392.0, 0.0, 0.18), (329.6, 0.18, 0.18), (261.6, 0.36, 0.4)]),
+            Sound::Draw => (OscillatorType::Sine, &[(440.0, 0.0, 0.15), (440.0, 0.22, 0.15)]),
+        }
+    }
+
+    // Vibration pattern in milliseconds: on, off, on...
+    fn vibration(self) -> &'static [u32] {
+        match self {
+            Sound::Drop => &[15],
+            Sound::Invalid => &[40, 40, 40],
+            Sound::Win => &[80, 50, 80, 50, 160],
+            Sound::Loss => &[300],
+            Sound::Draw => &[100, 60, 100],
+        }
+    }
+}
+
+// Browsers allow only a few audio contexts per page, so the one made for the first sound is kept
+thread_local! {
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+}
+
+fn context() -> Option<AudioContext> {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        if context.is_none() {
+            *context = AudioContext::new().ok();
+        }
+        context.clone()
+    })
+}
+
+fn beep(sound: Sound) -> Result<(), JsValue> {
+    let Some(context) = context() else {
+        return Ok(()); // no Web Audio in this browser
+    };
+    // a context made before the player touched the page starts out suspended
+    if context.state() == AudioContextState::Suspended {
+        let _ = context.resume()?;
+    }
+    let (wave, notes) = sound.notes();
+    let now = context.current_time();
+    for &(frequency, start, length) in notes {
+        let oscillator = context.create_oscillator()?;
+        oscillator.set_type(wave);
+        oscillator.frequency().set_value(frequency);
+        // fade each note in and out, so it doesn't click
+        let gain = context.create_gain()?;
+        let volume = gain.gain();
+        volume.set_value_at_time(0.0, now + start)?;
+        volume.linear_ramp_to_value_at_time(0.2, now + start + 0.01)?;
+        volume.exponential_ramp_to_value_at_time(0.001, now + start + length)?;
+        oscillator.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&context.destination())?;
+        oscillator.start_with_when(now + start)?;
+        oscillator.stop_with_when(now + start + length)?;
+    }
+    Ok(())
+}
+
+fn vibrate(sound: Sound) {
+    let Some(navigator) = window().map(|window| window.navigator()) else {
+        return;
+    };
+    // desktop browsers and Safari have no vibrate at all, and calling it there would throw
+    if !js_sys::Reflect::has(&navigator, &"vibrate".into()).unwrap_or(false) {
+        return;
+    }
+    let pattern: js_sys::Array = sound.vibration().iter().map(|&ms| JsValue::from(ms)).collect();
+    navigator.vibrate_with_pattern(&pattern);
+}
+
+// Play a sound, and buzz phones that can, unless the player muted the game in the settings
+pub fn play(sound: Sound, muted: bool) {
+    if muted {
+        return;
+    }
+    let _ = beep(sound);
+    vibrate(sound);
+}
+
+// Turns sounds and vibration off or on, for every page
+#[function_component(MuteToggle)]
+pub fn mute_toggle() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let muted = settings.muted;
+    let lang = settings.language;
+    let onclick = Callback::from(move |_| settings.dispatch(SettingsAction::Muted(!muted)));
+    html! {
+        <button class="mute-toggle" {onclick}>
+            { lang.t(if muted { "sound.off" } else { "sound.on" }) }
+        </button>
+    }
+}
@@ -36,6 +36,8 @@ pub struct Board {
     pub cols: usize,
     pub state: State,
     pub last_move: Option<(usize, usize)>, // Track the last move as (row, col)
+    #[serde(default)]
+    pub moves: Vec<(Player, usize, Piece)>, // Every move so far as (player, column, piece), oldest first
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -54,9 +56,17 @@ impl Board {
             cols,
             state: State::Running,
             last_move: None,
+            moves: Vec::new(),
         }
     }
 
+    // A new board where `first` makes the opening move
+    pub fn starting_with(rows: usize, cols: usize, first: Player) -> Board {
+        let mut board = Board::new(rows, cols);
+        board.current_turn = first;
+        board
+    }
+
 
     // pub fn display(&self) {
     //     for row in &self.grid {
@@ -119,18 +129,68 @@ impl Board {
     }
 
 
+    // The cells spelling TOOT or OTTO on the lines through the last move, once the game is over
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        if self.state == State::Running {
+            return None;
+        }
+        let (row, col) = self.last_move?;
+
+        let mut cells = Vec::new();
+        for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            // back up to the edge of the board, then collect the whole line through the last move
+            let (mut r, mut c) = (row as isize, col as isize);
+            while self.on_board(r - dr, c - dc) {
+                r -= dr;
+                c -= dc;
+            }
+            let mut line = Vec::new();
+            while self.on_board(r, c) {
+                line.push((r as usize, c as usize));
+                r += dr;
+                c += dc;
+            }
+
+            let letters: String = line.iter().map(|&(r, c)| match self.grid[r][c] {
+                Cell::Occupied(Piece::T) => 'T',
+                Cell::Occupied(Piece::O) => 'O',
+                Cell::Empty => '.',
+            }).collect();
+            for start in 0..letters.len().saturating_sub(3) {
+                if matches!(&letters[start..start + 4], "TOOT" | "OTTO") {
+                    cells.extend_from_slice(&line[start..start + 4]);
+                }
+            }
+        }
+
+        cells.sort();
+        cells.dedup();
+        if cells.is_empty() { None } else { Some(cells) }
+    }
+
+    fn on_board(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && row < self.rows as isize && col < self.cols as isize
+    }
+
     // Insert a piece into the specified column
     // Insert a piece into the specified column
     pub fn insert_piece(&mut self, col: usize, piece: Piece) -> Result<(), &'static str> {
         if col >= self.cols {
             return Err("Column out of bounds");
         }
+        if self.pieces_left(self.current_turn, piece) == 0 {
+            return Err(match piece {
+                Piece::T => "No T pieces left",
+                Piece::O => "No O pieces left",
+            });
+        }
 
         // Attempt to place the piece in the lowest empty cell in the specified column
         for row in (0..self.rows).rev() {
             if matches!(self.grid[row][col], Cell::Empty) {
                 self.grid[row][col] = Cell::Occupied(piece);
                 self.last_move = Some((row, col));
+                self.moves.push((self.current_turn, col, piece));
                 match self.check_win(row, col) {
                     Some(Winner::Player(player)) => {
                         self.state = State::Won(player);
@@ -174,6 +234,24 @@ impl Board {
     } 
 
 
+    // Pieces of one letter a player still has. As in the official game each player
+    // gets an equal number of Ts and Os, a quarter of the board each (6 of each on 4x6).
+    pub fn pieces_left(&self, player: Player, piece: Piece) -> usize {
+        let share = (self.rows * self.cols).div_ceil(4);
+        let played = self.moves.iter().filter(|&&(p, _, q)| p == player && q == piece).count();
+        share.saturating_sub(played)
+    }
+
+    // A player ran out of time, so their opponent wins
+    pub fn flag(&mut self, player: Player) {
+        if self.state == State::Running {
+            self.state = State::Won(match player {
+                Player::Toot => Player::Otto,
+                Player::Otto => Player::Toot,
+            });
+        }
+    }
+
     // Switch the current player's turn
     pub fn switch_turn(&mut self) {
         self.current_turn = match self.current_turn {
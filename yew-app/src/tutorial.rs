@@ -0,0 +1,344 @@
+use crate::i18n::Language;
+use crate::settings::Settings;
+use crate::sound::{self, Sound};
+use crate::{board_style, player_color, player_label};
+use yew::prelude::*;
+use yew_app::connect4::{self, Board, Player, State};
+use yew_app::toot_otto::{self, Board as TootBoard, Piece, Player as TootPlayer, State as TootState};
+
+// What the learner's move has to do to pass a step
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Goal {
+    Drop,  // any move the engine accepts
+    Win,   // win the game with it
+    Block, // leave the opponent no winning reply, and don't win it for them either
+}
+
+// A position made by playing these moves from an empty board. The learner always moves first, as
+// Red or TOOT, so an even number of moves leaves it their turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Opening {
+    ConnectFour(&'static [usize]),
+    TootOtto(&'static [(usize, Piece)]),
+}
+
+struct Step {
+    title: &'static str,
+    task: &'static str,
+    hint: &'static str, // shown when a Win step's move doesn't win
+    done: &'static str,
+    opening: Opening,
+    goal: Goal,
+}
+
+const STEPS: [Step; 5] = [
+    Step {
+        title: "tutorial.drop.title",
+        task: "tutorial.drop.task",
+        hint: "",
+        done: "tutorial.drop.done",
+        opening: Opening::ConnectFour(&[]),
+        goal: Goal::Drop,
+    },
+    // Yellow has three stacked in column 4
+    Step {
+        title: "tutorial.block.title",
+        task: "tutorial.block.task",
+        hint: "",
+        done: "tutorial.block.done",
+        opening: Opening::ConnectFour(&[0, 3, 6, 3, 5, 3]),
+        goal: Goal::Block,
+    },
+    // Red has three on the diagonal up from the bottom of column 2, and column 5 fills to meet it
+    Step {
+        title: "tutorial.diagonal.title",
+        task: "tutorial.diagonal.task",
+        hint: "tutorial.diagonal.hint",
+        done: "tutorial.diagonal.done",
+        opening: Opening::ConnectFour(&[1, 2, 2, 3, 4, 3, 3, 4, 0, 4]),
+        goal: Goal::Win,
+    },
+    // T O O _ _ T along the bottom
+    Step {
+        title: "tutorial.toot.title",
+        task: "tutorial.toot.task",
+        hint: "tutorial.toot.hint",
+        done: "tutorial.toot.done",
+        opening: Opening::TootOtto(&[(0, Piece::T), (1, Piece::O), (2, Piece::O), (5, Piece::T)]),
+        goal: Goal::Win,
+    },
+    // O T T _ _ O along the bottom: an O in column 4 would be OTTO, whoever drops it
+    Step {
+        title: "tutorial.otto.title",
+        task: "tutorial.otto.task",
+        hint: "",
+        done: "tutorial.otto.done",
+        opening: Opening::TootOtto(&[(0, Piece::O), (1, Piece::T), (2, Piece::T), (5, Piece::O)]),
+        goal: Goal::Block,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Move {
+    col: usize,
+    piece: Option<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TutorialBoard {
+    ConnectFour(Board),
+    TootOtto(TootBoard),
+}
+
+impl TutorialBoard {
+    fn start(opening: Opening) -> TutorialBoard {
+        let mut board = match opening {
+            Opening::ConnectFour(moves) => {
+                let mut board = Board::new(6, 7);
+                for &col in moves {
+                    board.insert_disc(col).expect("tutorial openings are legal");
+                }
+                TutorialBoard::ConnectFour(board)
+            }
+            Opening::TootOtto(moves) => {
+                let mut board = TootBoard::new(4, 6);
+                for &(col, piece) in moves {
+                    board.insert_piece(col, piece).expect("tutorial openings are legal");
+                }
+                TutorialBoard::TootOtto(board)
+            }
+        };
+        // the opening's last move is the opponent's, but there's nothing to point out yet
+        match &mut board {
+            TutorialBoard::ConnectFour(b) => b.last_move = None,
+            TutorialBoard::TootOtto(b) => b.last_move = None,
+        }
+        board
+    }
+
+    fn play(&mut self, mv: Move) -> Result<(), &'static str> {
+        match (self, mv.piece) {
+            (TutorialBoard::ConnectFour(b), _) => b.insert_disc(mv.col),
+            (TutorialBoard::TootOtto(b), Some(piece)) => b.insert_piece(mv.col, piece),
+            (TutorialBoard::TootOtto(_), None) => Err("Toot and Otto moves need a piece"),
+        }
+    }
+
+    fn learner_won(&self) -> bool {
+        match self {
+            TutorialBoard::ConnectFour(b) => b.state == State::Won(Player::Red),
+            TutorialBoard::TootOtto(b) => b.state == TootState::Won(TootPlayer::Toot),
+        }
+    }
+
+    fn opponent_won(&self) -> bool {
+        match self {
+            TutorialBoard::ConnectFour(b) => b.state == State::Won(Player::Yellow),
+            TutorialBoard::TootOtto(b) => b.state == TootState::Won(TootPlayer::Otto),
+        }
+    }
+
+    // The first move the opponent could win with from here, found by trying every one with the engine
+    fn winning_reply(&self) -> Option<Move> {
+        let replies: Vec<Move> = match self {
+            TutorialBoard::ConnectFour(b) => (0..b.cols).map(|col| Move { col, piece: None }).collect(),
+            TutorialBoard::TootOtto(b) => (0..b.cols)
+                .flat_map(|col| [Piece::T, Piece::O].map(|piece| Move { col, piece: Some(piece) }))
+                .collect(),
+        };
+        replies.into_iter().find(|&reply| {
+            let mut board = self.clone();
+            board.play(reply).is_ok() && board.opponent_won()
+        })
+    }
+
+    fn winning_line(&self) -> Vec<(usize, usize)> {
+        match self {
+            TutorialBoard::ConnectFour(b) => b.winning_line(),
+            TutorialBoard::TootOtto(b) => b.winning_line(),
+        }
+        .unwrap_or_default()
+    }
+}
+
+fn opponent_name(lang: Language, board: &TutorialBoard) -> String {
+    match board {
+        TutorialBoard::ConnectFour(_) => player_label(lang, Player::Yellow).to_owned(),
+        TutorialBoard::TootOtto(_) => "OTTO".to_owned(),
+    }
+}
+
+fn describe(lang: Language, mv: Move) -> String {
+    match mv.piece {
+        Some(piece) => lang.f("tutorial.piece_move", &[("piece", &format!("{:?}", piece)), ("col", &(mv.col + 1))]),
+        None => lang.f("tutorial.disc_move", &[("col", &(mv.col + 1))]),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Playing,
+    Missed, // the move is on the board to see why, until the step is tried again
+    Passed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Lesson {
+    step: usize,
+    board: TutorialBoard,
+    status: Status,
+    message: Option<String>,
+}
+
+impl Lesson {
+    fn start(step: usize) -> Lesson {
+        Lesson { step, board: TutorialBoard::start(STEPS[step].opening), status: Status::Playing, message: None }
+    }
+}
+
+// Scripted positions that teach both games a move at a time, each move checked by the real engine
+#[function_component(Tutorial)]
+pub fn tutorial() -> Html {
+    let settings = use_context::<Settings>().expect("pages are rendered inside the SettingsProvider");
+    let lang = settings.language;
+    let muted = settings.muted;
+    let lesson = use_state(|| Lesson::start(0));
+    let piece = use_state(|| Piece::T);
+
+    let open = {
+        let lesson = lesson.clone();
+        Callback::from(move |step: usize| lesson.set(Lesson::start(step)))
+    };
+
+    let on_move = {
+        let lesson = lesson.clone();
+        Callback::from(move |mv: Move| {
+            let mut l = (*lesson).clone();
+            if l.status != Status::Playing {
+                return;
+            }
+            let step = &STEPS[l.step];
+            let before = l.board.clone();
+            if let Err(e) = l.board.play(mv) {
+                sound::play(Sound::Invalid, muted);
+                l.message = Some(lang.translate(e));
+                lesson.set(l);
+                return;
+            }
+            let miss = match step.goal {
+                Goal::Drop => None,
+                Goal::Win => (!l.board.learner_won()).then(|| lang.t(step.hint).to_owned()),
+                Goal::Block if l.board.learner_won() => None,
+                Goal::Block if l.board.opponent_won() => {
+                    Some(lang.f("tutorial.gives_away", &[("player", &opponent_name(lang, &l.board))]))
+                }
+                Goal::Block => l.board.winning_reply().map(|reply| {
+                    lang.f("tutorial.threat", &[("player", &opponent_name(lang, &before)), ("move", &describe(lang, reply))])
+                }),
+            };
+            match miss {
+                Some(why) => {
+                    sound::play(Sound::Loss, muted);
+                    l.status = Status::Missed;
+                    l.message = Some(why);
+                }
+                None => {
+                    sound::play(if l.board.learner_won() { Sound::Win } else { Sound::Drop }, muted);
+                    l.status = Status::Passed;
+                    l.message = Some(lang.t(step.done).to_owned());
+                }
+            }
+            lesson.set(l);
+        })
+    };
+
+    let l = (*lesson).clone();
+    let step = &STEPS[l.step];
+    let (rows, cols, container, last) = match &l.board {
+        TutorialBoard::ConnectFour(b) => (b.rows, b.cols, "container-connect4", b.last_move),
+        TutorialBoard::TootOtto(b) => (b.rows, b.cols, "container-toototto", b.last_move),
+    };
+    let toot_otto = matches!(l.board, TutorialBoard::TootOtto(_));
+    let winning = l.board.winning_line();
+    let icons = settings.connect4_icons();
+    let cells = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).map(|(row, col)| {
+        let (content, label) = match &l.board {
+            TutorialBoard::ConnectFour(b) => match b.grid[row][col] {
+                connect4::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                connect4::Cell::Occupied(player) => (
+                    html! { <img src={icons[Player::ALL.iter().position(|&p| p == player).unwrap_or(0)]} alt="" /> },
+                    player_label(lang, player).to_owned(),
+                ),
+            },
+            TutorialBoard::TootOtto(b) => match b.grid[row][col] {
+                toot_otto::Cell::Empty => (html! {}, lang.t("board.empty").to_owned()),
+                toot_otto::Cell::Occupied(piece) => (
+                    html! { <text>{ if piece == Piece::T { "T" } else { "O" } }</text> },
+                    format!("{:?}", piece),
+                ),
+            },
+        };
+        let mv = Move { col, piece: toot_otto.then_some(*piece) };
+        html! {
+            <button
+                class={classes!("cell", winning.contains(&(row, col)).then_some("winning"), (last == Some((row, col))).then_some("last-move"))}
+                aria-label={lang.f("board.cell", &[("row", &(row + 1)), ("col", &(col + 1)), ("piece", &label)])}
+                onclick={on_move.reform(move |_| mv)}
+                disabled={l.status != Status::Playing}
+            >
+                { content }
+            </button>
+        }
+    });
+
+    let index = l.step;
+    let last_step = index + 1 == STEPS.len();
+    html! {
+        <section class="tutorial">
+            <p>{ lang.f("tutorial.progress", &[("step", &(l.step + 1)), ("count", &STEPS.len())]) }</p>
+            <h2 class="text_titles">
+                {
+                    if toot_otto {
+                        html! { <span>{ lang.t(step.title) }</span> }
+                    } else {
+                        html! { <span style={format!("border-bottom: 4px solid {};", player_color(Player::Red))}>{ lang.t(step.title) }</span> }
+                    }
+                }
+            </h2>
+            <p>{ lang.t(step.task) }</p>
+            {
+                if toot_otto {
+                    let select = |p: Piece| { let piece = piece.clone(); Callback::from(move |_| piece.set(p)) };
+                    html! {
+                        <p>
+                            <button onclick={select(Piece::T)} aria-pressed={(*piece == Piece::T).to_string()}>{ "T" }</button>
+                            <button onclick={select(Piece::O)} aria-pressed={(*piece == Piece::O).to_string()}>{ "O" }</button>
+                        </p>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <div class={container} style={board_style(cols)}>
+                <div class="grid" role="group" aria-label={lang.f("tutorial.board", &[("rows", &rows), ("cols", &cols)])}>
+                    { for cells }
+                </div>
+            </div>
+            <p class="replay-review" aria-live="polite">{ l.message.clone().unwrap_or_default() }</p>
+            <div class="sandbox-actions">
+                <button onclick={open.reform(move |_| index - 1)} disabled={index == 0}>{ lang.t("tutorial.previous") }</button>
+                <button onclick={open.reform(move |_| index)}>{ lang.t("tutorial.try_again") }</button>
+                <button onclick={open.reform(move |_| index + 1)} disabled={l.status != Status::Passed || last_step}>
+                    { lang.t("tutorial.next") }
+                </button>
+            </div>
+            {
+                if last_step && l.status == Status::Passed {
+                    html! { <p>{ lang.t("tutorial.finished") }</p> }
+                } else {
+                    html! {}
+                }
+            }
+        </section>
+    }
+}
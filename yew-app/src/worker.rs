@@ -0,0 +1,99 @@
+use crate::ai::{Ai, Difficulty, DEFAULT_DEPTH};
+use crate::clock::BrowserTimeSource;
+use crate::connect4::Board;
+use crate::toot_otto::{Board as TootBoard, Cell as TootCell, Piece};
+use gloo_worker::{HandlerId, Worker, WorkerScope};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Where trunk puts the loader for the `ai_worker` binary (see index.html)
+pub const AI_WORKER_LOADER: &str = "/ai_worker_loader.js";
+
+// A position for the computer to move in. The id and board come back with the answer, so the page can
+// tell an answer for the game on screen from a late one for a game that has since been restarted,
+// and two answers in a row for the same position (e.g. the opening move of two new games) apart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AiRequest {
+    ConnectFour { id: u32, board: Board, difficulty: Difficulty, budget_ms: u32 },
+    TootOtto { id: u32, board: TootBoard, difficulty: Difficulty, budget_ms: u32 },
+}
+
+// The computer's move, or None if it couldn't find one (e.g. the board is full)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AiResponse {
+    ConnectFour { id: u32, board: Board, col: Option<usize> },
+    TootOtto { id: u32, board: TootBoard, mv: Option<(usize, Piece)> },
+}
+
+impl AiResponse {
+    // The id of the request this answers
+    pub fn id(&self) -> u32 {
+        match self {
+            AiResponse::ConnectFour { id, .. } | AiResponse::TootOtto { id, .. } => *id,
+        }
+    }
+}
+
+// Runs the computer players in a Web Worker, so a long search doesn't freeze the page
+pub struct AiWorker {
+    ai: Ai,
+}
+
+impl Worker for AiWorker {
+    type Message = ();
+    type Input = AiRequest;
+    type Output = AiResponse;
+
+    fn create(_scope: &WorkerScope<Self>) -> Self {
+        AiWorker { ai: Ai::new(DEFAULT_DEPTH) }
+    }
+
+    fn update(&mut self, _scope: &WorkerScope<Self>, _msg: Self::Message) {}
+
+    fn received(&mut self, scope: &WorkerScope<Self>, msg: Self::Input, id: HandlerId) {
+        let response = match msg {
+            AiRequest::ConnectFour { id, board, difficulty, budget_ms } => {
+                let col = self.connect_four(&board, difficulty, budget_ms);
+                AiResponse::ConnectFour { id, board, col }
+            }
+            AiRequest::TootOtto { id, board, difficulty, .. } => {
+                let mv = toot_otto(&board, difficulty);
+                AiResponse::TootOtto { id, board, mv }
+            }
+        };
+        scope.respond(id, response);
+    }
+}
+
+impl AiWorker {
+    fn connect_four(&mut self, board: &Board, difficulty: Difficulty, budget_ms: u32) -> Option<usize> {
+        let budget = Duration::from_millis(budget_ms as u64);
+        let searched = match difficulty {
+            Difficulty::Hard => self.ai.choose(board, budget, &BrowserTimeSource),
+            _ => None,
+        };
+        // easy games, and boards the search can't handle, get the old random players
+        searched.or_else(|| {
+            let mut b = board.clone();
+            let played = match difficulty {
+                Difficulty::Hard => b.computer_move_hard(board.last_move.map_or(board.cols / 2, |(_row, col)| col)),
+                _ => b.computer_move(),
+            };
+            played.ok().and(b.last_move).map(|(_row, col)| col)
+        })
+    }
+}
+
+// TOOT-OTTO has no search yet: easy is random and hard plays near the last move
+fn toot_otto(board: &TootBoard, difficulty: Difficulty) -> Option<(usize, Piece)> {
+    let mut b = board.clone();
+    let played = match difficulty {
+        Difficulty::Hard => b.computer_move_hard(board.last_move.map_or(board.cols / 2, |(_row, col)| col)),
+        _ => b.computer_move(),
+    };
+    let (row, col) = played.ok().and(b.last_move)?;
+    match b.grid[row][col] {
+        TootCell::Occupied(piece) => Some((col, piece)),
+        TootCell::Empty => None,
+    }
+}
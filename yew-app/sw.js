@@ -0,0 +1,91 @@
+// Service worker: keeps a copy of the app so it starts, and plays against the computer, offline.
+// Trunk gives the wasm, js and css bundles hashed names, so they're found in index.html and the
+// AI worker's loader rather than listed here. Bump CACHE to throw away everything cached before.
+const CACHE = "game-center-v1";
+
+const FILES = [
+    "/",
+    "/manifest.webmanifest",
+    "/ai_worker_loader.js",
+    "/assets/SupplyCenter-0W9nz.ttf",
+    "/static/app-icon-192.png",
+    "/static/app-icon-512.png",
+    // the disc icons, as in src/icons.rs
+    "/static/armor.png",
+    "/static/sword.png",
+    "/static/bow.png",
+    "/static/gem.png",
+    "/static/heart.png",
+];
+
+// Every file a page or script refers to by an absolute path, e.g. "/yew-app-1a2b3c_bg.wasm"
+async function referencedFiles(url) {
+    const response = await fetch(url, { cache: "no-cache" });
+    if (!response.ok) {
+        return [];
+    }
+    const text = await response.text();
+    const found = text.matchAll(/["'](\/[^"'?#]+\.(?:js|wasm|css))["']/g);
+    return Array.from(found, (match) => match[1]);
+}
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(
+        (async () => {
+            const bundles = [...(await referencedFiles("/")), ...(await referencedFiles("/ai_worker_loader.js"))];
+            const cache = await caches.open(CACHE);
+            await cache.addAll([...new Set([...FILES, ...bundles])]);
+            await self.skipWaiting();
+        })()
+    );
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        (async () => {
+            for (const name of await caches.keys()) {
+                if (name !== CACHE) {
+                    await caches.delete(name);
+                }
+            }
+            await self.clients.claim();
+        })()
+    );
+});
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    // the game server is somewhere else, and online play needs the real thing
+    if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    if (request.mode === "navigate") {
+        // pages: the newest app when online; offline, the cached one, which routes any path itself
+        event.respondWith(
+            fetch(request)
+                .then((response) => {
+                    if (response.ok) {
+                        const copy = response.clone();
+                        caches.open(CACHE).then((cache) => cache.put("/", copy));
+                    }
+                    return response;
+                })
+                .catch(() => caches.match("/"))
+        );
+        return;
+    }
+    // everything else: the cached copy, or fetch and keep it
+    event.respondWith(
+        caches.match(request).then(
+            (cached) =>
+                cached ||
+                fetch(request).then((response) => {
+                    if (response.ok) {
+                        const copy = response.clone();
+                        caches.open(CACHE).then((cache) => cache.put(request, copy));
+                    }
+                    return response;
+                })
+        )
+    );
+});